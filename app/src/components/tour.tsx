@@ -123,7 +123,7 @@ export default function Tour() {
     const { sessionKey, isActive: sessionActive, createSessionKey, isLoading: sessionLoading, isRestoring } = useSessionKey()
     const { connection } = useConnection()
     const wallet = useWallet()
-    const { initializeUser, delegateUser, checkUserDelegation } = useMagicplaceProgram()
+    const { initializeUser, delegateUser, checkUserDelegation, delegateProfile, undelegateProfile, checkProfileDelegation } = useMagicplaceProgram()
     const { balance: sessionBalance, topupRequest, clearTopupRequest, topup, refreshBalance } = useSessionBalance()
 
     // UI state
@@ -255,6 +255,7 @@ export default function Tour() {
             setError("Wallet not properly connected")
             return
         }
+        const mainWallet = wallet.publicKey
 
         setIsProcessing(true)
         setError(null)
@@ -271,8 +272,9 @@ export default function Tour() {
                     try {
                         const bal = await connection.getBalance(keypair.publicKey)
                         const status = await checkUserDelegation(keypair.publicKey)
+                        const profileStatus = await checkProfileDelegation(mainWallet)
                         
-                        if (bal >= 0.005 * LAMPORTS_PER_SOL && status === "delegated") {
+                        if (bal >= 0.005 * LAMPORTS_PER_SOL && status === "delegated" && profileStatus === "delegated") {
                             // Already fully set up, animate through steps
                             setStepStatus(prev => ({ ...prev, authorize: true, fund: true, init: true, delegate: true }))
                             setSetupStep(null)
@@ -283,13 +285,15 @@ export default function Tour() {
                     } catch {}
                     return true
                 },
-                onCreateAccount: async (keypair, owner, signature, message) => {
+                onCreateAccount: async (keypair, owner, signature, message, nonce, expiresAt) => {
                     setStepStatus(prev => ({ ...prev, authorize: true }))
                     
                     // Check what we need to do
                     let needsFunding = true
                     let needsInit = true
                     let needsDelegate = true
+                    let needsProfileDelegate = true
+                    let profileDelegated = false
 
                     try {
                         const bal = await connection.getBalance(keypair.publicKey)
@@ -297,6 +301,9 @@ export default function Tour() {
                         const status = await checkUserDelegation(keypair.publicKey)
                         if (status === "delegated") { needsInit = false; needsDelegate = false }
                         else if (status === "undelegated") { needsInit = false }
+                        // The profile is shared by every session key of the wallet, so it may already be on the ER
+                        profileDelegated = (await checkProfileDelegation(owner)) === "delegated"
+                        needsProfileDelegate = !profileDelegated
                     } catch {}
 
                     // Fund
@@ -320,8 +327,13 @@ export default function Tour() {
                     // Initialize
                     setSetupStep("initializing")
                     if (needsInit) {
+                        // Sessions are registered on the base layer, so a delegated profile comes back first
+                        if (profileDelegated) {
+                            await undelegateProfile()
+                            needsProfileDelegate = true
+                        }
                         try {
-                            await initializeUser(keypair, owner, signature, message)
+                            await initializeUser(keypair, owner, signature, message, nonce, expiresAt)
                         } catch (e) {
                             if (!String(e).includes("already in use")) throw e
                         }
@@ -333,6 +345,9 @@ export default function Tour() {
                     if (needsDelegate) {
                         await delegateUser(keypair, owner)
                     }
+                    if (needsProfileDelegate) {
+                        await delegateProfile(keypair, owner)
+                    }
                     setStepStatus(prev => ({ ...prev, delegate: true }))
 
                     return "done"
@@ -349,7 +364,7 @@ export default function Tour() {
         } finally {
             setIsProcessing(false)
         }
-    }, [wallet, connection, createSessionKey, checkUserDelegation, initializeUser, delegateUser, actions])
+    }, [wallet, connection, createSessionKey, checkUserDelegation, initializeUser, delegateUser, checkProfileDelegation, delegateProfile, undelegateProfile, actions])
 
     // =========================================================================
     // State 5: OnboardingComplete - All set up, explain features
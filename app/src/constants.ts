@@ -5,6 +5,13 @@
 export const CANVAS_RES = 524288; // 2^19 - pixels per dimension
 export const SHARD_DIMENSION = 90; // 90×90 pixels per shard
 export const SHARDS_PER_DIM = Math.ceil(CANVAS_RES / SHARD_DIMENSION); // 5826 shards per dimension
+
+// The last row and column of shards overhang the canvas; only their top-left extent is paintable
+export const EDGE_SHARD_DIMENSION = CANVAS_RES - (SHARDS_PER_DIM - 1) * SHARD_DIMENSION; // 38 pixels
+export function shardExtent(shardX: number, shardY: number): { width: number; height: number } {
+    const side = (shard: number) => (shard === SHARDS_PER_DIM - 1 ? EDGE_SHARD_DIMENSION : SHARD_DIMENSION);
+    return { width: side(shardX), height: side(shardY) };
+}
 export const TILE_SIZE = 512; // Standard tile size
 export const MAX_REGION_SIZE = 10000; // Maximum pixels in a region query
 
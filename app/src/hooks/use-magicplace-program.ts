@@ -4,7 +4,7 @@ import { Program, AnchorProvider, setProvider } from "@coral-xyz/anchor";
 import { Connection, Keypair, PublicKey, Transaction, VersionedTransaction, SystemProgram, ComputeBudgetProgram } from "@solana/web3.js";
 import { type Magicplace } from "../idl/magicplace";
 import IDL from "../idl/magicplace.json";
-import { SHARD_DIMENSION, SHARDS_PER_DIM } from "../constants";
+import { SHARD_DIMENSION, SHARDS_PER_DIM, shardExtent } from "../constants";
 import { useSessionKey } from "./use-session-key";
 import { useRpcSettings, getWsEndpoint } from "./use-rpc-settings";
 import { BN } from "@coral-xyz/anchor"; // Ensure BN is available
@@ -45,12 +45,24 @@ const SHARD_SEED = Buffer.from("shard");
 // Seed prefix for session account PDAs (must match contract: b"session")
 const SESSION_SEED = Buffer.from("session");
 
+// Seed prefix for wallet profile PDAs (must match contract: b"wallet")
+const PROFILE_SEED = Buffer.from("wallet");
+
+// Seed prefix for shard deed PDAs (must match contract: b"deed")
+const DEED_SEED = Buffer.from("deed");
+
+// Seed for the game config PDA (must match contract: b"config")
+const CONFIG_SEED = Buffer.from("config");
+
+// Seed for the treasury PDA (must match contract: b"treasury")
+const TREASURY_SEED = Buffer.from("treasury");
+
 // Delegation Program ID
 const DELEGATION_PROGRAM_ID = new PublicKey("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
 
 // Cost estimation constants (in SOL)
-// Shard account: 8 + 2 + 2 + 8192 + 32 + 1 = 8237 bytes  
-// Rent-exempt minimum for ~8.3KB = ~0.058 SOL (at 6.96 lamports/byte)
+// Shard account: 8 + 8162 = 8170 bytes (discriminator + packed zero-copy data)
+// Rent-exempt minimum for ~8.2KB = ~0.058 SOL (at 6.96 lamports/byte)
 export const SHARD_RENT_SOL = 0.06;  // Slightly rounded up for safety
 export const TX_FEE_SOL = 0.0005;    // ~5000 lamports per transaction with priority fee
 export const DELEGATION_TX_FEE_SOL = 0.001; // Delegation CPI is more expensive
@@ -92,6 +104,49 @@ export function deriveSessionPDA(sessionKey: PublicKey): PublicKey {
     return pda;
 }
 
+/**
+ * Derive the PDA for the wallet profile that holds a main wallet's cooldown and session keys
+ */
+export function deriveProfilePDA(mainWallet: PublicKey): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+        [PROFILE_SEED, mainWallet.toBuffer()],
+        new PublicKey(IDL.address)
+    );
+    return pda;
+}
+
+/**
+ * Derive the PDA for the deed that records who owns the shard at (shardX, shardY)
+ */
+export function deriveDeedPDA(shardX: number, shardY: number): PublicKey {
+    const shardXBytes = Buffer.alloc(2);
+    shardXBytes.writeUInt16LE(shardX);
+    const shardYBytes = Buffer.alloc(2);
+    shardYBytes.writeUInt16LE(shardY);
+
+    const [pda] = PublicKey.findProgramAddressSync(
+        [DEED_SEED, shardXBytes, shardYBytes],
+        new PublicKey(IDL.address)
+    );
+    return pda;
+}
+
+/**
+ * Derive the PDA for the global game config
+ */
+export function deriveConfigPDA(): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync([CONFIG_SEED], new PublicKey(IDL.address));
+    return pda;
+}
+
+/**
+ * Derive the PDA for the treasury that collects shard fees
+ */
+export function deriveTreasuryPDA(): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync([TREASURY_SEED], new PublicKey(IDL.address));
+    return pda;
+}
+
 /**
  * Hook to interact with the Magicplace program on Solana.
  * Provides functions to manage shards and pixels.
@@ -526,13 +581,17 @@ export function useMagicplaceProgram() {
      * @param sessionKeypair - The session keypair (derived from first signature)
      * @param mainWallet - The main wallet public key
      * @param authSignature - The authorization signature from main wallet (second signature)
-     * @param authMessage - The message that was signed (for Ed25519 verification)
+     * @param authMessage - The canonical message that was signed (for Ed25519 verification)
+     * @param nonce - Authorization nonce bound into the message; must exceed the wallet's last one
+     * @param expiresAt - Unix timestamp (seconds) after which the authorization is rejected
      */
     const initializeUser = useCallback(async (
         sessionKeypair: Keypair,
         mainWallet: PublicKey,
         authSignature: Uint8Array,
-        authMessage?: string
+        authMessage: string,
+        nonce: number,
+        expiresAt: number
     ): Promise<string> => {
         if (!program) {
             throw new Error("Program not initialized");
@@ -545,9 +604,9 @@ export function useMagicplaceProgram() {
             // Import Ed25519Program for signature verification
             const { Ed25519Program, SYSVAR_INSTRUCTIONS_PUBKEY } = await import("@solana/web3.js");
             
-            // Generate the authorization message if not provided
-            const message = authMessage || `Authorize session key: ${sessionKeypair.publicKey.toBase58()} for wallet: ${mainWallet.toBase58()} on Magicplace`;
-            const messageBytes = new TextEncoder().encode(message);
+            // The program rebuilds the message from the session key, nonce and expiry and
+            // rejects the transaction unless the signed bytes match exactly
+            const messageBytes = new TextEncoder().encode(authMessage);
             
             // Create Ed25519 signature verification instruction
             // This MUST be the first instruction in the transaction
@@ -559,8 +618,15 @@ export function useMagicplaceProgram() {
             
             // Build the program instruction
             const programIx = await program.methods
-                .initializeUser(mainWallet, Array.from(authSignature) as number[])
-                .accounts({
+                .initializeUser(
+                    mainWallet,
+                    Array.from(authSignature) as number[],
+                    new BN(nonce),
+                    new BN(expiresAt)
+                )
+                .accountsPartial({
+                    user: deriveSessionPDA(sessionKeypair.publicKey),
+                    profile: deriveProfilePDA(mainWallet),
                     authority: sessionKeypair.publicKey,
                     // @ts-ignore
                     instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
@@ -659,6 +725,64 @@ export function useMagicplaceProgram() {
         }
     }, [program, connection]);
 
+    /**
+     * Delegate the main wallet's profile to Ephemeral Rollups.
+     * Placements charge cooldown against the profile, so it must be delegated alongside the session.
+     * Only needed once per wallet; signed by any session key registered on the profile.
+     * 
+     * @param sessionKeypair - A session keypair registered on the profile
+     * @param mainWallet - The main wallet public key
+     */
+    const delegateProfile = useCallback(async (
+        sessionKeypair: Keypair,
+        mainWallet: PublicKey
+    ): Promise<string> => {
+        if (!program) {
+            throw new Error("Program not initialized");
+        }
+
+        setIsLoading(true);
+        setError(null);
+
+        try {
+            // Priority fee instruction
+            const priorityFeeIx = ComputeBudgetProgram.setComputeUnitPrice({
+                microLamports: PRIORITY_FEE_MICRO_LAMPORTS,
+            });
+
+            const tx = await program.methods
+                .delegateProfile(mainWallet)
+                .accounts({
+                    authority: sessionKeypair.publicKey,
+                    // profile PDA is auto-derived from mainWallet
+                })
+                .preInstructions([priorityFeeIx])
+                .transaction();
+
+            // Set up transaction
+            tx.feePayer = sessionKeypair.publicKey;
+            const { blockhash, lastValidBlockHeight } = await connection.getLatestBlockhash();
+            tx.recentBlockhash = blockhash;
+
+            // Sign with session keypair
+            tx.sign(sessionKeypair);
+
+            // Send transaction
+            const signature = await connection.sendRawTransaction(tx.serialize(), {
+                skipPreflight: true, // Required for delegation CPI
+            });
+            await connection.confirmTransaction({ signature, blockhash, lastValidBlockHeight }, "confirmed");
+
+            return signature;
+        } catch (err) {
+            const message = err instanceof Error ? err.message : "Failed to delegate profile";
+            setError(message);
+            throw err;
+        } finally {
+            setIsLoading(false);
+        }
+    }, [program, connection]);
+
     /**
      * Check if a user session account is delegated to Ephemeral Rollups
      */
@@ -680,6 +804,78 @@ export function useMagicplaceProgram() {
         }
     }, [connection]);
 
+    /**
+     * Return the main wallet's profile from Ephemeral Rollups to the base layer.
+     * Sessions are registered on the base layer, so this runs before initializeUser while the
+     * profile is delegated; delegateProfile moves it back once the session is registered.
+     * Resolves once the profile is owned by the program again.
+     */
+    const undelegateProfile = useCallback(async (): Promise<string> => {
+        if (!program || !erProvider || !wallet.publicKey) {
+            throw new Error("Wallet not connected or ER not available");
+        }
+
+        setIsLoading(true);
+        setError(null);
+
+        try {
+            // Build transaction using base program, signed by the main wallet
+            let tx = await program.methods
+                .undelegateProfile()
+                .accounts({
+                    mainWallet: wallet.publicKey,
+                    // profile PDA is auto-derived from mainWallet
+                })
+                .transaction();
+
+            // Set up for ER connection
+            tx.feePayer = wallet.publicKey;
+            tx.recentBlockhash = (await erConnection.getLatestBlockhash()).blockhash;
+            tx = await erProvider.wallet.signTransaction(tx);
+
+            // Send using raw connection
+            const txHash = await erConnection.sendRawTransaction(tx.serialize(), {
+                skipPreflight: true,
+            });
+            await erConnection.confirmTransaction(txHash, "confirmed");
+
+            // Poll for up to 30 seconds until the undelegation lands on the base layer
+            const profilePDA = deriveProfilePDA(wallet.publicKey);
+            const programId = new PublicKey(IDL.address);
+            for (let i = 0; i < 60; i++) {
+                const accountInfo = await connection.getAccountInfo(profilePDA);
+                if (accountInfo?.owner.equals(programId)) {
+                    return txHash;
+                }
+                await new Promise(resolve => setTimeout(resolve, 500));
+            }
+            throw new Error("Profile did not return to the base layer in time. Please try again.");
+        } catch (err) {
+            const message = err instanceof Error ? err.message : "Failed to undelegate profile";
+            setError(message);
+            throw err;
+        } finally {
+            setIsLoading(false);
+        }
+    }, [program, erProvider, erConnection, connection, wallet.publicKey]);
+
+    /**
+     * Check if a main wallet's profile is delegated to Ephemeral Rollups
+     */
+    const checkProfileDelegation = useCallback(async (mainWallet: PublicKey): Promise<DelegationStatus> => {
+        try {
+            const accountInfo = await connection.getAccountInfo(deriveProfilePDA(mainWallet));
+
+            if (!accountInfo) {
+                return "not-initialized";
+            }
+
+            return accountInfo.owner.equals(DELEGATION_PROGRAM_ID) ? "delegated" : "undelegated";
+        } catch (err) {
+            return "undelegated";
+        }
+    }, [connection]);
+
     // ========================================
     // Pixel Placement Functions
     // ========================================
@@ -707,9 +903,14 @@ export function useMagicplaceProgram() {
             // So the session account is derived from wallet.publicKey
             const tx = await program.methods
                 .placePixel(shardX, shardY, px, py, color)
-                .accounts({
-                    signer: wallet.publicKey,
+                .accountsPartial({
+                    shard: deriveShardPDA(shardX, shardY),
+                    deed: deriveDeedPDA(shardX, shardY),
                     // session PDA auto-derived from signer
+                    profile: deriveProfilePDA(wallet.publicKey),
+                    config: deriveConfigPDA(),
+                    pass: null,
+                    signer: wallet.publicKey,
                 })
                 .rpc();
 
@@ -739,9 +940,14 @@ export function useMagicplaceProgram() {
         try {
             const tx = await program.methods
                 .erasePixel(shardX, shardY, px, py)
-                .accounts({
-                    signer: wallet.publicKey,
+                .accountsPartial({
+                    shard: deriveShardPDA(shardX, shardY),
+                    deed: deriveDeedPDA(shardX, shardY),
                     // session PDA auto-derived from signer
+                    profile: deriveProfilePDA(wallet.publicKey),
+                    config: deriveConfigPDA(),
+                    pass: null,
+                    signer: wallet.publicKey,
                 })
                 .rpc();
 
@@ -771,6 +977,10 @@ export function useMagicplaceProgram() {
             throw new Error("Session program or key not available");
         }
 
+        if (!wallet.publicKey) {
+            throw new Error("Wallet not connected");
+        }
+
         if (color < 1 || color > 255) {
             console.error(`[placePixelOnER] Invalid color: ${color}`);
             throw new Error(`Invalid color: ${color}. Must be 1-255`);
@@ -786,7 +996,13 @@ export function useMagicplaceProgram() {
             console.log("[placePixelOnER] Building instruction...");
             const placeIx = await sessionProgram.methods
                 .placePixel(shardX, shardY, px, py, color)
-                .accounts({
+                .accountsPartial({
+                    shard: deriveShardPDA(shardX, shardY),
+                    deed: deriveDeedPDA(shardX, shardY),
+                    // Cooldown is charged against the main wallet's profile, delegated alongside the session
+                    profile: deriveProfilePDA(wallet.publicKey),
+                    config: deriveConfigPDA(),
+                    pass: null,
                     signer: sessionKey.keypair.publicKey,
                 })
                 .instruction();
@@ -830,7 +1046,7 @@ export function useMagicplaceProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [sessionProgram, sessionKey.keypair, erConnection]);
+    }, [sessionProgram, sessionKey.keypair, wallet.publicKey, erConnection]);
 
     /**
      * Erase a pixel on Ephemeral Rollups (when shard is delegated)
@@ -841,6 +1057,10 @@ export function useMagicplaceProgram() {
             throw new Error("Session program or key not available");
         }
 
+        if (!wallet.publicKey) {
+            throw new Error("Wallet not connected");
+        }
+
         const { shardX, shardY } = getShardForPixel(px, py);
 
         setIsLoading(true);
@@ -850,9 +1070,14 @@ export function useMagicplaceProgram() {
             // Build instruction using session program for IDL
             const eraseIx = await sessionProgram.methods
                 .erasePixel(shardX, shardY, px, py)
-                .accounts({
-                    signer: sessionKey.keypair.publicKey,
+                .accountsPartial({
+                    shard: deriveShardPDA(shardX, shardY),
+                    deed: deriveDeedPDA(shardX, shardY),
                     // session -> auto-derived from signer
+                    profile: deriveProfilePDA(wallet.publicKey),
+                    config: deriveConfigPDA(),
+                    pass: null,
+                    signer: sessionKey.keypair.publicKey,
                 })
                 .instruction();
 
@@ -877,7 +1102,7 @@ export function useMagicplaceProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [sessionProgram, sessionKey.keypair, erConnection]);
+    }, [sessionProgram, sessionKey.keypair, wallet.publicKey, erConnection]);
 
     /**
      * Place multiple pixels in bulk on Ephemeral Rollups (when shard is delegated)
@@ -886,7 +1111,7 @@ export function useMagicplaceProgram() {
      * 
      * @param shardX - The shard X coordinate (0-5825)
      * @param shardY - The shard Y coordinate (0-5825)
-     * @param pixels - Array of pixels to place (max 50), each with localX, localY, color
+     * @param pixels - Array of pixels to place (at most the config's maxBulkPixels), each with localX, localY, color
      * @returns Transaction hash
      */
     const placePixelsBulkOnER = useCallback(async (
@@ -901,6 +1126,10 @@ export function useMagicplaceProgram() {
             throw new Error("Session program or key not available");
         }
 
+        if (!wallet.publicKey) {
+            throw new Error("Wallet not connected");
+        }
+
         if (pixels.length === 0) {
             throw new Error("No pixels to place");
         }
@@ -909,13 +1138,14 @@ export function useMagicplaceProgram() {
             throw new Error(`Too many pixels: ${pixels.length}. Maximum is ${COOLDOWN_LIMIT}`);
         }
 
-        // Validate all pixels
+        // Validate all pixels against the on-canvas part of the shard
+        const extent = shardExtent(shardX, shardY);
         for (const pixel of pixels) {
-            if (pixel.localX < 0 || pixel.localX >= SHARD_DIMENSION) {
-                throw new Error(`Invalid localX: ${pixel.localX}. Must be 0-${SHARD_DIMENSION - 1}`);
+            if (pixel.localX < 0 || pixel.localX >= extent.width) {
+                throw new Error(`Invalid localX: ${pixel.localX}. Must be 0-${extent.width - 1}`);
             }
-            if (pixel.localY < 0 || pixel.localY >= SHARD_DIMENSION) {
-                throw new Error(`Invalid localY: ${pixel.localY}. Must be 0-${SHARD_DIMENSION - 1}`);
+            if (pixel.localY < 0 || pixel.localY >= extent.height) {
+                throw new Error(`Invalid localY: ${pixel.localY}. Must be 0-${extent.height - 1}`);
             }
             if (pixel.color < 1 || pixel.color > 255) {
                 throw new Error(`Invalid color: ${pixel.color}. Must be 1-255`);
@@ -934,7 +1164,12 @@ export function useMagicplaceProgram() {
 
             const bulkIx = await sessionProgram.methods
                 .placePixelsBulk(shardX, shardY, bulkPixels)
-                .accounts({
+                .accountsPartial({
+                    shard: deriveShardPDA(shardX, shardY),
+                    deed: deriveDeedPDA(shardX, shardY),
+                    profile: deriveProfilePDA(wallet.publicKey),
+                    config: deriveConfigPDA(),
+                    pass: null,
                     signer: sessionKey.keypair.publicKey,
                 })
                 .instruction();
@@ -975,7 +1210,7 @@ export function useMagicplaceProgram() {
             setError(message);
             throw err;
         }
-    }, [sessionProgram, sessionKey.keypair, erConnection]);
+    }, [sessionProgram, sessionKey.keypair, wallet.publicKey, erConnection]);
 
     // ========================================
     // Ephemeral Rollups Shard Functions
@@ -1219,9 +1454,13 @@ export function useMagicplaceProgram() {
                 // and sign with session key
                 const initIx = await sessionProgram.methods
                     .initializeShard(shardX, shardY)
-                    .accounts({
-                        authority: sessionKey.keypair.publicKey,
+                    .accountsPartial({
+                        shard: shardPDA,
+                        deed: deriveDeedPDA(shardX, shardY),
                         // session -> auto-derived from authority (session key)
+                        config: deriveConfigPDA(),
+                        treasury: deriveTreasuryPDA(),
+                        authority: sessionKey.keypair.publicKey,
                     })
                     .instruction();
 
@@ -1464,6 +1703,9 @@ export function useMagicplaceProgram() {
         initializeUser,
         delegateUser,
         checkUserDelegation, // Exporting this function
+        delegateProfile,
+        undelegateProfile,
+        checkProfileDelegation,
         deriveSessionPDA,
         fetchSessionAccount,
         checkCanPlacePixel,
@@ -2,6 +2,7 @@ import { useCallback, useEffect, useMemo, useState, createContext, useContext, t
 import { useWallet } from "@solana/wallet-adapter-react";
 import { Keypair, PublicKey, Transaction, VersionedTransaction } from "@solana/web3.js";
 import * as nacl from "tweetnacl";
+import IDL from "../idl/magicplace.json";
 
 /**
  * Session key state
@@ -33,14 +34,16 @@ export interface CreateSessionKeyOptions {
     salt?: string;
     /** 
      * Callback to create the on-chain session account after getting signatures.
-     * Called with (sessionKeypair, mainWallet, authSignature, authMessage).
-     * Should call initializeUser from useMagicplaceProgram.
+     * Called with (sessionKeypair, mainWallet, authSignature, authMessage, nonce, expiresAt).
+     * Should call initializeUser from useMagicplaceProgram with the same nonce and expiry.
      */
     onCreateAccount?: (
         sessionKeypair: import("@solana/web3.js").Keypair,
         mainWallet: import("@solana/web3.js").PublicKey,
         authSignature: Uint8Array,
-        authMessage: string
+        authMessage: string,
+        nonce: number,
+        expiresAt: number
     ) => Promise<string>;
     /**
      * Optional callback after key is derived but before authorization.
@@ -59,6 +62,11 @@ const STORAGE_KEY_PREFIX = "pixelworld_session_key_";
  */
 const DEFAULT_SESSION_DURATION = 24 * 60 * 60 * 1000;
 
+/**
+ * How long a signed authorization stays usable for initialize_user (in seconds)
+ */
+const AUTH_VALIDITY_SECONDS = 5 * 60;
+
 /**
  * Generates the message for deriving the session keypair.
  * This is the first signature - used to deterministically create the session key.
@@ -67,13 +75,30 @@ function generateDerivationMessage(walletPubkey: PublicKey): string {
     return `Create session key for Pixelworld\nWallet: ${walletPubkey.toBase58()}`;
 }
 
+/**
+ * Cluster tag the program binds into session authorizations.
+ * Read from the IDL, which scripts/anchor-build.sh generates with the same cluster feature as the deployed program.
+ */
+const CLUSTER_TAG: string = JSON.parse(IDL.constants.find(c => c.name === "CLUSTER_TAG")!.value);
+
 /**
  * Generates the authorization message for the program.
  * This is the second signature - proves the main wallet authorized this specific session key.
- * This message format MUST match what the Solana program expects.
+ * This message format MUST match session_auth_message in the Solana program byte-for-byte.
  */
-function generateAuthorizationMessage(sessionKeyPubkey: PublicKey, mainWalletPubkey: PublicKey): string {
-    return `Authorize session key: ${sessionKeyPubkey.toBase58()} for wallet: ${mainWalletPubkey.toBase58()} on Magicplace`;
+export function generateAuthorizationMessage(
+    sessionKeyPubkey: PublicKey,
+    nonce: number,
+    expiresAt: number
+): string {
+    return [
+        "Magicplace session authorization",
+        `program: ${IDL.address}`,
+        `session: ${sessionKeyPubkey.toBase58()}`,
+        `cluster: ${CLUSTER_TAG}`,
+        `nonce: ${nonce}`,
+        `expires: ${expiresAt}`,
+    ].join("\n");
 }
 
 /**
@@ -240,7 +265,14 @@ export function SessionKeyProvider({ children }: { children: ReactNode }) {
             if (proceed) {
                 // SIGNATURE 2: Authorize this specific session key
                 // This proves the main wallet authorized THIS session key (not just any key)
-                const authMessage = generateAuthorizationMessage(keypair.publicKey, wallet.publicKey);
+                // Nonces must increase per wallet, so a millisecond timestamp works across devices
+                const nonce = Date.now();
+                const authExpiresAt = Math.floor(nonce / 1000) + AUTH_VALIDITY_SECONDS;
+                const authMessage = generateAuthorizationMessage(
+                    keypair.publicKey,
+                    nonce,
+                    authExpiresAt
+                );
                 const authMessageBytes = new TextEncoder().encode(authMessage);
                 
                 // Request second signature from wallet (popup 2)
@@ -248,7 +280,7 @@ export function SessionKeyProvider({ children }: { children: ReactNode }) {
                 
                 // STEP 3: Create on-chain session account (if callback provided)
                 if (onCreateAccount) {
-                    await onCreateAccount(keypair, wallet.publicKey, authSignature, authMessage);
+                    await onCreateAccount(keypair, wallet.publicKey, authSignature, authMessage, nonce, authExpiresAt);
                 }
             }
             
@@ -14,160 +14,197 @@ export type Magicplace = {
   },
   "instructions": [
     {
-      "name": "commitShard",
+      "name": "buyCooldownPass",
       "docs": [
-        "Commit shard state from ER to base layer"
+        "Buy (or extend) a cooldown pass for the signing main wallet",
+        "Shard passes pay the shard owner in SOL or project tokens at the deed's price;",
+        "global passes pay the treasury in SOL at the config price"
       ],
       "discriminator": [
-        85,
-        249,
-        246,
-        67,
-        192,
-        89,
-        165,
-        50
+        12,
+        138,
+        103,
+        146,
+        66,
+        32,
+        58,
+        177
       ],
       "accounts": [
         {
-          "name": "payer",
-          "writable": true,
-          "signer": true
-        },
-        {
-          "name": "shard",
+          "name": "pass",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
-                  104,
+                  112,
                   97,
-                  114,
-                  100
+                  115,
+                  115
                 ]
               },
               {
-                "kind": "arg",
-                "path": "shardX"
+                "kind": "account",
+                "path": "buyer"
               },
               {
                 "kind": "arg",
-                "path": "shardY"
+                "path": "scope"
               }
             ]
           }
         },
         {
-          "name": "magicProgram",
-          "address": "Magic11111111111111111111111111111111111111"
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
         },
         {
-          "name": "magicContext",
+          "name": "treasury",
+          "docs": [
+            "Receives payment for global passes"
+          ],
           "writable": true,
-          "address": "MagicContext1111111111111111111111111111111"
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Deed of the shard in scope; required for shard passes"
+          ],
+          "optional": true
+        },
+        {
+          "name": "shardOwner",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "buyerTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "ownerTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "buyer",
+          "docs": [
+            "Main wallet buying the pass"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
         }
       ],
       "args": [
         {
-          "name": "shardX",
-          "type": "u16"
+          "name": "scope",
+          "type": {
+            "defined": {
+              "name": "passScope"
+            }
+          }
         },
         {
-          "name": "shardY",
-          "type": "u16"
+          "name": "payment",
+          "type": {
+            "defined": {
+              "name": "passPayment"
+            }
+          }
         }
       ]
     },
     {
-      "name": "delegateShard",
+      "name": "buyShard",
       "docs": [
-        "Delegate an existing shard to Ephemeral Rollups",
-        "This should be called after initialize_shard in a separate transaction"
+        "Buy a listed shard",
+        "The price is escrowed into the listing PDA, which is then closed to the seller,",
+        "so ownership and payment move together or not at all. Earnings accrued so far go to the seller"
       ],
       "discriminator": [
-        193,
-        43,
-        22,
-        82,
-        105,
-        20,
-        98,
-        22
+        137,
+        179,
+        211,
+        7,
+        144,
+        42,
+        17,
+        116
       ],
       "accounts": [
         {
-          "name": "authority",
+          "name": "shard",
           "docs": [
-            "The authority requesting delegation"
+            "only its committed foreign_pixels counter is read"
           ],
-          "writable": true,
-          "signer": true
-        },
-        {
-          "name": "bufferPda",
-          "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  98,
-                  117,
-                  102,
-                  102,
-                  101,
-                  114
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "pda"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            ],
-            "program": {
-              "kind": "const",
-              "value": [
-                55,
-                86,
-                190,
-                231,
-                187,
-                241,
-                178,
-                93,
-                156,
-                164,
-                97,
-                165,
-                93,
-                69,
-                109,
-                254,
-                60,
-                140,
-                218,
-                139,
-                104,
-                66,
-                16,
-                9,
-                139,
-                59,
-                136,
-                184,
-                153,
-                69,
-                121,
-                115
-              ]
-            }
+            ]
           }
         },
         {
-          "name": "delegationRecordPda",
+          "name": "deed",
           "writable": true,
           "pda": {
             "seeds": [
@@ -176,80 +213,75 @@ export type Magicplace = {
                 "value": [
                   100,
                   101,
-                  108,
                   101,
-                  103,
-                  97,
-                  116,
-                  105,
-                  111,
-                  110
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "pda"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            ],
-            "program": {
-              "kind": "account",
-              "path": "delegationProgram"
-            }
+            ]
           }
         },
         {
-          "name": "delegationMetadataPda",
+          "name": "listing",
+          "docs": [
+            "Escrows the buyer's payment, then closes to the seller with price + rent"
+          ],
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  100,
-                  101,
                   108,
-                  101,
-                  103,
-                  97,
+                  105,
+                  115,
                   116,
                   105,
-                  111,
                   110,
-                  45,
-                  109,
-                  101,
-                  116,
-                  97,
-                  100,
-                  97,
-                  116,
-                  97
+                  103
                 ]
               },
               {
-                "kind": "account",
-                "path": "pda"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            ],
-            "program": {
-              "kind": "account",
-              "path": "delegationProgram"
-            }
+            ]
           }
         },
         {
-          "name": "pda",
+          "name": "seller",
+          "writable": true,
+          "relations": [
+            "listing"
+          ]
+        },
+        {
+          "name": "earnings",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
-                  104,
+                  101,
                   97,
                   114,
-                  100
+                  110,
+                  105,
+                  110,
+                  103,
+                  115
                 ]
               },
               {
@@ -264,15 +296,64 @@ export type Magicplace = {
           }
         },
         {
-          "name": "ownerProgram",
-          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
-        },
-        {
-          "name": "delegationProgram",
-          "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
-        },
-        {
-          "name": "systemProgram",
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "docs": [
+            "Only needed when earnings are owed, like the token accounts below"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  105,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "sellerTokenAccount",
+          "docs": [
+            "Receives the outgoing owner's unclaimed earnings"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "buyer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
           "address": "11111111111111111111111111111111"
         }
       ],
@@ -284,123 +365,125 @@ export type Magicplace = {
         {
           "name": "shardY",
           "type": "u16"
+        },
+        {
+          "name": "maxPrice",
+          "type": "u64"
         }
       ]
     },
     {
-      "name": "delegateUser",
+      "name": "cancelListing",
       "docs": [
-        "Delegate a user session account to Ephemeral Rollups",
-        "This should be called after initialize_user in a separate transaction"
+        "Withdraw a listing; its rent goes back to the seller"
       ],
       "discriminator": [
-        237,
-        220,
-        199,
-        24,
-        59,
         41,
-        247,
-        24
+        183,
+        50,
+        232,
+        230,
+        233,
+        157,
+        70
       ],
       "accounts": [
         {
-          "name": "user",
-          "docs": [
-            "The session account to delegate"
-          ],
+          "name": "listing",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
+                  108,
+                  105,
                   115,
-                  101,
-                  115,
-                  115,
+                  116,
                   105,
-                  111,
-                  110
+                  110,
+                  103
                 ]
               },
               {
-                "kind": "account",
-                "path": "authority"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
             ]
           }
         },
         {
-          "name": "authority",
-          "docs": [
-            "The session key authority"
-          ],
+          "name": "seller",
           "writable": true,
-          "signer": true
+          "signer": true,
+          "relations": [
+            "listing"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
         },
         {
-          "name": "bufferPda",
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "claimAndPaint",
+      "docs": [
+        "Claim a shard if nobody has yet and paint a first batch of pixels on it, on the base layer",
+        "The first painter to land pays rent and the platform fee and becomes both creator and",
+        "deed owner, so the batch is cooldown-free. If the shard already exists the call only",
+        "paints, and only for the deed owner; anyone else has lost the race and gets",
+        "ShardAlreadyClaimed. Append delegate_shard to the same transaction to move it to the ER."
+      ],
+      "discriminator": [
+        112,
+        193,
+        243,
+        177,
+        168,
+        5,
+        86,
+        196
+      ],
+      "accounts": [
+        {
+          "name": "shard",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  98,
-                  117,
-                  102,
-                  102,
-                  101,
-                  114
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "pda"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            ],
-            "program": {
-              "kind": "const",
-              "value": [
-                55,
-                86,
-                190,
-                231,
-                187,
-                241,
-                178,
-                93,
-                156,
-                164,
-                97,
-                165,
-                93,
-                69,
-                109,
-                254,
-                60,
-                140,
-                218,
-                139,
-                104,
-                66,
-                16,
-                9,
-                139,
-                59,
-                136,
-                184,
-                153,
-                69,
-                121,
-                115
-              ]
-            }
+            ]
           }
         },
         {
-          "name": "delegationRecordPda",
+          "name": "deed",
           "writable": true,
           "pda": {
             "seeds": [
@@ -409,98 +492,90 @@ export type Magicplace = {
                 "value": [
                   100,
                   101,
-                  108,
                   101,
-                  103,
-                  97,
-                  116,
-                  105,
-                  111,
-                  110
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "pda"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            ],
-            "program": {
-              "kind": "account",
-              "path": "delegationProgram"
-            }
+            ]
           }
         },
         {
-          "name": "delegationMetadataPda",
-          "writable": true,
+          "name": "session",
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  100,
-                  101,
-                  108,
+                  115,
                   101,
-                  103,
-                  97,
-                  116,
+                  115,
+                  115,
                   105,
                   111,
-                  110,
-                  45,
-                  109,
-                  101,
-                  116,
-                  97,
-                  100,
-                  97,
-                  116,
-                  97
+                  110
                 ]
               },
               {
                 "kind": "account",
-                "path": "pda"
+                "path": "authority"
               }
-            ],
-            "program": {
-              "kind": "account",
-              "path": "delegationProgram"
-            }
+            ]
           }
         },
         {
-          "name": "pda",
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the platform fee when the shard is claimed"
+          ],
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
+                  116,
+                  114,
                   101,
+                  97,
                   115,
-                  115,
-                  105,
-                  111,
-                  110
+                  117,
+                  114,
+                  121
                 ]
-              },
-              {
-                "kind": "account",
-                "path": "authority"
               }
             ]
           }
         },
         {
-          "name": "ownerProgram",
-          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
-        },
-        {
-          "name": "delegationProgram",
-          "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+          "name": "authority",
+          "writable": true,
+          "signer": true
         },
         {
           "name": "systemProgram",
@@ -509,30 +584,48 @@ export type Magicplace = {
       ],
       "args": [
         {
-          "name": "mainWallet",
-          "type": "pubkey"
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "pixels",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "bulkPixel"
+              }
+            }
+          }
         }
       ]
     },
     {
-      "name": "erasePixel",
+      "name": "claimEarnings",
       "docs": [
-        "Erase a pixel (set to 0/transparent)"
+        "Mint the tokens a shard's owner has earned from other painters",
+        "Accrual happens on the ER (PixelShard::foreign_pixels); this runs on the base layer and",
+        "reads the last committed shard state, so it works whether or not the shard is delegated"
       ],
       "discriminator": [
-        6,
-        38,
-        248,
-        220,
-        47,
-        82,
-        57,
-        222
+        49,
+        99,
+        161,
+        170,
+        22,
+        233,
+        54,
+        140
       ],
       "accounts": [
         {
           "name": "shard",
-          "writable": true,
+          "docs": [
+            "only its committed foreign_pixels counter is read"
+          ],
           "pda": {
             "seeds": [
               {
@@ -557,89 +650,46 @@ export type Magicplace = {
           }
         },
         {
-          "name": "session",
+          "name": "deed",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
+                  100,
                   101,
-                  115,
-                  115,
-                  105,
-                  111,
-                  110
+                  101,
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "signer"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
             ]
           }
         },
         {
-          "name": "signer",
-          "writable": true,
-          "signer": true
-        }
-      ],
-      "args": [
-        {
-          "name": "shardX",
-          "type": "u16"
-        },
-        {
-          "name": "shardY",
-          "type": "u16"
-        },
-        {
-          "name": "px",
-          "type": "u32"
-        },
-        {
-          "name": "py",
-          "type": "u32"
-        }
-      ]
-    },
-    {
-      "name": "initializeShard",
-      "docs": [
-        "Initialize a shard at (shard_x, shard_y) coordinates (without delegation)",
-        "Shards are created on-demand when a user wants to paint in that region",
-        "shard_x, shard_y: 0-4095 (4096 shards per dimension)",
-        "Call delegate_shard separately after this to delegate to ER"
-      ],
-      "discriminator": [
-        100,
-        96,
-        88,
-        58,
-        225,
-        178,
-        9,
-        147
-      ],
-      "accounts": [
-        {
-          "name": "shard",
-          "docs": [
-            "The shard account to initialize"
-          ],
+          "name": "earnings",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
-                  104,
+                  101,
                   97,
                   114,
-                  100
+                  110,
+                  105,
+                  110,
+                  103,
+                  115
                 ]
               },
               {
@@ -654,32 +704,55 @@ export type Magicplace = {
           }
         },
         {
-          "name": "session",
+          "name": "config",
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
-                  101,
-                  115,
-                  115,
-                  105,
+                  99,
                   111,
-                  110
+                  110,
+                  102,
+                  105,
+                  103
                 ]
-              },
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "writable": true,
+          "pda": {
+            "seeds": [
               {
-                "kind": "account",
-                "path": "authority"
+                "kind": "const",
+                "value": [
+                  109,
+                  105,
+                  110,
+                  116
+                ]
               }
             ]
           }
         },
         {
-          "name": "authority",
+          "name": "ownerTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "owner",
           "writable": true,
-          "signer": true
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
         },
         {
           "name": "systemProgram",
@@ -698,24 +771,25 @@ export type Magicplace = {
       ]
     },
     {
-      "name": "initializeUser",
+      "name": "closeBaselineSession",
+      "docs": [
+        "Close a session left in the baseline layout instead of migrating it, refunding its rent to the",
+        "session key that paid it; signed by that key. A delegated one must first come back through",
+        "undelegateBaselineSession"
+      ],
       "discriminator": [
-        111,
-        17,
-        185,
-        250,
-        60,
-        122,
-        38,
+        97,
+        144,
+        34,
+        142,
+        219,
+        53,
+        100,
         254
       ],
       "accounts": [
         {
-          "name": "user",
-          "docs": [
-            "Session account PDA derived from the MAIN wallet (not session key)",
-            "This ensures each main wallet has exactly one session account"
-          ],
+          "name": "session",
           "writable": true,
           "pda": {
             "seeds": [
@@ -740,53 +814,81 @@ export type Magicplace = {
         },
         {
           "name": "authority",
-          "docs": [
-            "The session key that is authorized to act on behalf of main_wallet"
-          ],
           "writable": true,
           "signer": true
-        },
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "closeSession",
+      "docs": [
+        "Close a revoked, undelegated session and refund its rent to the main wallet"
+      ],
+      "discriminator": [
+        68,
+        114,
+        178,
+        140,
+        222,
+        38,
+        248,
+        211
+      ],
+      "accounts": [
         {
-          "name": "systemProgram",
-          "address": "11111111111111111111111111111111"
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "authority"
+              }
+            ]
+          }
         },
         {
-          "name": "instructionsSysvar",
-          "address": "Sysvar1nstructions1111111111111111111111111"
+          "name": "mainWallet",
+          "writable": true,
+          "signer": true
         }
       ],
       "args": [
         {
-          "name": "mainWallet",
+          "name": "authority",
           "type": "pubkey"
-        },
-        {
-          "name": "signature",
-          "type": {
-            "array": [
-              "u8",
-              64
-            ]
-          }
         }
       ]
     },
     {
-      "name": "placePixel",
+      "name": "closeShard",
       "docs": [
-        "Place a pixel using global coordinates",
-        "px, py: 0 to 524,287 (global pixel coordinates)",
-        "color: 1-15 (0 is reserved for unset/transparent, 4-bit packing)"
+        "Close an undelegated, unlisted shard with its deed and earnings record, refunding all rent to the owner",
+        "A painted shard can only be closed once close_quiet_secs have passed since its last foreign paint;",
+        "unclaimed earnings are forfeited, so call claim_earnings first"
       ],
       "discriminator": [
-        178,
-        40,
+        18,
+        225,
         167,
-        97,
-        31,
-        149,
-        219,
-        143
+        168,
+        223,
+        60,
+        73,
+        176
       ],
       "accounts": [
         {
@@ -816,77 +918,141 @@ export type Magicplace = {
           }
         },
         {
-          "name": "session",
+          "name": "deed",
           "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
+                  100,
                   101,
-                  115,
-                  115,
-                  105,
-                  111,
-                  110
+                  101,
+                  100
                 ]
               },
               {
-                "kind": "account",
-                "path": "signer"
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
             ]
           }
         },
         {
-          "name": "signer",
-          "writable": true,
-          "signer": true
-        }
-      ],
-      "args": [
-        {
-          "name": "shardX",
-          "type": "u16"
+          "name": "listing",
+          "docs": [
+            "Must be absent: cancel the listing first"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  105,
+                  115,
+                  116,
+                  105,
+                  110,
+                  103
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
         },
         {
-          "name": "shardY",
-          "type": "u16"
+          "name": "earnings",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  101,
+                  97,
+                  114,
+                  110,
+                  105,
+                  110,
+                  103,
+                  115
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
         },
         {
-          "name": "px",
-          "type": "u32"
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
         },
         {
-          "name": "py",
-          "type": "u32"
+          "name": "owner",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
         },
         {
-          "name": "color",
-          "type": "u8"
+          "name": "shardY",
+          "type": "u16"
         }
       ]
     },
     {
-      "name": "placePixelsBulk",
+      "name": "commitAndUndelegateShard",
       "docs": [
-        "Place multiple pixels in bulk (max 50 pixels per call)",
-        "All pixels must be within the same shard",
-        "Each pixel is specified as (local_x, local_y, color) where:",
-        "- local_x: 0-89 (position within shard)",
-        "- local_y: 0-89 (position within shard)",
-        "- color: 1-255 (0 is reserved for transparent)"
+        "Maintenance path: the admin or moderator commits and undelegates any shard"
       ],
       "discriminator": [
-        52,
-        220,
-        66,
-        126,
-        140,
-        36,
-        121,
-        150
+        145,
+        251,
+        153,
+        107,
+        65,
+        205,
+        71,
+        248
       ],
       "accounts": [
         {
@@ -916,33 +1082,36 @@ export type Magicplace = {
           }
         },
         {
-          "name": "session",
-          "writable": true,
+          "name": "config",
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
-                  115,
-                  101,
-                  115,
-                  115,
-                  105,
+                  99,
                   111,
-                  110
+                  110,
+                  102,
+                  105,
+                  103
                 ]
-              },
-              {
-                "kind": "account",
-                "path": "signer"
               }
             ]
           }
         },
         {
-          "name": "signer",
+          "name": "moderator",
           "writable": true,
           "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
         }
       ],
       "args": [
@@ -953,213 +1122,6324 @@ export type Magicplace = {
         {
           "name": "shardY",
           "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "commitShard",
+      "docs": [
+        "Commit shard state from ER to base layer"
+      ],
+      "discriminator": [
+        85,
+        249,
+        246,
+        67,
+        192,
+        89,
+        165,
+        50
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
         },
         {
-          "name": "pixels",
-          "type": {
-            "vec": {
-              "defined": {
-                "name": "bulkPixel"
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
               }
-            }
+            ]
           }
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
         }
       ]
     },
     {
-      "name": "processUndelegation",
+      "name": "commitShards",
+      "docs": [
+        "Commit several shards from ER to base layer in one instruction",
+        "Shard accounts are passed in remaining_accounts, in the same order as `shards`"
+      ],
       "discriminator": [
-        196,
-        28,
-        41,
-        206,
-        48,
-        37,
-        51,
-        167
+        177,
+        150,
+        22,
+        195,
+        80,
+        69,
+        77,
+        136
       ],
       "accounts": [
         {
-          "name": "baseAccount",
-          "writable": true
-        },
-        {
-          "name": "buffer"
+          "name": "payer",
+          "writable": true,
+          "signer": true
         },
         {
-          "name": "payer",
-          "writable": true
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
         },
         {
-          "name": "systemProgram"
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
         }
       ],
       "args": [
         {
-          "name": "accountSeeds",
+          "name": "shards",
           "type": {
-            "vec": "bytes"
+            "vec": {
+              "defined": {
+                "name": "shardCoord"
+              }
+            }
           }
         }
       ]
-    }
-  ],
-  "accounts": [
-    {
-      "name": "pixelShard",
-      "discriminator": [
-        71,
-        74,
-        139,
-        167,
-        18,
-        144,
-        148,
-        25
-      ]
     },
     {
-      "name": "sessionAccount",
+      "name": "delegateProfile",
+      "docs": [
+        "Delegate a main wallet's profile to Ephemeral Rollups",
+        "Placements charge cooldown against the profile, so it must live on the ER with the sessions",
+        "Only needs to happen once per wallet, by any of its registered session keys"
+      ],
       "discriminator": [
-        74,
-        34,
-        65,
-        133,
-        96,
-        163,
-        80,
-        69
-      ]
-    }
-  ],
+        197,
+        115,
+        194,
+        166,
+        110,
+        39,
+        73,
+        134
+      ],
+      "accounts": [
+        {
+          "name": "authority",
+          "docs": [
+            "A session key registered on the profile"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "bufferPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  98,
+                  117,
+                  102,
+                  102,
+                  101,
+                  114
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                55,
+                86,
+                190,
+                231,
+                187,
+                241,
+                178,
+                93,
+                156,
+                164,
+                97,
+                165,
+                93,
+                69,
+                109,
+                254,
+                60,
+                140,
+                218,
+                139,
+                104,
+                66,
+                16,
+                9,
+                139,
+                59,
+                136,
+                184,
+                153,
+                69,
+                121,
+                115
+              ]
+            }
+          }
+        },
+        {
+          "name": "delegationRecordPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "delegationMetadataPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110,
+                  45,
+                  109,
+                  101,
+                  116,
+                  97,
+                  100,
+                  97,
+                  116,
+                  97
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "pda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "ownerProgram",
+          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
+        },
+        {
+          "name": "delegationProgram",
+          "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "mainWallet",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "delegateShard",
+      "docs": [
+        "Delegate an existing shard to Ephemeral Rollups",
+        "This should be called after initialize_shard in a separate transaction"
+      ],
+      "discriminator": [
+        193,
+        43,
+        22,
+        82,
+        105,
+        20,
+        98,
+        22
+      ],
+      "accounts": [
+        {
+          "name": "authority",
+          "docs": [
+            "The authority requesting delegation"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "bufferPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  98,
+                  117,
+                  102,
+                  102,
+                  101,
+                  114
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                55,
+                86,
+                190,
+                231,
+                187,
+                241,
+                178,
+                93,
+                156,
+                164,
+                97,
+                165,
+                93,
+                69,
+                109,
+                254,
+                60,
+                140,
+                218,
+                139,
+                104,
+                66,
+                16,
+                9,
+                139,
+                59,
+                136,
+                184,
+                153,
+                69,
+                121,
+                115
+              ]
+            }
+          }
+        },
+        {
+          "name": "delegationRecordPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "delegationMetadataPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110,
+                  45,
+                  109,
+                  101,
+                  116,
+                  97,
+                  100,
+                  97,
+                  116,
+                  97
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "pda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "ownerProgram",
+          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
+        },
+        {
+          "name": "delegationProgram",
+          "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "delegateUser",
+      "docs": [
+        "Delegate a user session account to Ephemeral Rollups",
+        "This should be called after initialize_user in a separate transaction"
+      ],
+      "discriminator": [
+        237,
+        220,
+        199,
+        24,
+        59,
+        41,
+        247,
+        24
+      ],
+      "accounts": [
+        {
+          "name": "user",
+          "docs": [
+            "The session account to delegate"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The session key authority"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "bufferPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  98,
+                  117,
+                  102,
+                  102,
+                  101,
+                  114
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                55,
+                86,
+                190,
+                231,
+                187,
+                241,
+                178,
+                93,
+                156,
+                164,
+                97,
+                165,
+                93,
+                69,
+                109,
+                254,
+                60,
+                140,
+                218,
+                139,
+                104,
+                66,
+                16,
+                9,
+                139,
+                59,
+                136,
+                184,
+                153,
+                69,
+                121,
+                115
+              ]
+            }
+          }
+        },
+        {
+          "name": "delegationRecordPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "delegationMetadataPda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  108,
+                  101,
+                  103,
+                  97,
+                  116,
+                  105,
+                  111,
+                  110,
+                  45,
+                  109,
+                  101,
+                  116,
+                  97,
+                  100,
+                  97,
+                  116,
+                  97
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "pda"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "delegationProgram"
+            }
+          }
+        },
+        {
+          "name": "pda",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "ownerProgram",
+          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
+        },
+        {
+          "name": "delegationProgram",
+          "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "mainWallet",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "erasePixel",
+      "docs": [
+        "Erase a pixel (set to 0/transparent)",
+        "Non-owners are rate-limited exactly like place_pixel, or rejected outright",
+        "when the config's erase_policy is OwnerOnly"
+      ],
+      "discriminator": [
+        6,
+        38,
+        248,
+        220,
+        47,
+        82,
+        57,
+        222
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "px",
+          "type": "u32"
+        },
+        {
+          "name": "py",
+          "type": "u32"
+        }
+      ]
+    },
+    {
+      "name": "fillRect",
+      "docs": [
+        "Fill a rectangle of one shard with a single color, in local coordinates",
+        "Cooldown and foreign-paint earnings count only pixels whose color actually changed"
+      ],
+      "discriminator": [
+        142,
+        153,
+        137,
+        106,
+        18,
+        3,
+        7,
+        195
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "x",
+          "type": "u8"
+        },
+        {
+          "name": "y",
+          "type": "u8"
+        },
+        {
+          "name": "width",
+          "type": "u8"
+        },
+        {
+          "name": "height",
+          "type": "u8"
+        },
+        {
+          "name": "color",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "freezeShard",
+      "docs": [
+        "Make a shard read-only (or writable again), signed by the moderator or admin",
+        "The flag lives on the deed, so this works on the base layer even while the shard is on the ER"
+      ],
+      "discriminator": [
+        116,
+        222,
+        170,
+        108,
+        241,
+        29,
+        45,
+        168
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "moderator",
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "frozen",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "getPixel",
+      "docs": [
+        "Read one pixel, in global coordinates, and its shard's creator"
+      ],
+      "discriminator": [
+        105,
+        199,
+        169,
+        92,
+        25,
+        234,
+        213,
+        141
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "px",
+          "type": "u32"
+        },
+        {
+          "name": "py",
+          "type": "u32"
+        }
+      ],
+      "returns": {
+        "defined": {
+          "name": "pixelRead"
+        }
+      }
+    },
+    {
+      "name": "getRegion",
+      "docs": [
+        "Read a rectangle of one shard, in local coordinates, row by row",
+        "At most MAX_REGION_PIXELS (960) pixels per call"
+      ],
+      "discriminator": [
+        244,
+        145,
+        179,
+        157,
+        203,
+        183,
+        211,
+        25
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "x",
+          "type": "u8"
+        },
+        {
+          "name": "y",
+          "type": "u8"
+        },
+        {
+          "name": "width",
+          "type": "u8"
+        },
+        {
+          "name": "height",
+          "type": "u8"
+        }
+      ],
+      "returns": {
+        "defined": {
+          "name": "regionRead"
+        }
+      }
+    },
+    {
+      "name": "initializeConfig",
+      "docs": [
+        "Create the global config and treasury; the program's upgrade authority becomes admin",
+        "Run once right after deployment. Gameplay values start at their defaults;",
+        "fees and reclaiming start disabled until set with update_config"
+      ],
+      "discriminator": [
+        208,
+        127,
+        21,
+        1,
+        194,
+        190,
+        196,
+        70
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "program",
+          "address": "4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe"
+        },
+        {
+          "name": "programData"
+        },
+        {
+          "name": "admin",
+          "docs": [
+            "Must be the program's upgrade authority, so nobody can front-run the deployment"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "initializeDeed",
+      "docs": [
+        "Create the missing deed for a shard claimed before deeds existed, owned by the shard's creator",
+        "Permissionless; `payer` covers the rent. Works on a shard in any layout, delegated or not"
+      ],
+      "discriminator": [
+        26,
+        241,
+        43,
+        210,
+        186,
+        193,
+        3,
+        51
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "initializeMint",
+      "docs": [
+        "Create the project token mint; the config PDA is its mint authority"
+      ],
+      "discriminator": [
+        209,
+        42,
+        195,
+        4,
+        129,
+        85,
+        209,
+        44
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  105,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "initializeShard",
+      "docs": [
+        "Initialize a shard at (shard_x, shard_y) coordinates (without delegation)",
+        "Shards are created on-demand when a user wants to paint in that region",
+        "shard_x, shard_y: 0-4095 (4096 shards per dimension)",
+        "Call delegate_shard separately after this to delegate to ER"
+      ],
+      "discriminator": [
+        100,
+        96,
+        88,
+        58,
+        225,
+        178,
+        9,
+        147
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "docs": [
+            "The shard account to initialize"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Ownership record for the shard, kept on the base layer"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the platform fee"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "initializeShards",
+      "docs": [
+        "Initialize a contiguous block of shards in one call, at a volume discount on the platform fee",
+        "remaining_accounts: a (shard, deed) pair of uninitialized PDAs for each entry of `shards`",
+        "The shards must be 4-connected (each touching another by an edge) and inside the canvas"
+      ],
+      "discriminator": [
+        54,
+        96,
+        52,
+        65,
+        93,
+        27,
+        238,
+        140
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the platform fee"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shards",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "shardCoord"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "initializeUser",
+      "docs": [
+        "Create a session account for `authority` on behalf of `main_wallet`",
+        "The transaction must carry an Ed25519 verify instruction at index 0 over the",
+        "canonical message built by `session_auth_message(authority, nonce, expires_at)`"
+      ],
+      "discriminator": [
+        111,
+        17,
+        185,
+        250,
+        60,
+        122,
+        38,
+        254
+      ],
+      "accounts": [
+        {
+          "name": "user",
+          "docs": [
+            "Session account PDA derived from the session key",
+            "A main wallet may register up to MAX_SESSIONS_PER_WALLET of these via its profile"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "docs": [
+            "Per-main-wallet profile holding cooldown state and the list of session keys",
+            "Must be on the base layer (not delegated) to register a new session; see undelegate_profile"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The session key that is authorized to act on behalf of main_wallet"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "instructionsSysvar",
+          "address": "Sysvar1nstructions1111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "mainWallet",
+          "type": "pubkey"
+        },
+        {
+          "name": "signature",
+          "type": {
+            "array": [
+              "u8",
+              64
+            ]
+          }
+        },
+        {
+          "name": "nonce",
+          "type": "u64"
+        },
+        {
+          "name": "expiresAt",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "listShard",
+      "docs": [
+        "List a shard for sale at a fixed SOL price",
+        "While listed, transfer_shard is blocked so the listing can't be sold out from under a buyer"
+      ],
+      "discriminator": [
+        172,
+        178,
+        233,
+        6,
+        227,
+        26,
+        5,
+        180
+      ],
+      "accounts": [
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "listing",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  105,
+                  115,
+                  116,
+                  105,
+                  110,
+                  103
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "owner",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "price",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "migrateSession",
+      "docs": [
+        "Upgrade a session account to the current layout, growing it in place",
+        "A baseline session (version 0) was registered by a replayable message, so its main wallet signs",
+        "the upgrade, pays any extra rent and gets the key registered on its profile as initialize_user",
+        "does; the baseline cooldown is dropped, since it now lives on the profile. Later layouts step up",
+        "from `version`. The session and profile must be undelegated (on the base layer); migrating an",
+        "up-to-date session is a no-op"
+      ],
+      "discriminator": [
+        176,
+        128,
+        4,
+        35,
+        63,
+        1,
+        118,
+        108
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "docs": [
+            "A baseline session is registered here, as initialize_user does; must not be delegated"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "authority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "migrateShard",
+      "docs": [
+        "Upgrade a shard to the current layout",
+        "A shard still in the baseline Borsh layout (version 0) is rewritten in place into the zero-copy",
+        "layout, stamped with PixelShard::VERSION; later layouts step up from `version`. Until then the",
+        "shard still paints and reads but tracks no activity or earnings; whatever needs them gets",
+        "shardNeedsMigration",
+        "Permissionless; `payer` tops up rent if needed. The shard must be undelegated (on the base layer):",
+        "a delegated baseline shard comes back through undelegate_baseline_shard. Migrating an up-to-date",
+        "shard is a no-op"
+      ],
+      "discriminator": [
+        104,
+        161,
+        75,
+        116,
+        173,
+        53,
+        64,
+        154
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "placePixel",
+      "docs": [
+        "Place a pixel using global coordinates",
+        "px, py: 0 to 524,287 (global pixel coordinates)",
+        "color: 1-15 (0 is reserved for unset/transparent, 4-bit packing)"
+      ],
+      "discriminator": [
+        178,
+        40,
+        167,
+        97,
+        31,
+        149,
+        219,
+        143
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "px",
+          "type": "u32"
+        },
+        {
+          "name": "py",
+          "type": "u32"
+        },
+        {
+          "name": "color",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "placePixelChecked",
+      "docs": [
+        "Compare-and-swap variant of place_pixel: only paints if the pixel still has `expected_color`",
+        "On conflict either fails with PixelConflict or skips, per `on_conflict`;",
+        "returns the conflicting pixel (index 0) when skipped"
+      ],
+      "discriminator": [
+        130,
+        66,
+        14,
+        17,
+        139,
+        173,
+        216,
+        83
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "pixel",
+          "type": {
+            "defined": {
+              "name": "checkedPixel"
+            }
+          }
+        },
+        {
+          "name": "onConflict",
+          "type": {
+            "defined": {
+              "name": "conflictPolicy"
+            }
+          }
+        }
+      ],
+      "returns": {
+        "vec": {
+          "defined": {
+            "name": "pixelConflict"
+          }
+        }
+      }
+    },
+    {
+      "name": "placePixelRuns",
+      "docs": [
+        "Place run-length encoded pixels on one shard: each run paints `length` pixels of",
+        "`row`, starting at column `start`. Costs 4 bytes per run instead of 3 per pixel",
+        "Cooldown and foreign-paint earnings count only pixels whose color actually changed"
+      ],
+      "discriminator": [
+        32,
+        224,
+        193,
+        66,
+        183,
+        105,
+        128,
+        148
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "runs",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "pixelRun"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "placePixelsBulk",
+      "docs": [
+        "Place multiple pixels in bulk, at most GameConfig.max_bulk_pixels per call",
+        "All pixels must be within the same shard",
+        "Each pixel is specified as (local_x, local_y, color) where:",
+        "- local_x: 0-89 (position within shard; 0-37 in the last shard column)",
+        "- local_y: 0-89 (position within shard; 0-37 in the last shard row)",
+        "- color: 1-255 (0 is reserved for transparent)"
+      ],
+      "discriminator": [
+        52,
+        220,
+        66,
+        126,
+        140,
+        36,
+        121,
+        150
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "pixels",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "bulkPixel"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "placePixelsBulkChecked",
+      "docs": [
+        "Compare-and-swap variant of place_pixels_bulk; each pixel may carry an `expected_color`",
+        "With ConflictPolicy::Fail any conflict aborts the whole batch; with Skip the conflicting",
+        "pixels are left alone and returned (by position in `pixels`), and only placed pixels",
+        "cost cooldown"
+      ],
+      "discriminator": [
+        155,
+        172,
+        3,
+        53,
+        227,
+        213,
+        97,
+        113
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer); decides who paints cooldown-free"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Live game parameters, read-only on the ER"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "pixels",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "checkedBulkPixel"
+              }
+            }
+          }
+        },
+        {
+          "name": "onConflict",
+          "type": {
+            "defined": {
+              "name": "conflictPolicy"
+            }
+          }
+        }
+      ],
+      "returns": {
+        "vec": {
+          "defined": {
+            "name": "pixelConflict"
+          }
+        }
+      }
+    },
+    {
+      "name": "placePixelsCrossShard",
+      "docs": [
+        "Place pixels given in global coordinates, spread over any number of shards, atomically",
+        "remaining_accounts holds a (shard, deed) pair for every shard the pixels land in;",
+        "ownership, freezes, passes and foreign-paint earnings apply per shard as in place_pixels_bulk,",
+        "and the pixels that are not cooldown-free are charged to the profile together"
+      ],
+      "discriminator": [
+        143,
+        72,
+        175,
+        127,
+        240,
+        57,
+        25,
+        110
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "pass",
+          "docs": [
+            "Optional cooldown pass owned by the session's main wallet, read-only on the ER"
+          ],
+          "optional": true
+        },
+        {
+          "name": "signer",
+          "writable": true,
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "pixels",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "globalPixel"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "processUndelegation",
+      "discriminator": [
+        196,
+        28,
+        41,
+        206,
+        48,
+        37,
+        51,
+        167
+      ],
+      "accounts": [
+        {
+          "name": "baseAccount",
+          "writable": true
+        },
+        {
+          "name": "buffer"
+        },
+        {
+          "name": "payer",
+          "writable": true
+        },
+        {
+          "name": "systemProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "accountSeeds",
+          "type": {
+            "vec": "bytes"
+          }
+        }
+      ]
+    },
+    {
+      "name": "reclaimShard",
+      "docs": [
+        "Take over a shard whose owner and painters have been inactive for the configured window",
+        "The new owner is the main wallet behind the signing session; the fee goes to the treasury",
+        "and earnings accrued so far to the previous owner"
+      ],
+      "discriminator": [
+        122,
+        171,
+        119,
+        102,
+        237,
+        84,
+        35,
+        181
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "docs": [
+            "only its committed last_activity and foreign_pixels are read"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "listing",
+          "docs": [
+            "Must be absent: listed shards are not abandoned"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  105,
+                  115,
+                  116,
+                  105,
+                  110,
+                  103
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "session",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "earnings",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  101,
+                  97,
+                  114,
+                  110,
+                  105,
+                  110,
+                  103,
+                  115
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "docs": [
+            "Only needed when earnings are owed, like the token accounts below"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  105,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "previousOwnerTokenAccount",
+          "docs": [
+            "Receives the outgoing owner's unclaimed earnings"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "revokeAndUndelegateSession",
+      "docs": [
+        "Revoke a delegated session on the ER and return it to the base layer",
+        "Once the undelegation lands, close_session can reclaim its rent"
+      ],
+      "discriminator": [
+        161,
+        13,
+        27,
+        141,
+        206,
+        170,
+        92,
+        188
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "authority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "revokeSession",
+      "docs": [
+        "Revoke a session key so it can no longer paint",
+        "Runs on whichever layer currently holds the session and profile"
+      ],
+      "discriminator": [
+        86,
+        92,
+        198,
+        120,
+        144,
+        2,
+        7,
+        194
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "authority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "rotateSession",
+      "docs": [
+        "Replace a session key with a new one in a single step on the base layer",
+        "The old session is closed (rent to the main wallet) and the new one is paid for by the main wallet",
+        "A delegated profile has to come back first, through undelegate_profile"
+      ],
+      "discriminator": [
+        105,
+        76,
+        255,
+        50,
+        28,
+        5,
+        13,
+        132
+      ],
+      "accounts": [
+        {
+          "name": "oldSession",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "oldAuthority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "newSession",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "newAuthority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "oldAuthority",
+          "type": "pubkey"
+        },
+        {
+          "name": "newAuthority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setModerator",
+      "docs": [
+        "Choose the wallet allowed to freeze individual shards"
+      ],
+      "discriminator": [
+        142,
+        48,
+        168,
+        55,
+        125,
+        196,
+        149,
+        56
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "moderator",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setPassPrice",
+      "docs": [
+        "Set what other painters pay this shard's owner for a cooldown pass (0 = not for sale)"
+      ],
+      "discriminator": [
+        247,
+        41,
+        138,
+        159,
+        225,
+        209,
+        90,
+        52
+      ],
+      "accounts": [
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "owner",
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "priceLamports",
+          "type": "u64"
+        },
+        {
+          "name": "priceTokens",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setPaused",
+      "docs": [
+        "Emergency brake: while paused, placing, erasing and shard creation are rejected"
+      ],
+      "discriminator": [
+        91,
+        60,
+        125,
+        192,
+        176,
+        225,
+        166,
+        218
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "paused",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setSessionExpiry",
+      "docs": [
+        "Set the unix timestamp after which a session key stops working (0 = never expires)"
+      ],
+      "discriminator": [
+        11,
+        24,
+        149,
+        69,
+        56,
+        231,
+        43,
+        87
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "authority",
+          "type": "pubkey"
+        },
+        {
+          "name": "expiresAt",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "transferShard",
+      "docs": [
+        "Give a shard to another main wallet, signed by the current owner",
+        "Earnings accrued so far are paid to the current owner first"
+      ],
+      "discriminator": [
+        246,
+        174,
+        89,
+        47,
+        148,
+        217,
+        221,
+        16
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "docs": [
+            "only its committed foreign_pixels counter is read"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "listing",
+          "docs": [
+            "Must be absent: a listed shard can only change hands through buy_shard"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  105,
+                  115,
+                  116,
+                  105,
+                  110,
+                  103
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "earnings",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  101,
+                  97,
+                  114,
+                  110,
+                  105,
+                  110,
+                  103,
+                  115
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "docs": [
+            "Only needed when earnings are owed, like the token accounts below"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  105,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "ownerTokenAccount",
+          "docs": [
+            "Receives the outgoing owner's unclaimed earnings"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "owner",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        },
+        {
+          "name": "newOwner",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "undelegateBaselineSession",
+      "docs": [
+        "Return a delegated baseline session to the base layer so migrate_session can upgrade it",
+        "(or close_baseline_session retire it)",
+        "Signed by its session key, like undelegate_user"
+      ],
+      "discriminator": [
+        135,
+        195,
+        159,
+        76,
+        48,
+        48,
+        250,
+        175
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "undelegateBaselineShard",
+      "docs": [
+        "Return a delegated baseline shard to the base layer so migrate_shard can rewrite it",
+        "Permissionless like migrate_shard: baseline shards have no deed to name an owner"
+      ],
+      "discriminator": [
+        75,
+        237,
+        212,
+        204,
+        32,
+        22,
+        2,
+        165
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "undelegateProfile",
+      "docs": [
+        "Commit a wallet profile and return it to the base layer, signed by the main wallet",
+        "initialize_user and rotate_session write the profile on the base layer, so a wallet whose",
+        "profile is delegated runs this first and delegate_profile again once the session is registered"
+      ],
+      "discriminator": [
+        48,
+        29,
+        12,
+        69,
+        45,
+        87,
+        67,
+        159
+      ],
+      "accounts": [
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mainWallet"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mainWallet",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "undelegateShard",
+      "docs": [
+        "Commit a shard and return it to the base layer, signed by the shard owner",
+        "Needed before anything that rewrites the shard account itself (closing, migrations)"
+      ],
+      "discriminator": [
+        133,
+        23,
+        123,
+        148,
+        157,
+        175,
+        184,
+        81
+      ],
+      "accounts": [
+        {
+          "name": "shard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  104,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "deed",
+          "docs": [
+            "Read-only on the ER (cloned from the base layer)"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  100,
+                  101,
+                  101,
+                  100
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "shardX"
+              },
+              {
+                "kind": "arg",
+                "path": "shardY"
+              }
+            ]
+          }
+        },
+        {
+          "name": "owner",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "deed"
+          ]
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "shardX",
+          "type": "u16"
+        },
+        {
+          "name": "shardY",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "undelegateUser",
+      "docs": [
+        "Commit a session (and optionally its wallet profile) and return it to the base layer",
+        "Signed by an unrevoked session key; leave the profile delegated while other sessions still paint",
+        "Only keys still registered on the profile may take it off the ER"
+      ],
+      "discriminator": [
+        116,
+        139,
+        246,
+        224,
+        235,
+        172,
+        97,
+        25
+      ],
+      "accounts": [
+        {
+          "name": "session",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  101,
+                  115,
+                  115,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "profile",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  119,
+                  97,
+                  108,
+                  108,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "session.main_address",
+                "account": "sessionAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "magicProgram",
+          "address": "Magic11111111111111111111111111111111111111"
+        },
+        {
+          "name": "magicContext",
+          "writable": true,
+          "address": "MagicContext1111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "includeProfile",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "updateConfig",
+      "docs": [
+        "Replace all tunable game parameters at once",
+        "Placement instructions read these live, so changes apply without a program upgrade"
+      ],
+      "discriminator": [
+        29,
+        158,
+        252,
+        191,
+        10,
+        83,
+        219,
+        99
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "params",
+          "type": {
+            "defined": {
+              "name": "configParams"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "withdrawTreasury",
+      "docs": [
+        "Move collected fees out of the treasury, keeping it rent-exempt"
+      ],
+      "discriminator": [
+        40,
+        63,
+        122,
+        158,
+        144,
+        216,
+        83,
+        96
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "recipient",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "cooldownPass",
+      "discriminator": [
+        98,
+        64,
+        163,
+        13,
+        128,
+        85,
+        216,
+        57
+      ]
+    },
+    {
+      "name": "gameConfig",
+      "discriminator": [
+        45,
+        146,
+        146,
+        33,
+        170,
+        69,
+        96,
+        133
+      ]
+    },
+    {
+      "name": "pixelShard",
+      "discriminator": [
+        71,
+        74,
+        139,
+        167,
+        18,
+        144,
+        148,
+        25
+      ]
+    },
+    {
+      "name": "sessionAccount",
+      "discriminator": [
+        74,
+        34,
+        65,
+        133,
+        96,
+        163,
+        80,
+        69
+      ]
+    },
+    {
+      "name": "shardDeed",
+      "discriminator": [
+        187,
+        7,
+        191,
+        152,
+        234,
+        233,
+        124,
+        215
+      ]
+    },
+    {
+      "name": "shardEarnings",
+      "discriminator": [
+        69,
+        199,
+        69,
+        243,
+        45,
+        44,
+        255,
+        95
+      ]
+    },
+    {
+      "name": "shardListing",
+      "discriminator": [
+        222,
+        90,
+        250,
+        19,
+        33,
+        241,
+        146,
+        130
+      ]
+    },
+    {
+      "name": "treasury",
+      "discriminator": [
+        238,
+        239,
+        123,
+        238,
+        89,
+        1,
+        168,
+        253
+      ]
+    },
+    {
+      "name": "walletProfile",
+      "discriminator": [
+        9,
+        216,
+        26,
+        246,
+        37,
+        66,
+        29,
+        169
+      ]
+    }
+  ],
   "events": [
+    {
+      "name": "configUpdated",
+      "discriminator": [
+        40,
+        241,
+        230,
+        122,
+        11,
+        19,
+        198,
+        194
+      ]
+    },
+    {
+      "name": "cooldownPassPurchased",
+      "discriminator": [
+        250,
+        201,
+        136,
+        89,
+        163,
+        50,
+        141,
+        227
+      ]
+    },
+    {
+      "name": "earningsClaimed",
+      "discriminator": [
+        106,
+        170,
+        154,
+        105,
+        21,
+        43,
+        189,
+        97
+      ]
+    },
+    {
+      "name": "pixelChanged",
+      "discriminator": [
+        140,
+        76,
+        21,
+        27,
+        179,
+        226,
+        1,
+        84
+      ]
+    },
+    {
+      "name": "pixelErased",
+      "discriminator": [
+        165,
+        204,
+        68,
+        193,
+        224,
+        131,
+        140,
+        199
+      ]
+    },
+    {
+      "name": "pixelRunsPlaced",
+      "discriminator": [
+        45,
+        178,
+        157,
+        214,
+        153,
+        13,
+        181,
+        240
+      ]
+    },
+    {
+      "name": "rectFilled",
+      "discriminator": [
+        16,
+        193,
+        128,
+        211,
+        138,
+        236,
+        102,
+        69
+      ]
+    },
+    {
+      "name": "sessionRevoked",
+      "discriminator": [
+        90,
+        48,
+        35,
+        234,
+        203,
+        192,
+        126,
+        211
+      ]
+    },
+    {
+      "name": "shardClosed",
+      "discriminator": [
+        181,
+        79,
+        79,
+        41,
+        139,
+        177,
+        154,
+        14
+      ]
+    },
+    {
+      "name": "shardFreezeChanged",
+      "discriminator": [
+        181,
+        252,
+        143,
+        108,
+        82,
+        163,
+        174,
+        247
+      ]
+    },
+    {
+      "name": "shardInitialized",
+      "discriminator": [
+        183,
+        80,
+        70,
+        77,
+        210,
+        226,
+        161,
+        150
+      ]
+    },
+    {
+      "name": "shardReclaimed",
+      "discriminator": [
+        136,
+        187,
+        229,
+        8,
+        196,
+        254,
+        53,
+        164
+      ]
+    },
+    {
+      "name": "shardTransferred",
+      "discriminator": [
+        250,
+        151,
+        208,
+        176,
+        198,
+        90,
+        28,
+        36
+      ]
+    },
+    {
+      "name": "shardsCommitted",
+      "discriminator": [
+        197,
+        131,
+        178,
+        183,
+        244,
+        28,
+        200,
+        208
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "invalidShardCoord",
+      "msg": "Invalid shard coordinates: must be 0-5825"
+    },
+    {
+      "code": 6001,
+      "name": "invalidPixelCoord",
+      "msg": "Invalid pixel coordinates: must be 0-524287"
+    },
+    {
+      "code": 6002,
+      "name": "shardMismatch",
+      "msg": "Shard coordinates don't match pixel location"
+    },
+    {
+      "code": 6003,
+      "name": "invalidColor",
+      "msg": "Invalid color: must be 1-255"
+    },
+    {
+      "code": 6004,
+      "name": "invalidAuth",
+      "msg": "Invalid authentication"
+    },
+    {
+      "code": 6005,
+      "name": "cooldown",
+      "msg": "Cooldown active: limit reached"
+    },
+    {
+      "code": 6006,
+      "name": "emptyBulkPixels",
+      "msg": "Bulk pixels array is empty"
+    },
+    {
+      "code": 6007,
+      "name": "bulkTooLarge",
+      "msg": "Bulk pixels exceeds the configured maximum"
+    },
+    {
+      "code": 6008,
+      "name": "bulkExceedsCooldown",
+      "msg": "Bulk placement would exceed cooldown limit"
+    },
+    {
+      "code": 6009,
+      "name": "invalidEd25519instruction",
+      "msg": "First instruction must be a single-signature Ed25519 verify over its own data"
+    },
+    {
+      "code": 6010,
+      "name": "authPubkeyMismatch",
+      "msg": "Ed25519 signer does not match the main wallet"
+    },
+    {
+      "code": 6011,
+      "name": "authSignatureMismatch",
+      "msg": "Ed25519 signature does not match the provided signature"
+    },
+    {
+      "code": 6012,
+      "name": "authMessageMismatch",
+      "msg": "Signed message is not the canonical session authorization"
+    },
+    {
+      "code": 6013,
+      "name": "authExpired",
+      "msg": "Session authorization has expired"
+    },
+    {
+      "code": 6014,
+      "name": "authNonceReused",
+      "msg": "Authorization nonce must be greater than the last one used"
+    },
+    {
+      "code": 6015,
+      "name": "tooManySessions",
+      "msg": "Main wallet already has the maximum number of session keys"
+    },
+    {
+      "code": 6016,
+      "name": "sessionRevoked",
+      "msg": "Session key has been revoked"
+    },
+    {
+      "code": 6017,
+      "name": "sessionExpired",
+      "msg": "Session key has expired"
+    },
+    {
+      "code": 6018,
+      "name": "sessionNotRevoked",
+      "msg": "Session must be revoked before it can be closed"
+    },
+    {
+      "code": 6019,
+      "name": "notShardOwner",
+      "msg": "Signer does not own this shard"
+    },
+    {
+      "code": 6020,
+      "name": "shardListed",
+      "msg": "Shard is listed for sale; cancel the listing first"
+    },
+    {
+      "code": 6021,
+      "name": "invalidPrice",
+      "msg": "Listing price must be greater than zero"
+    },
+    {
+      "code": 6022,
+      "name": "listingPriceChanged",
+      "msg": "Listing price is above the buyer's maximum"
+    },
+    {
+      "code": 6023,
+      "name": "notAdmin",
+      "msg": "Signer is not the config admin"
+    },
+    {
+      "code": 6024,
+      "name": "insufficientTreasury",
+      "msg": "Treasury balance too low for this withdrawal"
+    },
+    {
+      "code": 6025,
+      "name": "invalidShardAccount",
+      "msg": "Account is not a valid shard"
+    },
+    {
+      "code": 6026,
+      "name": "nothingToClaim",
+      "msg": "No earnings to claim yet"
+    },
+    {
+      "code": 6027,
+      "name": "passNotForSale",
+      "msg": "Cooldown pass is not for sale in this currency"
+    },
+    {
+      "code": 6028,
+      "name": "missingPassAccount",
+      "msg": "An account required for this cooldown pass purchase is missing"
+    },
+    {
+      "code": 6029,
+      "name": "invalidPassMint",
+      "msg": "Cooldown passes can only be paid in the project token"
+    },
+    {
+      "code": 6030,
+      "name": "invalidConfig",
+      "msg": "Invalid config value"
+    },
+    {
+      "code": 6031,
+      "name": "reclaimDisabled",
+      "msg": "Reclaiming shards is disabled"
+    },
+    {
+      "code": 6032,
+      "name": "shardStillActive",
+      "msg": "Shard has been active within the reclaim window"
+    },
+    {
+      "code": 6033,
+      "name": "gamePaused",
+      "msg": "Game is paused"
+    },
+    {
+      "code": 6034,
+      "name": "shardFrozen",
+      "msg": "Shard is frozen by a moderator"
+    },
+    {
+      "code": 6035,
+      "name": "notModerator",
+      "msg": "Signer is not a moderator"
+    },
+    {
+      "code": 6036,
+      "name": "eraseOwnerOnly",
+      "msg": "Only the shard owner may erase pixels"
+    },
+    {
+      "code": 6037,
+      "name": "emptyShardList",
+      "msg": "No shards given"
+    },
+    {
+      "code": 6038,
+      "name": "duplicateShard",
+      "msg": "The same shard was given more than once"
+    },
+    {
+      "code": 6039,
+      "name": "pixelConflict",
+      "msg": "Pixel color differs from the expected color"
+    },
+    {
+      "code": 6040,
+      "name": "regionTooLarge",
+      "msg": "Region exceeds the maximum readable size"
+    },
+    {
+      "code": 6041,
+      "name": "shardAlreadyClaimed",
+      "msg": "Shard has already been claimed"
+    },
+    {
+      "code": 6042,
+      "name": "tooManyShards",
+      "msg": "Too many shards in one call"
+    },
+    {
+      "code": 6043,
+      "name": "shardsNotContiguous",
+      "msg": "Shards must form one contiguous block"
+    },
+    {
+      "code": 6044,
+      "name": "shardNotQuiet",
+      "msg": "Shard must be blank or free of foreign paint for the close window"
+    },
+    {
+      "code": 6045,
+      "name": "notBaselineSession",
+      "msg": "Session is not in the baseline layout"
+    },
+    {
+      "code": 6046,
+      "name": "notBaselineShard",
+      "msg": "Shard is not in the baseline layout"
+    },
+    {
+      "code": 6047,
+      "name": "notUpgradeAuthority",
+      "msg": "Only the program's upgrade authority can initialize the config"
+    },
+    {
+      "code": 6048,
+      "name": "missingEarningsAccount",
+      "msg": "Earnings are owed: pass the mint, the receiving token account and the token program"
+    },
+    {
+      "code": 6049,
+      "name": "earningsOverflow",
+      "msg": "Earnings exceed the largest mintable token amount"
+    },
+    {
+      "code": 6050,
+      "name": "shardNeedsMigration",
+      "msg": "Shard is in an older layout: run migrate_shard first"
+    }
+  ],
+  "types": [
+    {
+      "name": "bulkPixel",
+      "docs": [
+        "Pixel data for bulk placement",
+        "Uses local coordinates within a shard (0-89), limited to the on-canvas part of edge shards"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "localX",
+            "docs": [
+              "Local X coordinate within shard (0-89)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "localY",
+            "docs": [
+              "Local Y coordinate within shard (0-89)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "color",
+            "docs": [
+              "Color index (1-255, 0 is reserved for transparent)"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "checkedBulkPixel",
+      "docs": [
+        "Bulk pixel for compare-and-swap placement, in local shard coordinates"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "localX",
+            "type": "u8"
+          },
+          {
+            "name": "localY",
+            "type": "u8"
+          },
+          {
+            "name": "color",
+            "type": "u8"
+          },
+          {
+            "name": "expectedColor",
+            "docs": [
+              "Only paint if the pixel currently has this color (None = always paint)"
+            ],
+            "type": {
+              "option": "u8"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "checkedPixel",
+      "docs": [
+        "Single pixel for compare-and-swap placement, in global coordinates"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "px",
+            "type": "u32"
+          },
+          {
+            "name": "py",
+            "type": "u32"
+          },
+          {
+            "name": "color",
+            "type": "u8"
+          },
+          {
+            "name": "expectedColor",
+            "docs": [
+              "Only paint if the pixel currently has this color (None = always paint)"
+            ],
+            "type": {
+              "option": "u8"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "configParams",
+      "docs": [
+        "Tunable game parameters, replaced as a whole by update_config"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "cooldownLimit",
+            "docs": [
+              "Max pixels a non-owner may place before the cooldown kicks in"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "cooldownPeriod",
+            "docs": [
+              "Seconds after hitting the limit before the burst counter resets"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "availableColors",
+            "docs": [
+              "Highest valid color index (colors are 1..=available_colors)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "maxBulkPixels",
+            "docs": [
+              "Max pixels in a single place_pixels_bulk call"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "shardFeeLamports",
+            "docs": [
+              "Platform fee in lamports paid to the treasury by initialize_shard"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "batchDiscountBps",
+            "docs": [
+              "Per-shard discount on the fee for initialize_shards batches, in basis points per extra shard"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "globalPassPriceLamports",
+            "docs": [
+              "Price of a global cooldown pass in lamports, paid to the treasury (0 = not for sale)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "reclaimAfterSecs",
+            "docs": [
+              "Seconds a shard must be inactive before reclaim_shard can take it (0 = disabled)"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "reclaimFeeLamports",
+            "docs": [
+              "Fee in lamports paid to the treasury to reclaim a dead shard"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "closeQuietSecs",
+            "docs": [
+              "Seconds since the last foreign paint before close_shard may close a painted shard",
+              "(0 = any time; blank shards can always be closed)"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "erasePolicy",
+            "docs": [
+              "Who may erase pixels on a shard they don't own"
+            ],
+            "type": {
+              "defined": {
+                "name": "erasePolicy"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "configUpdated",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "params",
+            "type": {
+              "defined": {
+                "name": "configParams"
+              }
+            }
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "conflictPolicy",
+      "docs": [
+        "What a compare-and-swap placement does when a pixel no longer has its expected color"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "fail"
+          },
+          {
+            "name": "skip"
+          }
+        ]
+      }
+    },
+    {
+      "name": "cooldownPass",
+      "docs": [
+        "Lets a painter skip cooldown on one shard or everywhere until valid_until"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "painter",
+            "docs": [
+              "Main wallet the pass belongs to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "scope",
+            "type": {
+              "defined": {
+                "name": "passScope"
+              }
+            }
+          },
+          {
+            "name": "validUntil",
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "cooldownPassPurchased",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "painter",
+            "type": "pubkey"
+          },
+          {
+            "name": "scope",
+            "type": {
+              "defined": {
+                "name": "passScope"
+              }
+            }
+          },
+          {
+            "name": "validUntil",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "earningsClaimed",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "owner",
+            "type": "pubkey"
+          },
+          {
+            "name": "tokens",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "erasePolicy",
+      "docs": [
+        "Rules for erasing pixels on someone else's shard"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "rateLimited"
+          },
+          {
+            "name": "ownerOnly"
+          }
+        ]
+      }
+    },
+    {
+      "name": "gameConfig",
+      "docs": [
+        "Global settings governed by the admin",
+        "Never delegated; the ER reads it as a cloned read-only account"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "moderator",
+            "docs": [
+              "May freeze and unfreeze individual shards"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "paused",
+            "docs": [
+              "Global emergency brake for placement and shard creation"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "params",
+            "type": {
+              "defined": {
+                "name": "configParams"
+              }
+            }
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "globalPixel",
+      "docs": [
+        "Pixel data for cross-shard placement, in global canvas coordinates"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "px",
+            "type": "u32"
+          },
+          {
+            "name": "py",
+            "type": "u32"
+          },
+          {
+            "name": "color",
+            "docs": [
+              "Color index (1-255, 0 is reserved for transparent)"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "passPayment",
+      "docs": [
+        "Currency used to buy a cooldown pass"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "sol"
+          },
+          {
+            "name": "token"
+          }
+        ]
+      }
+    },
+    {
+      "name": "passScope",
+      "docs": [
+        "Where a cooldown pass applies"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "global"
+          },
+          {
+            "name": "shard",
+            "fields": [
+              {
+                "name": "shardX",
+                "type": "u16"
+              },
+              {
+                "name": "shardY",
+                "type": "u16"
+              }
+            ]
+          }
+        ]
+      }
+    },
     {
       "name": "pixelChanged",
-      "discriminator": [
-        140,
-        76,
-        21,
-        27,
-        179,
-        226,
-        1,
-        84
-      ]
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "px",
+            "type": "u32"
+          },
+          {
+            "name": "py",
+            "type": "u32"
+          },
+          {
+            "name": "color",
+            "type": "u8"
+          },
+          {
+            "name": "painter",
+            "type": "pubkey"
+          },
+          {
+            "name": "mainWallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pixelConflict",
+      "docs": [
+        "A pixel skipped by compare-and-swap placement, returned via return data"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "index",
+            "docs": [
+              "Position of the pixel in the instruction's input"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "currentColor",
+            "docs": [
+              "Color the pixel actually has"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pixelErased",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "px",
+            "type": "u32"
+          },
+          {
+            "name": "py",
+            "type": "u32"
+          },
+          {
+            "name": "painter",
+            "type": "pubkey"
+          },
+          {
+            "name": "mainWallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pixelRead",
+      "docs": [
+        "Result of get_pixel"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "color",
+            "type": "u8"
+          },
+          {
+            "name": "creator",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pixelRun",
+      "docs": [
+        "A horizontal run of same-colored pixels, in local shard coordinates"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "row",
+            "docs": [
+              "Row within the shard (0-89)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "start",
+            "docs": [
+              "First column of the run (0-89)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "length",
+            "docs": [
+              "Number of pixels in the run; start + length must not exceed 90"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "color",
+            "docs": [
+              "Color index (1-255, 0 is reserved for transparent)"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
     },
     {
-      "name": "shardInitialized",
-      "discriminator": [
-        183,
-        80,
-        70,
-        77,
-        210,
-        226,
-        161,
-        150
-      ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "invalidShardCoord",
-      "msg": "Invalid shard coordinates: must be 0-5825"
+      "name": "pixelRunsPlaced",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "runs",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "pixelRun"
+                }
+              }
+            }
+          },
+          {
+            "name": "changed",
+            "type": "u32"
+          },
+          {
+            "name": "painter",
+            "type": "pubkey"
+          },
+          {
+            "name": "mainWallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6001,
-      "name": "invalidPixelCoord",
-      "msg": "Invalid pixel coordinates: must be 0-524287"
+      "name": "pixelShard",
+      "docs": [
+        "A single shard of the pixel canvas",
+        "Each shard stores 8,100 pixels (90×90 grid) using 8-bit colors = ~8KB",
+        "Up to 33,942,276 shards (5826×5826 grid) can cover the full 524,288×524,288 canvas",
+        "Shards are created on-demand when users paint in new regions",
+        "Zero-copy so a single pixel write touches one byte instead of re-serializing the whole shard;",
+        "packed so the account carries no padding (8,170 bytes, 21 more than the baseline Borsh layout)"
+      ],
+      "serialization": "bytemuck",
+      "repr": {
+        "kind": "c",
+        "packed": true
+      },
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "foreignPixels",
+            "docs": [
+              "Running total of pixels placed by non-owners without a cooldown pass, paid out via claim_earnings"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lastActivity",
+            "docs": [
+              "Unix timestamp of the last placement or erase on this shard"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "lastForeignPaint",
+            "docs": [
+              "Unix timestamp of the last pixel placed by a non-owner (0 = never)"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "creator",
+            "docs": [
+              "Creator of the shard (who paid for initialization)"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "pixels",
+            "docs": [
+              "Pixel data - 8-bit storage (1 byte per pixel)",
+              "Index = local_y * 90 + local_x",
+              "Value = color_index (0 = unset/transparent, 1-255 = palette colors)"
+            ],
+            "type": {
+              "array": [
+                "u8",
+                8100
+              ]
+            }
+          },
+          {
+            "name": "shardX",
+            "docs": [
+              "Shard X coordinate (0-5825)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "docs": [
+              "Shard Y coordinate (0-5825)"
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "PDA bump seed"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "version",
+            "docs": [
+              "Layout version, upgraded by migrate_shard"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
     },
     {
-      "code": 6002,
-      "name": "shardMismatch",
-      "msg": "Shard coordinates don't match pixel location"
+      "name": "rectFilled",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "x",
+            "type": "u8"
+          },
+          {
+            "name": "y",
+            "type": "u8"
+          },
+          {
+            "name": "width",
+            "type": "u8"
+          },
+          {
+            "name": "height",
+            "type": "u8"
+          },
+          {
+            "name": "color",
+            "type": "u8"
+          },
+          {
+            "name": "changed",
+            "type": "u32"
+          },
+          {
+            "name": "painter",
+            "type": "pubkey"
+          },
+          {
+            "name": "mainWallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6003,
-      "name": "invalidColor",
-      "msg": "Invalid color: must be 1-255"
+      "name": "regionRead",
+      "docs": [
+        "Result of get_region"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "creator",
+            "type": "pubkey"
+          },
+          {
+            "name": "colors",
+            "docs": [
+              "Colors of the region, row by row (index = row * width + column)"
+            ],
+            "type": "bytes"
+          }
+        ]
+      }
     },
     {
-      "code": 6004,
-      "name": "invalidAuth",
-      "msg": "Invalid authentication"
+      "name": "sessionAccount",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mainAddress",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "authNonce",
+            "docs": [
+              "Nonce of the main wallet authorization that created this session"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "expiresAt",
+            "docs": [
+              "Unix timestamp after which the session stops working (0 = never)"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "revoked",
+            "docs": [
+              "Set by the main wallet to disable a leaked or retired session key"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "version",
+            "docs": [
+              "Layout version, upgraded by migrate_session"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
     },
     {
-      "code": 6005,
-      "name": "cooldown",
-      "msg": "Cooldown active: limit reached"
+      "name": "sessionRevoked",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mainWallet",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6006,
-      "name": "emptyBulkPixels",
-      "msg": "Bulk pixels array is empty"
+      "name": "shardClosed",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "owner",
+            "type": "pubkey"
+          },
+          {
+            "name": "refunded",
+            "docs": [
+              "Rent returned to the owner for the shard, deed and earnings accounts"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
     },
     {
-      "code": 6007,
-      "name": "bulkTooLarge",
-      "msg": "Bulk pixels exceeds maximum limit of 50"
+      "name": "shardCoord",
+      "docs": [
+        "Shard coordinates, used to address shards passed in remaining_accounts"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          }
+        ]
+      }
     },
     {
-      "code": 6008,
-      "name": "bulkExceedsCooldown",
-      "msg": "Bulk placement would exceed cooldown limit"
-    }
-  ],
-  "types": [
-    {
-      "name": "bulkPixel",
+      "name": "shardDeed",
       "docs": [
-        "Pixel data for bulk placement",
-        "Uses local coordinates within a shard (0-89)"
+        "Current owner of a shard",
+        "Never delegated, so ownership can change on the base layer while the shard's pixels",
+        "live on the ER; the ER reads it as a cloned read-only account during placement"
       ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "localX",
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "owner",
             "docs": [
-              "Local X coordinate within shard (0-89)"
+              "Main wallet that owns the shard and paints on it without cooldown"
             ],
-            "type": "u8"
+            "type": "pubkey"
           },
           {
-            "name": "localY",
+            "name": "passPriceLamports",
             "docs": [
-              "Local Y coordinate within shard (0-89)"
+              "Price of a cooldown pass for this shard in lamports (0 = not sold for SOL)"
             ],
-            "type": "u8"
+            "type": "u64"
           },
           {
-            "name": "color",
+            "name": "passPriceTokens",
             "docs": [
-              "Color index (1-255, 0 is reserved for transparent)"
+              "Price of a cooldown pass for this shard in project token base units (0 = not sold for tokens)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lastActivity",
+            "docs": [
+              "Unix timestamp of the owner's last action on the deed (transfer, listing, pricing, claim)"
             ],
+            "type": "i64"
+          },
+          {
+            "name": "frozen",
+            "docs": [
+              "Set by a moderator to make the shard read-only"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "bump",
             "type": "u8"
           }
         ]
       }
     },
     {
-      "name": "pixelChanged",
+      "name": "shardEarnings",
+      "docs": [
+        "How much of a shard's foreign_pixels have already been paid out as tokens",
+        "Base-layer only, so claiming never needs the shard itself to be writable"
+      ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "px",
-            "type": "u32"
+            "name": "shardX",
+            "type": "u16"
           },
           {
-            "name": "py",
-            "type": "u32"
+            "name": "shardY",
+            "type": "u16"
           },
           {
-            "name": "color",
+            "name": "claimedPixels",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
             "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "shardFreezeChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
           },
           {
-            "name": "painter",
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "frozen",
+            "type": "bool"
+          },
+          {
+            "name": "moderator",
+            "type": "pubkey"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "shardInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shardX",
+            "type": "u16"
+          },
+          {
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "creator",
             "type": "pubkey"
           },
           {
@@ -1174,86 +7454,79 @@ export type Magicplace = {
       }
     },
     {
-      "name": "pixelShard",
+      "name": "shardListing",
       "docs": [
-        "A single shard of the pixel canvas",
-        "Each shard stores 8,100 pixels (90×90 grid) using 8-bit colors = ~8KB",
-        "Up to 33,942,276 shards (5826×5826 grid) can cover the full 524,288×524,288 canvas",
-        "Shards are created on-demand when users paint in new regions"
+        "A fixed-price sale offer for a shard"
       ],
       "type": {
         "kind": "struct",
         "fields": [
           {
             "name": "shardX",
-            "docs": [
-              "Shard X coordinate (0-5825)"
-            ],
             "type": "u16"
           },
           {
             "name": "shardY",
-            "docs": [
-              "Shard Y coordinate (0-5825)"
-            ],
             "type": "u16"
           },
           {
-            "name": "pixels",
+            "name": "seller",
             "docs": [
-              "Pixel data - 8-bit storage (1 byte per pixel)",
-              "Index = local_y * 90 + local_x",
-              "Value = color_index (0 = unset/transparent, 1-255 = palette colors)"
+              "Owner at listing time, who receives the proceeds"
             ],
-            "type": "bytes"
+            "type": "pubkey"
           },
           {
-            "name": "creator",
+            "name": "price",
             "docs": [
-              "Creator of the shard (who paid for initialization)"
+              "Asking price in lamports"
             ],
-            "type": "pubkey"
+            "type": "u64"
           },
           {
             "name": "bump",
-            "docs": [
-              "PDA bump seed"
-            ],
             "type": "u8"
           }
         ]
       }
     },
     {
-      "name": "sessionAccount",
+      "name": "shardReclaimed",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "mainAddress",
-            "type": "pubkey"
+            "name": "shardX",
+            "type": "u16"
           },
           {
-            "name": "authority",
+            "name": "shardY",
+            "type": "u16"
+          },
+          {
+            "name": "previousOwner",
             "type": "pubkey"
           },
           {
-            "name": "cooldownCounter",
-            "type": "u8"
+            "name": "newOwner",
+            "type": "pubkey"
           },
           {
-            "name": "lastPlaceTimestamp",
+            "name": "fee",
+            "docs": [
+              "Fee paid to the treasury in lamports"
+            ],
             "type": "u64"
           },
           {
-            "name": "bump",
-            "type": "u8"
+            "name": "timestamp",
+            "type": "u64"
           }
         ]
       }
     },
     {
-      "name": "shardInitialized",
+      "name": "shardTransferred",
       "type": {
         "kind": "struct",
         "fields": [
@@ -1266,19 +7539,118 @@ export type Magicplace = {
             "type": "u16"
           },
           {
-            "name": "creator",
+            "name": "from",
             "type": "pubkey"
           },
           {
-            "name": "mainWallet",
+            "name": "to",
             "type": "pubkey"
           },
+          {
+            "name": "price",
+            "docs": [
+              "Sale price in lamports (0 for a direct transfer)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "shardsCommitted",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "shards",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "shardCoord"
+                }
+              }
+            }
+          },
           {
             "name": "timestamp",
             "type": "u64"
           }
         ]
       }
+    },
+    {
+      "name": "treasury",
+      "docs": [
+        "Program-owned account that accumulates platform fees"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "walletProfile",
+      "docs": [
+        "Authoritative per-main-wallet state, seeded by the main wallet address",
+        "Cooldown lives here so minting extra session keys can't reset it"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mainAddress",
+            "type": "pubkey"
+          },
+          {
+            "name": "sessions",
+            "docs": [
+              "Session key authorities currently registered for this wallet"
+            ],
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "lastAuthNonce",
+            "docs": [
+              "Highest authorization nonce consumed; new sessions must use a larger one"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "cooldownCounter",
+            "type": "u8"
+          },
+          {
+            "name": "lastPlaceTimestamp",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          }
+        ]
+      }
+    }
+  ],
+  "constants": [
+    {
+      "name": "clusterTag",
+      "docs": [
+        "Cluster tag bound into session authorizations so a signature cannot be replayed across clusters",
+        "Chosen by the `devnet`/`mainnet` build features and published in the IDL for clients to sign"
+      ],
+      "type": "string",
+      "value": "\"devnet\""
     }
   ]
 };
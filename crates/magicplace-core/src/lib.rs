@@ -0,0 +1,193 @@
+//! Canvas math and placement rules for magicplace, free of any Solana or Anchor dependency
+//!
+//! The on-chain program uses these for every coordinate, color and cooldown check, so
+//! off-chain tools that link this crate agree with it pixel for pixel.
+
+// ========================================
+// Canvas Configuration - 524,288 x 524,288 with dynamic sharding
+// ========================================
+
+/// Total canvas resolution per dimension (2^19 = 524,288)
+pub const CANVAS_RES: u32 = 524288;
+
+/// Each shard is 90x90 pixels
+pub const SHARD_DIMENSION: u32 = 90;
+
+/// Number of shards per dimension (ceiling division: 524,288 / 90 = 5,826)
+pub const SHARDS_PER_DIM: u32 = CANVAS_RES.div_ceil(SHARD_DIMENSION);
+
+/// Total pixels stored in each shard (90 * 90 = 8,100)
+pub const PIXELS_PER_SHARD: usize = (SHARD_DIMENSION * SHARD_DIMENSION) as usize;
+
+// ========================================
+// Coordinates
+// ========================================
+// Global pixels (px, py) live in 0..CANVAS_RES; shards (shard_x, shard_y) in 0..SHARDS_PER_DIM;
+// local pixels (local_x, local_y) in 0..SHARD_DIMENSION, stored row-major in a shard. The canvas
+// is not a multiple of SHARD_DIMENSION, so the last row and column of shards are partial: only
+// their ShardExtent lies on the canvas.
+
+/// Whether a global pixel lies on the canvas
+pub fn is_on_canvas(px: u32, py: u32) -> bool {
+    px < CANVAS_RES && py < CANVAS_RES
+}
+
+/// Whether shard coordinates lie inside the shard grid
+pub fn is_valid_shard(shard_x: u16, shard_y: u16) -> bool {
+    (shard_x as u32) < SHARDS_PER_DIM && (shard_y as u32) < SHARDS_PER_DIM
+}
+
+/// Shard containing a global pixel; callers check `is_on_canvas` first
+pub fn shard_of(px: u32, py: u32) -> (u16, u16) {
+    ((px / SHARD_DIMENSION) as u16, (py / SHARD_DIMENSION) as u16)
+}
+
+/// Local coordinates of a global pixel within its shard
+pub fn local_of(px: u32, py: u32) -> (u8, u8) {
+    ((px % SHARD_DIMENSION) as u8, (py % SHARD_DIMENSION) as u8)
+}
+
+/// Global coordinates of a shard's top-left pixel
+pub fn shard_origin(shard_x: u16, shard_y: u16) -> (u32, u32) {
+    (shard_x as u32 * SHARD_DIMENSION, shard_y as u32 * SHARD_DIMENSION)
+}
+
+/// Global coordinates of a local pixel of a shard
+pub fn global_of(shard_x: u16, shard_y: u16, local_x: u8, local_y: u8) -> (u32, u32) {
+    let (base_px, base_py) = shard_origin(shard_x, shard_y);
+    (base_px + local_x as u32, base_py + local_y as u32)
+}
+
+/// Index of a local pixel in its shard's pixel array (local_y * 90 + local_x)
+pub fn local_index(local_x: u8, local_y: u8) -> usize {
+    local_y as usize * SHARD_DIMENSION as usize + local_x as usize
+}
+
+/// Index of a global pixel in its shard's pixel array
+pub fn pixel_index(px: u32, py: u32) -> usize {
+    let (local_x, local_y) = local_of(px, py);
+    local_index(local_x, local_y)
+}
+
+/// Pixels of a last-column (last-row) shard's width (height) that lie on the canvas (524,288 - 5,825 * 90 = 38)
+const EDGE_SHARD_DIMENSION: u32 = CANVAS_RES - (SHARDS_PER_DIM - 1) * SHARD_DIMENSION;
+
+/// The on-canvas part of a shard, anchored at its top-left pixel: 90x90 for interior shards,
+/// 38 pixels wide (tall) for shards in the last column (row)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardExtent {
+    pub width: u8,
+    pub height: u8,
+}
+
+impl ShardExtent {
+    /// Extent of a shard; callers check `is_valid_shard` first
+    pub fn of(shard_x: u16, shard_y: u16) -> Self {
+        let side = |shard: u16| {
+            if shard as u32 == SHARDS_PER_DIM - 1 { EDGE_SHARD_DIMENSION as u8 } else { SHARD_DIMENSION as u8 }
+        };
+        Self { width: side(shard_x), height: side(shard_y) }
+    }
+
+    /// Whether a local pixel lies on the canvas
+    pub fn contains(&self, local_x: u8, local_y: u8) -> bool {
+        local_x < self.width && local_y < self.height
+    }
+
+    /// Whether a non-empty `length`-pixel run of row `row` from column `start` lies on the canvas
+    pub fn contains_span(&self, row: u8, start: u8, length: u8) -> bool {
+        length > 0 && row < self.height && start as u32 + length as u32 <= self.width as u32
+    }
+
+    /// Whether a non-empty `width` x `height` rectangle with its top-left corner at `(x, y)` lies on the canvas
+    pub fn contains_rect(&self, x: u8, y: u8, width: u8, height: u8) -> bool {
+        width > 0
+            && height > 0
+            && x as u32 + width as u32 <= self.width as u32
+            && y as u32 + height as u32 <= self.height as u32
+    }
+}
+
+// ========================================
+// Colors
+// ========================================
+
+/// Whether `color` is a paintable palette index; 0 is reserved for unset/transparent
+pub fn is_valid_color(color: u8, available_colors: u8) -> bool {
+    (1..=available_colors).contains(&color)
+}
+
+// ========================================
+// Cooldown
+// ========================================
+// Non-owners may place `limit` pixels in a burst; the pixel that reaches the limit starts a
+// `period`-second cooldown, after which the burst allowance resets.
+
+/// Burst limit and cooldown length, from the game config
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CooldownRules {
+    /// Max pixels a non-owner may place before the cooldown kicks in
+    pub limit: u8,
+    /// Cooldown length in seconds
+    pub period: u64,
+}
+
+/// Per-wallet cooldown state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cooldown {
+    /// Pixels placed in the current burst
+    pub counter: u8,
+    /// Unix timestamp at which the burst hit the limit
+    pub last_place_timestamp: u64,
+}
+
+/// Why a cooldown charge was refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CooldownError {
+    /// The burst is spent and the cooldown period has not passed
+    CoolingDown,
+    /// The charge is larger than what is left of the burst
+    ExceedsLimit,
+}
+
+impl Cooldown {
+    /// Whether the burst is spent and still cooling down at `now`
+    pub fn is_cooling_down(&self, rules: CooldownRules, now: u64) -> bool {
+        self.counter >= rules.limit && now.saturating_sub(self.last_place_timestamp) < rules.period
+    }
+
+    /// Pixels that could be placed at `now` in one charge
+    pub fn available(&self, rules: CooldownRules, now: u64) -> u8 {
+        if self.counter >= rules.limit {
+            if self.is_cooling_down(rules, now) { 0 } else { rules.limit }
+        } else {
+            rules.limit - self.counter
+        }
+    }
+
+    /// Charge `pixels` against the burst limit, resetting it once the cooldown period has passed
+    /// Leaves the state untouched when the charge is refused
+    pub fn charge(&mut self, pixels: u8, rules: CooldownRules, now: u64) -> Result<(), CooldownError> {
+        let mut next = *self;
+        if next.counter >= rules.limit {
+            if next.is_cooling_down(rules, now) {
+                return Err(CooldownError::CoolingDown);
+            }
+            next.counter = 0;
+        }
+
+        // Checked, not saturating: with a limit of 255 a saturated sum would slip under it
+        next.counter = next
+            .counter
+            .checked_add(pixels)
+            .filter(|&counter| counter <= rules.limit)
+            .ok_or(CooldownError::ExceedsLimit)?;
+
+        // The pixel that reaches the limit starts the cooldown
+        if next.counter >= rules.limit {
+            next.last_place_timestamp = now;
+        }
+        *self = next;
+        Ok(())
+    }
+}
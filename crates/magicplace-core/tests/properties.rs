@@ -0,0 +1,180 @@
+//! Properties of the canvas math and the cooldown state machine
+
+use magicplace_core::*;
+use proptest::prelude::*;
+
+fn canvas_pixel() -> impl Strategy<Value = (u32, u32)> {
+    (0..CANVAS_RES, 0..CANVAS_RES)
+}
+
+/// Any shard, with the partial last row and column drawn about half the time
+fn shard() -> impl Strategy<Value = (u16, u16)> {
+    let last = SHARDS_PER_DIM as u16 - 1;
+    let side = prop_oneof![0..=last, Just(last)];
+    (side.clone(), side)
+}
+
+fn local_pixel() -> impl Strategy<Value = (u8, u8)> {
+    (0..SHARD_DIMENSION as u8, 0..SHARD_DIMENSION as u8)
+}
+
+fn rules() -> impl Strategy<Value = CooldownRules> {
+    (1..=u8::MAX, 0..10_000u64).prop_map(|(limit, period)| CooldownRules { limit, period })
+}
+
+proptest! {
+    #[test]
+    fn global_round_trips_through_shard_and_local((px, py) in canvas_pixel()) {
+        prop_assert!(is_on_canvas(px, py));
+        let (shard_x, shard_y) = shard_of(px, py);
+        let (local_x, local_y) = local_of(px, py);
+        prop_assert!(is_valid_shard(shard_x, shard_y));
+        prop_assert!(ShardExtent::of(shard_x, shard_y).contains(local_x, local_y));
+        prop_assert_eq!(global_of(shard_x, shard_y, local_x, local_y), (px, py));
+    }
+
+    #[test]
+    fn shard_and_local_round_trip_through_global((shard_x, shard_y) in shard(), (local_x, local_y) in local_pixel()) {
+        let (px, py) = global_of(shard_x, shard_y, local_x, local_y);
+        prop_assert_eq!(shard_of(px, py), (shard_x, shard_y));
+        prop_assert_eq!(local_of(px, py), (local_x, local_y));
+        prop_assert_eq!(pixel_index(px, py), local_index(local_x, local_y));
+    }
+
+    #[test]
+    fn local_index_is_row_major_and_in_bounds((local_x, local_y) in local_pixel()) {
+        let index = local_index(local_x, local_y);
+        prop_assert!(index < PIXELS_PER_SHARD);
+        prop_assert_eq!((index % SHARD_DIMENSION as usize, index / SHARD_DIMENSION as usize), (local_x as usize, local_y as usize));
+    }
+
+    #[test]
+    fn off_canvas_pixels_are_rejected(px in CANVAS_RES.., py in any::<u32>()) {
+        prop_assert!(!is_on_canvas(px, py));
+        prop_assert!(!is_on_canvas(py, px));
+    }
+
+    #[test]
+    fn a_shard_extent_is_exactly_its_on_canvas_pixels((shard_x, shard_y) in shard(), (local_x, local_y) in local_pixel()) {
+        let (px, py) = global_of(shard_x, shard_y, local_x, local_y);
+        prop_assert_eq!(ShardExtent::of(shard_x, shard_y).contains(local_x, local_y), is_on_canvas(px, py));
+    }
+
+    #[test]
+    fn spans_and_rects_fit_exactly_when_their_far_corner_is_on_canvas(
+        (shard_x, shard_y) in shard(),
+        x in any::<u8>(),
+        y in any::<u8>(),
+        width in any::<u8>(),
+        height in any::<u8>(),
+    ) {
+        let extent = ShardExtent::of(shard_x, shard_y);
+        let fits = |x: u8, y: u8, width: u8, height: u8| {
+            width > 0 && height > 0 && {
+                let (right, bottom) = (x as u32 + width as u32 - 1, y as u32 + height as u32 - 1);
+                right < 90 && bottom < 90 && extent.contains(right as u8, bottom as u8)
+            }
+        };
+        prop_assert_eq!(extent.contains_rect(x, y, width, height), fits(x, y, width, height));
+        prop_assert_eq!(extent.contains_span(y, x, width), fits(x, y, width, 1));
+    }
+
+    #[test]
+    fn only_palette_colors_are_valid(color in any::<u8>(), available in any::<u8>()) {
+        prop_assert_eq!(is_valid_color(color, available), color != 0 && color <= available);
+    }
+
+    #[test]
+    fn a_charge_succeeds_exactly_when_it_fits(
+        rules in rules(),
+        counter in any::<u8>(),
+        last in 0..1_000_000u64,
+        elapsed in 0..20_000u64,
+        pixels in any::<u8>(),
+    ) {
+        let before = Cooldown { counter: counter.min(rules.limit), last_place_timestamp: last };
+        let now = last + elapsed;
+        let mut after = before;
+        match after.charge(pixels, rules, now) {
+            Ok(()) => {
+                prop_assert!(pixels <= before.available(rules, now));
+                prop_assert!(after.counter <= rules.limit);
+                if after.counter == rules.limit {
+                    prop_assert_eq!(after.last_place_timestamp, now);
+                }
+            }
+            Err(error) => {
+                prop_assert_eq!(after, before);
+                if before.is_cooling_down(rules, now) {
+                    prop_assert_eq!(error, CooldownError::CoolingDown);
+                } else {
+                    prop_assert_eq!(error, CooldownError::ExceedsLimit);
+                    prop_assert!(pixels > before.available(rules, now));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_spent_burst_resets_after_the_period(rules in rules(), start in 0..1_000_000u64, wait in 0..20_000u64) {
+        let mut cooldown = Cooldown::default();
+        cooldown.charge(rules.limit, rules, start).unwrap();
+        prop_assert_eq!(cooldown.last_place_timestamp, start);
+
+        let now = start + wait;
+        let mut next = cooldown;
+        if wait < rules.period {
+            prop_assert_eq!(next.charge(1, rules, now), Err(CooldownError::CoolingDown));
+            prop_assert_eq!(cooldown.available(rules, now), 0);
+        } else {
+            prop_assert_eq!(next.charge(1, rules, now), Ok(()));
+            prop_assert_eq!(next.counter, 1);
+            prop_assert_eq!(cooldown.available(rules, now), rules.limit);
+        }
+    }
+}
+
+#[test]
+fn edge_shards_are_partial() {
+    // 5,826 shards of 90 pixels overhang the canvas by 52 pixels in the last row and column
+    let last = SHARDS_PER_DIM as u16 - 1;
+    assert_eq!(shard_of(CANVAS_RES - 1, CANVAS_RES - 1), (last, last));
+    assert_eq!(local_of(CANVAS_RES - 1, 0), (37, 0));
+    assert!(is_valid_shard(last, last));
+    assert!(!is_valid_shard(last + 1, 0));
+    let (base_px, _) = shard_origin(last, 0);
+    assert!(is_on_canvas(base_px + 37, 0));
+    assert!(!is_on_canvas(base_px + 38, 0));
+
+    assert_eq!(ShardExtent::of(0, 0), ShardExtent { width: 90, height: 90 });
+    assert_eq!(ShardExtent::of(last, 7), ShardExtent { width: 38, height: 90 });
+    assert_eq!(ShardExtent::of(7, last), ShardExtent { width: 90, height: 38 });
+    assert_eq!(ShardExtent::of(last, last), ShardExtent { width: 38, height: 38 });
+    let edge = ShardExtent::of(last, 0);
+    assert!(edge.contains(37, 89) && !edge.contains(38, 0));
+    assert!(edge.contains_rect(0, 0, 38, 90) && !edge.contains_rect(30, 0, 9, 1));
+    assert!(edge.contains_span(89, 30, 8) && !edge.contains_span(0, 37, 2));
+}
+
+#[test]
+fn cooldown_walks_a_burst() {
+    let rules = CooldownRules { limit: 3, period: 30 };
+    let mut cooldown = Cooldown::default();
+    cooldown.charge(2, rules, 100).unwrap();
+    assert_eq!((cooldown.counter, cooldown.available(rules, 100)), (2, 1));
+    assert_eq!(cooldown.charge(2, rules, 101), Err(CooldownError::ExceedsLimit));
+    cooldown.charge(1, rules, 105).unwrap();
+    assert_eq!(cooldown, Cooldown { counter: 3, last_place_timestamp: 105 });
+    assert_eq!(cooldown.charge(1, rules, 134), Err(CooldownError::CoolingDown));
+    cooldown.charge(1, rules, 135).unwrap();
+    assert_eq!(cooldown.counter, 1);
+}
+
+#[test]
+fn cooldown_charges_do_not_overflow_a_full_limit() {
+    let rules = CooldownRules { limit: u8::MAX, period: 0 };
+    let mut cooldown = Cooldown { counter: 139, last_place_timestamp: 0 };
+    assert_eq!(cooldown.charge(117, rules, 0), Err(CooldownError::ExceedsLimit));
+    cooldown.charge(116, rules, 0).unwrap();
+    assert_eq!(cooldown.counter, u8::MAX);
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
 use anchor_lang::solana_program::sysvar::instructions::{self, load_instruction_at_checked};
 
 /// Ed25519 program ID: Ed25519SigVerify111111111111111111111111111
@@ -9,7 +10,13 @@ const ED25519_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
 ]);
 use ephemeral_rollups_sdk::anchor::{commit, delegate, ephemeral};
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
-use ephemeral_rollups_sdk::ephem::commit_accounts;
+use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
+use magicplace_core::{
+    global_of, is_on_canvas, is_valid_color, is_valid_shard, local_index, pixel_index, shard_of, Cooldown,
+    CooldownError, CooldownRules, ShardExtent, PIXELS_PER_SHARD,
+};
+use std::cell::{Ref, RefMut};
+use std::ops::{Deref, DerefMut};
 
 declare_id!("4j29Do6VWdMhfLBdi4n3AeWdVXNEzJNG72sFVUe9cUSe");
 
@@ -18,93 +25,163 @@ const DELEGATION_PROGRAM_ID: Pubkey = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjte
 // ========================================
 // Canvas Configuration - 524,288 x 524,288 with dynamic sharding
 // ========================================
-
-/// Total canvas resolution per dimension (2^19 = 524,288)
-const CANVAS_RES: u32 = 524288;
-
-/// Each shard is 90x90 pixels
-const SHARD_DIMENSION: u32 = 90;
-
-/// Number of shards per dimension (ceiling division: 524,288 / 90 = 5,826)
-const SHARDS_PER_DIM: u32 = (CANVAS_RES + SHARD_DIMENSION - 1) / SHARD_DIMENSION;
-
-/// Total pixels stored in each shard (90 * 90 = 8,100)
-const PIXELS_PER_SHARD: usize = (SHARD_DIMENSION * SHARD_DIMENSION) as usize;
+// Canvas dimensions and coordinate math live in magicplace-core, shared with off-chain tools
 
 /// Bytes needed to store pixels (1 byte per pixel using 8-bit colors)
 const BYTES_PER_SHARD: usize = PIXELS_PER_SHARD;
 
+/// Size of a PixelShard account as first deployed, before earnings were tracked
+pub const BASELINE_SHARD_ACCOUNT_LEN: usize = 8 + BaselinePixelShard::INIT_SPACE;
+
+/// Size of a SessionAccount as first deployed, before signed authorizations
+pub const BASELINE_SESSION_ACCOUNT_LEN: usize = 8 + BaselineSessionAccount::INIT_SPACE;
+
 /// Seed prefix for shard PDAs
 const SHARD_SEED: &[u8] = b"shard";
 
+/// Seed prefix for shard ownership deed PDAs
+const DEED_SEED: &[u8] = b"deed";
+
+/// Seed prefix for shard marketplace listing PDAs
+const LISTING_SEED: &[u8] = b"listing";
+
+/// Seed prefix for the global game config PDA
+const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed prefix for the platform treasury PDA
+const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Seed prefix for the project token mint PDA
+const MINT_SEED: &[u8] = b"mint";
+
+/// Seed prefix for per-shard earnings claim records
+const EARNINGS_SEED: &[u8] = b"earnings";
+
+/// Decimals of the project token
+const TOKEN_DECIMALS: u8 = 6;
+
+/// Foreign pixels placed on a shard that earn its owner one whole token
+const PIXELS_PER_TOKEN: u64 = 10;
+
+/// Seed prefix for cooldown pass PDAs
+const PASS_SEED: &[u8] = b"pass";
+
+/// How long a purchased cooldown pass lasts, in seconds (3 hours)
+const COOLDOWN_PASS_DURATION: i64 = 3 * 60 * 60;
+
+/// Seed prefix for per-main-wallet profile PDAs
+const PROFILE_SEED: &[u8] = b"wallet";
+
+/// Max session keys a single main wallet may have registered at once
+const MAX_SESSIONS_PER_WALLET: usize = 4;
+
+// Gameplay defaults written by initialize_config; live values are read from GameConfig
+
 /// Available colors using 8-bit storage (0 = unset/transparent, 1-255 = palette colors)
-const AVAILABLE_COLORS: u8 = 255;
+const DEFAULT_AVAILABLE_COLORS: u8 = 255;
 
 /// Max pixels allowed in a burst for non-owners
-const COOLDOWN_LIMIT: u8 = 60;
+const DEFAULT_COOLDOWN_LIMIT: u8 = 60;
 
 /// Cooldown period in seconds resetting the burst counter
-const COOLDOWN_PERIOD: u64 = 15;
+const DEFAULT_COOLDOWN_PERIOD: u64 = 15;
+
+/// Max pixels in a single place_pixels_bulk call
+const DEFAULT_MAX_BULK_PIXELS: u8 = 60;
+
+/// Platform fee discount in basis points for each shard beyond the first in initialize_shards
+const DEFAULT_BATCH_DISCOUNT_BPS: u16 = 100;
+
+/// Cap on the initialize_shards volume discount (basis points)
+const MAX_BATCH_DISCOUNT_BPS: u16 = 2500;
+
+/// Max shards created by one initialize_shards call; two accounts each, so large
+/// batches need an address lookup table
+const MAX_SHARDS_PER_BATCH: usize = 16;
+
+/// Max pixels returned by get_region; return data is capped at 1024 bytes
+/// and the creator and length prefix take 36 of them
+const MAX_REGION_PIXELS: usize = 960;
 
 #[ephemeral]
 #[program]
 pub mod magicplace {
     use super::*;
 
+    /// Create a session account for `authority` on behalf of `main_wallet`
+    /// The transaction must carry an Ed25519 verify instruction at index 0 over the
+    /// canonical message built by `session_auth_message(authority, nonce, expires_at)`
     pub fn initialize_user(
         ctx: Context<InitializeUser>,
         main_wallet: Pubkey,
-        _signature: [u8; 64],
+        signature: [u8; 64],
+        nonce: u64,
+        expires_at: i64,
     ) -> Result<()> {
         // Verify Ed25519 signature using Solana's native Ed25519 program
         // The frontend must include an Ed25519 verify instruction as the first instruction
         // in the transaction. This program reads the instructions sysvar to verify it.
         
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= expires_at, PixelError::AuthExpired);
+
         let ix_sysvar = &ctx.accounts.instructions_sysvar;
         
         // Load the first instruction (index 0) - should be the Ed25519 verify instruction
         let ed25519_ix = load_instruction_at_checked(0, ix_sysvar)
-            .map_err(|_| PixelError::InvalidAuth)?;
+            .map_err(|_| PixelError::InvalidEd25519Instruction)?;
         
         // Verify it's from the Ed25519 program
         require!(
             ed25519_ix.program_id == ED25519_PROGRAM_ID,
-            PixelError::InvalidAuth
+            PixelError::InvalidEd25519Instruction
         );
         
-        // Parse the Ed25519 instruction data to verify the signature matches
-        let ix_data = &ed25519_ix.data;
-        require!(ix_data.len() >= 2, PixelError::InvalidAuth);
-        
-        let num_signatures = ix_data[0];
-        require!(num_signatures >= 1, PixelError::InvalidAuth);
-        
-        // Parse the first signature header (starts at offset 2)
-        require!(ix_data.len() >= 18, PixelError::InvalidAuth); // 2 + 16 bytes header
-        
-        let pubkey_offset = u16::from_le_bytes([ix_data[6], ix_data[7]]) as usize;
-        
-        // Extract the public key from the instruction data
-        require!(ix_data.len() >= pubkey_offset + 32, PixelError::InvalidAuth);
-        let pubkey_bytes = &ix_data[pubkey_offset..pubkey_offset + 32];
-        let verified_pubkey = Pubkey::try_from(pubkey_bytes)
-            .map_err(|_| PixelError::InvalidAuth)?;
+        let verified = parse_ed25519_instruction(&ed25519_ix.data)?;
         
         // Verify the public key matches the main_wallet
         require!(
-            verified_pubkey == main_wallet,
-            PixelError::InvalidAuth
+            verified.pubkey == main_wallet.as_ref(),
+            PixelError::AuthPubkeyMismatch
+        );
+        require!(
+            verified.signature == signature.as_slice(),
+            PixelError::AuthSignatureMismatch
+        );
+
+        // The signed bytes must be exactly the canonical authorization for this session key
+        let expected_message = session_auth_message(&ctx.accounts.authority.key(), nonce, expires_at);
+        require!(
+            verified.message == expected_message.as_bytes(),
+            PixelError::AuthMessageMismatch
         );
         
         msg!("Ed25519 signature verified for main wallet: {}", main_wallet);
         
+        // Register the session on the wallet profile, which is created on first use
+        // Nonces must strictly increase so an old authorization can never be replayed
+        let profile = &mut ctx.accounts.profile;
+        if profile.main_address == Pubkey::default() {
+            profile.main_address = main_wallet;
+            profile.bump = ctx.bumps.profile;
+        }
+        require!(nonce > profile.last_auth_nonce, PixelError::AuthNonceReused);
+        require!(
+            profile.sessions.len() < MAX_SESSIONS_PER_WALLET,
+            PixelError::TooManySessions
+        );
+        profile.last_auth_nonce = nonce;
+        profile.sessions.push(ctx.accounts.authority.key());
+
         // Initialize the session account
         let user = &mut ctx.accounts.user;
         user.main_address = main_wallet;
         user.authority = ctx.accounts.authority.key();
-        user.cooldown_counter = 0;
-        user.last_place_timestamp = 0;
+        user.auth_nonce = nonce;
+        user.expires_at = 0;
+        user.revoked = false;
         user.bump = ctx.bumps.user;
+        user.version = SessionAccount::VERSION;
         
         msg!("Session account initialized for main wallet: {}", main_wallet);
         Ok(())
@@ -136,6 +213,240 @@ pub mod magicplace {
         Ok(())
     }
 
+    /// Delegate a main wallet's profile to Ephemeral Rollups
+    /// Placements charge cooldown against the profile, so it must live on the ER with the sessions
+    /// Only needs to happen once per wallet, by any of its registered session keys
+    pub fn delegate_profile(
+        ctx: Context<DelegateProfile>,
+        main_wallet: Pubkey,
+    ) -> Result<()> {
+        let profile = WalletProfile::try_deserialize(&mut &ctx.accounts.pda.data.borrow()[..])?;
+        require!(
+            profile.sessions.contains(&ctx.accounts.authority.key()),
+            PixelError::InvalidAuth
+        );
+
+        ctx.accounts.delegate_pda(
+            &ctx.accounts.authority,
+            &[PROFILE_SEED, main_wallet.as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+
+        msg!("Wallet profile delegated to ER for wallet: {}", main_wallet);
+        Ok(())
+    }
+
+    // ========================================
+    // Admin & Treasury
+    // ========================================
+
+    /// Create the global config and treasury; the program's upgrade authority becomes admin
+    /// Run once right after deployment. Gameplay values start at their defaults;
+    /// fees and reclaiming start disabled until set with update_config
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.moderator = ctx.accounts.admin.key();
+        config.paused = false;
+        config.params = ConfigParams::default();
+        config.bump = ctx.bumps.config;
+
+        ctx.accounts.treasury.bump = ctx.bumps.treasury;
+
+        msg!("Config initialized, admin {}", config.admin);
+        Ok(())
+    }
+
+    /// Replace all tunable game parameters at once
+    /// Placement instructions read these live, so changes apply without a program upgrade
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        params: ConfigParams,
+    ) -> Result<()> {
+        params.validate()?;
+        let config = &mut ctx.accounts.config;
+        config.params = params;
+
+        msg!("Config updated by {}", config.admin);
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            params,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Emergency brake: while paused, placing, erasing and shard creation are rejected
+    pub fn set_paused(
+        ctx: Context<UpdateConfig>,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.config.paused = paused;
+        msg!("Game paused: {}", paused);
+        Ok(())
+    }
+
+    /// Choose the wallet allowed to freeze individual shards
+    pub fn set_moderator(
+        ctx: Context<UpdateConfig>,
+        moderator: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.config.moderator = moderator;
+        msg!("Moderator set to {}", moderator);
+        Ok(())
+    }
+
+    /// Make a shard read-only (or writable again), signed by the moderator or admin
+    /// The flag lives on the deed, so this works on the base layer even while the shard is on the ER
+    pub fn freeze_shard(
+        ctx: Context<FreezeShard>,
+        shard_x: u16,
+        shard_y: u16,
+        frozen: bool,
+    ) -> Result<()> {
+        ctx.accounts.deed.frozen = frozen;
+
+        msg!("Shard ({}, {}) frozen: {}", shard_x, shard_y, frozen);
+        emit!(ShardFreezeChanged {
+            shard_x,
+            shard_y,
+            frozen,
+            moderator: ctx.accounts.moderator.key(),
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Move collected fees out of the treasury, keeping it rent-exempt
+    pub fn withdraw_treasury(
+        ctx: Context<WithdrawTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury.lamports().saturating_sub(rent_floor);
+        require!(amount <= available, PixelError::InsufficientTreasury);
+
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+
+        msg!("Withdrew {} lamports from treasury to {}", amount, ctx.accounts.recipient.key());
+        Ok(())
+    }
+
+    /// Create the project token mint; the config PDA is its mint authority
+    pub fn initialize_mint(_ctx: Context<InitializeMint>) -> Result<()> {
+        msg!("Project token mint initialized");
+        Ok(())
+    }
+
+    // ========================================
+    // Session Management (signed by the main wallet)
+    // ========================================
+
+    /// Revoke a session key so it can no longer paint
+    /// Runs on whichever layer currently holds the session and profile
+    pub fn revoke_session(
+        ctx: Context<RevokeSession>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.session.revoked = true;
+        ctx.accounts.profile.sessions.retain(|key| key != &authority);
+
+        msg!("Session {} revoked", authority);
+        emit!(SessionRevoked {
+            main_wallet: ctx.accounts.main_wallet.key(),
+            authority,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Revoke a delegated session on the ER and return it to the base layer
+    /// Once the undelegation lands, close_session can reclaim its rent
+    pub fn revoke_and_undelegate_session(
+        ctx: Context<RevokeAndUndelegateSession>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.session.revoked = true;
+        ctx.accounts.profile.sessions.retain(|key| key != &authority);
+
+        // Flush the revocation before the account is handed back to the base layer
+        ctx.accounts.session.exit(&crate::ID)?;
+        commit_and_undelegate_accounts(
+            &ctx.accounts.main_wallet,
+            vec![&ctx.accounts.session.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+
+        msg!("Session {} revoked and scheduled for undelegation", authority);
+        emit!(SessionRevoked {
+            main_wallet: ctx.accounts.main_wallet.key(),
+            authority,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Set the unix timestamp after which a session key stops working (0 = never expires)
+    pub fn set_session_expiry(
+        ctx: Context<SetSessionExpiry>,
+        _authority: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.session.expires_at = expires_at;
+        msg!("Session expiry set to {}", expires_at);
+        Ok(())
+    }
+
+    /// Close a revoked, undelegated session and refund its rent to the main wallet
+    pub fn close_session(
+        _ctx: Context<CloseSession>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        msg!("Session {} closed", authority);
+        Ok(())
+    }
+
+    /// Replace a session key with a new one in a single step on the base layer
+    /// The old session is closed (rent to the main wallet) and the new one is paid for by the main wallet
+    /// A delegated profile has to come back first, through undelegate_profile
+    pub fn rotate_session(
+        ctx: Context<RotateSession>,
+        old_authority: Pubkey,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.sessions.retain(|key| key != &old_authority);
+        require!(
+            profile.sessions.len() < MAX_SESSIONS_PER_WALLET,
+            PixelError::TooManySessions
+        );
+        profile.sessions.push(new_authority);
+
+        let old_session = &ctx.accounts.old_session;
+        let new_session = &mut ctx.accounts.new_session;
+        new_session.main_address = old_session.main_address;
+        new_session.authority = new_authority;
+        new_session.auth_nonce = old_session.auth_nonce;
+        new_session.expires_at = old_session.expires_at;
+        new_session.revoked = false;
+        new_session.bump = ctx.bumps.new_session;
+        new_session.version = SessionAccount::VERSION;
+
+        msg!("Session rotated from {} to {}", old_authority, new_authority);
+        emit!(SessionRevoked {
+            main_wallet: ctx.accounts.main_wallet.key(),
+            authority: old_authority,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
     // ========================================
     // Shard Management
     // ========================================
@@ -150,25 +461,47 @@ pub mod magicplace {
         shard_y: u16
     ) -> Result<()> {
         require!(
-            (shard_x as u32) < SHARDS_PER_DIM && (shard_y as u32) < SHARDS_PER_DIM,
+            is_valid_shard(shard_x, shard_y),
             PixelError::InvalidShardCoord
         );
         
         // Handle session account (potentially delegated)
-        let session_info = &ctx.accounts.session;
-        require!(
-            session_info.owner == &crate::ID || session_info.owner == &DELEGATION_PROGRAM_ID,
-            PixelError::InvalidAuth
-        );
+        let session = read_session(&ctx.accounts.session)?;
         
-        let session = SessionAccount::try_deserialize(&mut &session_info.data.borrow()[..])?;
-        
-        let shard = &mut ctx.accounts.shard;
+        // Platform fee on top of rent, stored in config so it can be tuned without a redeploy
+        let fee = ctx.accounts.config.params.shard_fee_lamports;
+        if fee > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
+        // A fresh zero-copy account is all zeros: blank pixels, no foreign paint
+        let shard = &mut ctx.accounts.shard.load_init()?;
         shard.shard_x = shard_x;
         shard.shard_y = shard_y;
-        shard.pixels = vec![0u8; BYTES_PER_SHARD];
         shard.creator = session.main_address;
+        shard.last_activity = Clock::get()?.unix_timestamp;
         shard.bump = ctx.bumps.shard;
+        shard.version = PixelShard::VERSION;
+
+        // Ownership lives in a deed that always stays on the base layer
+        let deed = &mut ctx.accounts.deed;
+        deed.shard_x = shard_x;
+        deed.shard_y = shard_y;
+        deed.owner = session.main_address;
+        deed.pass_price_lamports = 0;
+        deed.pass_price_tokens = 0;
+        deed.last_activity = shard.last_activity;
+        deed.frozen = false;
+        deed.bump = ctx.bumps.deed;
         
         msg!(
             "Shard ({}, {}) initialized with {} pixels ({} bytes packed)", 
@@ -185,6 +518,210 @@ pub mod magicplace {
         Ok(())
     }
 
+    /// Claim a shard if nobody has yet and paint a first batch of pixels on it, on the base layer
+    /// The first painter to land pays rent and the platform fee and becomes both creator and
+    /// deed owner, so the batch is cooldown-free. If the shard already exists the call only
+    /// paints, and only for the deed owner; anyone else has lost the race and gets
+    /// ShardAlreadyClaimed. Append delegate_shard to the same transaction to move it to the ER.
+    pub fn claim_and_paint(
+        ctx: Context<ClaimAndPaint>,
+        shard_x: u16,
+        shard_y: u16,
+        pixels: Vec<BulkPixel>,
+    ) -> Result<()> {
+        require!(
+            is_valid_shard(shard_x, shard_y),
+            PixelError::InvalidShardCoord
+        );
+        require!(!pixels.is_empty(), PixelError::EmptyBulkPixels);
+        let config = &ctx.accounts.config.params;
+        require!(pixels.len() <= config.max_bulk_pixels as usize, PixelError::BulkTooLarge);
+
+        // The session may already be delegated
+        let session = read_session(&ctx.accounts.session)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let deed = &mut ctx.accounts.deed;
+        let claimed = deed.owner == Pubkey::default();
+        let shard = &mut if claimed {
+            ctx.accounts.shard.load_init()?
+        } else {
+            ctx.accounts.shard.load_mut()?
+        };
+        if claimed {
+            let fee = config.shard_fee_lamports;
+            if fee > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: ctx.accounts.treasury.to_account_info(),
+                        },
+                    ),
+                    fee,
+                )?;
+            }
+
+            shard.shard_x = shard_x;
+            shard.shard_y = shard_y;
+            shard.creator = session.main_address;
+            shard.bump = ctx.bumps.shard;
+            shard.version = PixelShard::VERSION;
+
+            deed.shard_x = shard_x;
+            deed.shard_y = shard_y;
+            deed.owner = session.main_address;
+            deed.pass_price_lamports = 0;
+            deed.pass_price_tokens = 0;
+            deed.last_activity = now;
+            deed.frozen = false;
+            deed.bump = ctx.bumps.deed;
+
+            emit!(ShardInitialized {
+                shard_x,
+                shard_y,
+                creator: shard.creator,
+                main_wallet: session.main_address,
+                timestamp: now as u64,
+            });
+        } else {
+            require!(deed.owner == session.main_address, PixelError::ShardAlreadyClaimed);
+        }
+
+        // The painter owns the shard at this point, so no cooldown and no foreign pixels
+        let painter = ctx.accounts.authority.key();
+        let extent = ShardExtent::of(shard_x, shard_y);
+        for pixel in pixels.iter() {
+            require!(extent.contains(pixel.local_x, pixel.local_y), PixelError::InvalidPixelCoord);
+            require!(is_valid_color(pixel.color, config.available_colors), PixelError::InvalidColor);
+
+            let local_pixel_id = local_index(pixel.local_x, pixel.local_y);
+            shard.pixels[local_pixel_id] = pixel.color;
+
+            let (px, py) = global_of(shard_x, shard_y, pixel.local_x, pixel.local_y);
+            emit!(PixelChanged {
+                px,
+                py,
+                color: pixel.color,
+                painter,
+                main_wallet: session.main_address,
+                timestamp: now as u64,
+            });
+        }
+        shard.last_activity = now;
+
+        msg!(
+            "Shard ({}, {}) {} with {} pixels",
+            shard_x,
+            shard_y,
+            if claimed { "claimed and painted" } else { "painted" },
+            pixels.len()
+        );
+        Ok(())
+    }
+
+    /// Initialize a contiguous block of shards in one call, at a volume discount on the platform fee
+    /// remaining_accounts: a (shard, deed) pair of uninitialized PDAs for each entry of `shards`
+    /// The shards must be 4-connected (each touching another by an edge) and inside the canvas
+    pub fn initialize_shards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeShards<'info>>,
+        shards: Vec<ShardCoord>,
+    ) -> Result<()> {
+        require!(!shards.is_empty(), PixelError::EmptyShardList);
+        require!(shards.len() <= MAX_SHARDS_PER_BATCH, PixelError::TooManyShards);
+        require!(
+            ctx.remaining_accounts.len() == shards.len() * 2,
+            PixelError::InvalidShardAccount
+        );
+        for (i, coord) in shards.iter().enumerate() {
+            require!(
+                is_valid_shard(coord.shard_x, coord.shard_y),
+                PixelError::InvalidShardCoord
+            );
+            require!(!shards[..i].contains(coord), PixelError::DuplicateShard);
+        }
+
+        // Flood fill from the first shard; every shard must be reached
+        let mut reached = vec![false; shards.len()];
+        let mut frontier = vec![0];
+        reached[0] = true;
+        while let Some(current) = frontier.pop() {
+            let ShardCoord { shard_x, shard_y } = shards[current];
+            for (i, other) in shards.iter().enumerate() {
+                if !reached[i] && shard_x.abs_diff(other.shard_x) + shard_y.abs_diff(other.shard_y) == 1 {
+                    reached[i] = true;
+                    frontier.push(i);
+                }
+            }
+        }
+        require!(reached.iter().all(|&r| r), PixelError::ShardsNotContiguous);
+
+        let session = read_session(&ctx.accounts.session)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payer = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        let fee = ctx.accounts.config.params.batch_shard_fee(shards.len());
+        if fee > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.clone(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
+        for (coord, pair) in shards.iter().zip(ctx.remaining_accounts.chunks(2)) {
+            let ShardCoord { shard_x, shard_y } = *coord;
+            let (x, y) = (shard_x.to_le_bytes(), shard_y.to_le_bytes());
+
+            let space = 8 + PixelShard::INIT_SPACE;
+            let bump = create_pda(&pair[0], &[SHARD_SEED, &x, &y], space, &payer, &system_program)?;
+            let shard = AccountLoader::<PixelShard>::try_from_unchecked(&crate::ID, &pair[0])?;
+            {
+                let shard = &mut shard.load_init()?;
+                shard.shard_x = shard_x;
+                shard.shard_y = shard_y;
+                shard.creator = session.main_address;
+                shard.last_activity = now;
+                shard.bump = bump;
+                shard.version = PixelShard::VERSION;
+            }
+            // Writes the discriminator, as Anchor does for `init`
+            shard.exit(&crate::ID)?;
+
+            let space = 8 + ShardDeed::INIT_SPACE;
+            let bump = create_pda(&pair[1], &[DEED_SEED, &x, &y], space, &payer, &system_program)?;
+            let deed = ShardDeed {
+                shard_x,
+                shard_y,
+                owner: session.main_address,
+                pass_price_lamports: 0,
+                pass_price_tokens: 0,
+                last_activity: now,
+                frozen: false,
+                bump,
+            };
+            deed.try_serialize(&mut &mut pair[1].try_borrow_mut_data()?[..])?;
+
+            emit!(ShardInitialized {
+                shard_x,
+                shard_y,
+                creator: session.main_address,
+                main_wallet: session.main_address,
+                timestamp: now as u64,
+            });
+        }
+
+        msg!("{} shards initialized for {} lamports", shards.len(), fee);
+        Ok(())
+    }
+
     /// Delegate an existing shard to Ephemeral Rollups
     /// This should be called after initialize_shard in a separate transaction
     pub fn delegate_shard(
@@ -206,85 +743,636 @@ pub mod magicplace {
         Ok(())
     }
 
-    // ========================================
-    // Pixel Placement
-    // ========================================
-
-    /// Place a pixel using global coordinates
-    /// px, py: 0 to 524,287 (global pixel coordinates)
-    /// color: 1-15 (0 is reserved for unset/transparent, 4-bit packing)
-    pub fn place_pixel(
-        ctx: Context<PlacePixel>,
-        _shard_x: u16,  // Used in seeds validation
-        _shard_y: u16,  // Used in seeds validation
-        px: u32,
-        py: u32,
-        color: u8
+    /// Upgrade a shard to the current layout
+    /// A shard still in the baseline Borsh layout (version 0) is rewritten in place into the zero-copy
+    /// layout, stamped with PixelShard::VERSION; later layouts step up from `version`. Until then the
+    /// shard still paints and reads but tracks no activity or earnings; whatever needs them gets
+    /// ShardNeedsMigration
+    /// Permissionless; `payer` tops up rent if needed. The shard must be undelegated (on the base layer):
+    /// a delegated baseline shard comes back through undelegate_baseline_shard. Migrating an up-to-date
+    /// shard is a no-op
+    pub fn migrate_shard(
+        ctx: Context<MigrateShard>,
+        shard_x: u16,
+        shard_y: u16,
     ) -> Result<()> {
-        require!(px < CANVAS_RES && py < CANVAS_RES, PixelError::InvalidPixelCoord);
-        require!(color > 0 && color <= AVAILABLE_COLORS, PixelError::InvalidColor);
-        
-        // Calculate expected shard coordinates
-        let expected_shard_x = (px / SHARD_DIMENSION) as u16;
-        let expected_shard_y = (py / SHARD_DIMENSION) as u16;
-        
-        // Verify the correct shard was passed
-        let shard = &mut ctx.accounts.shard;
+        let info = ctx.accounts.shard.to_account_info();
+        require!(info.owner == &crate::ID, PixelError::InvalidShardAccount);
+        let new_len = 8 + PixelShard::INIT_SPACE;
+
+        let version = shard_version(&info.try_borrow_data()?)?;
+        if version == PixelShard::VERSION {
+            msg!("Shard ({}, {}) already at version {}", shard_x, shard_y, version);
+            return Ok(());
+        }
+
+        // Version 0, the baseline Borsh layout, is the only older one so far
+        let baseline = BaselinePixelShard::deserialize(&mut &info.try_borrow_data()?[8..])?;
         require!(
-            shard.shard_x == expected_shard_x && shard.shard_y == expected_shard_y,
-            PixelError::ShardMismatch
+            baseline.shard_x == shard_x && baseline.shard_y == shard_y && baseline.pixels.len() == PIXELS_PER_SHARD,
+            PixelError::InvalidShardAccount
         );
 
-        let session = &mut ctx.accounts.session;
+        let shortfall = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(new_len)?;
 
-        if shard.creator != session.main_address {
-             let clock = Clock::get()?;
-             let now = clock.unix_timestamp as u64;
+        // Every field is written, so nothing of the old layout survives; built in place
+        // because the shard is too large for the stack. The baseline tracked no earnings
+        // or activity, so those start from zero
+        let mut data = info.try_borrow_mut_data()?;
+        let shard: &mut PixelShard = bytemuck::from_bytes_mut(&mut data[8..]);
+        shard.foreign_pixels = 0;
+        shard.last_activity = 0;
+        shard.last_foreign_paint = 0;
+        shard.creator = baseline.creator;
+        shard.pixels.copy_from_slice(&baseline.pixels);
+        shard.shard_x = baseline.shard_x;
+        shard.shard_y = baseline.shard_y;
+        shard.bump = baseline.bump;
+        shard.version = PixelShard::VERSION;
 
-             if session.cooldown_counter >= COOLDOWN_LIMIT {
-                 if now.saturating_sub(session.last_place_timestamp) >= COOLDOWN_PERIOD {
-                     session.cooldown_counter = 0;
-                 } else {
-                     return err!(PixelError::Cooldown);
-                 }
-             }
+        msg!("Shard ({}, {}) migrated to version {}", shard_x, shard_y, shard.version);
+        Ok(())
+    }
 
-             session.cooldown_counter = session.cooldown_counter.checked_add(1).unwrap();
+    /// Upgrade a session account to the current layout, growing it in place
+    /// A baseline session (version 0) was registered by a replayable message, so its main wallet signs
+    /// the upgrade, pays any extra rent and gets the key registered on its profile as initialize_user
+    /// does; the baseline cooldown is dropped, since it now lives on the profile. Later layouts step up
+    /// from `version`. The session and profile must be undelegated (on the base layer); migrating an
+    /// up-to-date session is a no-op
+    pub fn migrate_session(ctx: Context<MigrateSession>, authority: Pubkey) -> Result<()> {
+        let info = ctx.accounts.session.to_account_info();
+        let main_wallet = ctx.accounts.main_wallet.key();
 
-             if session.cooldown_counter >= COOLDOWN_LIMIT {
-                 session.last_place_timestamp = now;
-             }
+        let version = session_version(&info.try_borrow_data()?)?;
+        if version == SessionAccount::VERSION {
+            let session = SessionAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(session.main_address, main_wallet, PixelError::InvalidAuth);
+            msg!("Session {} already at version {}", authority, version);
+            return Ok(());
         }
-        
-        // Calculate local pixel position within the shard
-        let local_x = px % SHARD_DIMENSION;
-        let local_y = py % SHARD_DIMENSION;
-        let local_pixel_id = (local_y * SHARD_DIMENSION + local_x) as usize;
-        
-        // 8-bit storage: 1 byte per pixel, direct indexing
-        shard.pixels[local_pixel_id] = color;
-        
-        msg!(
-            "Pixel ({}, {}) -> Shard ({}, {}) index {} = color {}",
-            px, py,
-            shard.shard_x, shard.shard_y,
-            local_pixel_id,
-            color
+
+        // Version 0, the baseline layout, is the only older one so far
+        let baseline = BaselineSessionAccount::deserialize(&mut &info.try_borrow_data()?[8..])?;
+        require!(
+            baseline.main_address == main_wallet && baseline.authority == authority,
+            PixelError::InvalidAuth
         );
 
-        emit!(PixelChanged {
-            px,
-            py,
-            color,
-            painter: ctx.accounts.signer.key(),
-            main_wallet: session.main_address,
-            timestamp: Clock::get()?.unix_timestamp as u64,
-        });
+        let profile = &mut ctx.accounts.profile;
+        if profile.main_address == Pubkey::default() {
+            profile.main_address = main_wallet;
+            profile.bump = ctx.bumps.profile;
+        }
+        if !profile.sessions.contains(&authority) {
+            require!(
+                profile.sessions.len() < MAX_SESSIONS_PER_WALLET,
+                PixelError::TooManySessions
+            );
+            profile.sessions.push(authority);
+        }
 
-        Ok(())
+        let new_len = 8 + SessionAccount::INIT_SPACE;
+        let shortfall = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.main_wallet.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(new_len)?;
+
+        // No authorization nonce was consumed, and the baseline had no expiry or revocation
+        let session = SessionAccount {
+            main_address: main_wallet,
+            authority,
+            auth_nonce: 0,
+            expires_at: 0,
+            revoked: false,
+            bump: baseline.bump,
+            version: SessionAccount::VERSION,
+        };
+        session.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Session {} migrated to version {}", authority, session.version);
+        Ok(())
+    }
+
+    /// Close a session left in the baseline layout instead of migrating it, refunding its rent to the
+    /// session key that paid it; signed by that key. A delegated one must first come back through
+    /// undelegate_baseline_session
+    pub fn close_baseline_session(ctx: Context<CloseBaselineSession>) -> Result<()> {
+        let session = ctx.accounts.session.to_account_info();
+        require_baseline_session(&session)?;
+
+        let refunded = session.lamports();
+        let recipient = ctx.accounts.authority.to_account_info();
+        **recipient.lamports.borrow_mut() += refunded;
+        **session.lamports.borrow_mut() = 0;
+        session.assign(&system_program::ID);
+        session.resize(0)?;
+
+        msg!("Baseline session {} closed, {} lamports refunded", recipient.key(), refunded);
+        Ok(())
+    }
+
+    /// Close an undelegated, unlisted shard with its deed and earnings record, refunding all rent to the owner
+    /// A painted shard can only be closed once close_quiet_secs have passed since its last foreign paint;
+    /// unclaimed earnings are forfeited, so call claim_earnings first
+    pub fn close_shard(
+        ctx: Context<CloseShard>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let shard = ctx.accounts.shard.load()?;
+        let quiet_secs = ctx.accounts.config.params.close_quiet_secs;
+        require!(
+            quiet_secs == 0
+                || now.saturating_sub(shard.last_foreign_paint) >= quiet_secs
+                || shard.pixels.iter().all(|&color| color == 0),
+            PixelError::ShardNotQuiet
+        );
+
+        let owner = ctx.accounts.owner.to_account_info();
+        let earnings = ctx.accounts.earnings.to_account_info();
+        let mut refunded =
+            ctx.accounts.shard.to_account_info().lamports() + ctx.accounts.deed.to_account_info().lamports();
+        if !earnings.data_is_empty() {
+            // Left behind, a stale claimed_pixels would block earnings on a re-created shard
+            refunded += earnings.lamports();
+            **owner.lamports.borrow_mut() += earnings.lamports();
+            **earnings.lamports.borrow_mut() = 0;
+            earnings.assign(&system_program::ID);
+            earnings.resize(0)?;
+        }
+
+        msg!("Shard ({}, {}) closed, {} lamports refunded", shard_x, shard_y, refunded);
+        emit!(ShardClosed {
+            shard_x,
+            shard_y,
+            owner: ctx.accounts.owner.key(),
+            refunded,
+            timestamp: now as u64,
+        });
+        Ok(())
+    }
+
+    // ========================================
+    // Shard Ownership & Marketplace
+    // ========================================
+    // Ownership is tracked on ShardDeed, which is never delegated, so these
+    // instructions run on the base layer whether or not the shard is on the ER.
+
+    /// Create the missing deed for a shard claimed before deeds existed, owned by the shard's creator
+    /// Permissionless; `payer` covers the rent. Works on a shard in any layout, delegated or not
+    pub fn initialize_deed(
+        ctx: Context<InitializeDeed>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        let creator = committed_shard_view(&ctx.accounts.shard)?.creator();
+
+        // The idle clock starts now, so a backfilled shard can't be reclaimed straight away
+        let deed = &mut ctx.accounts.deed;
+        deed.shard_x = shard_x;
+        deed.shard_y = shard_y;
+        deed.owner = creator;
+        deed.pass_price_lamports = 0;
+        deed.pass_price_tokens = 0;
+        deed.last_activity = Clock::get()?.unix_timestamp;
+        deed.frozen = false;
+        deed.bump = ctx.bumps.deed;
+
+        msg!("Deed for shard ({}, {}) issued to {}", shard_x, shard_y, creator);
+        Ok(())
+    }
+
+    /// Give a shard to another main wallet, signed by the current owner
+    /// Earnings accrued so far are paid to the current owner first
+    pub fn transfer_shard(
+        ctx: Context<TransferShard>,
+        shard_x: u16,
+        shard_y: u16,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        require!(new_owner != Pubkey::default(), PixelError::InvalidAuth);
+
+        let foreign_pixels = committed_shard(&ctx.accounts.shard)?.foreign_pixels;
+        let tokens = settle_earnings(&mut ctx.accounts.earnings, shard_x, shard_y, ctx.bumps.earnings, foreign_pixels);
+        pay_earnings(
+            (shard_x, shard_y),
+            ctx.accounts.deed.owner,
+            tokens,
+            &ctx.accounts.config,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.owner_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let deed = &mut ctx.accounts.deed;
+        let from = deed.owner;
+        deed.owner = new_owner;
+        deed.last_activity = now;
+
+        msg!("Shard ({}, {}) transferred from {} to {}", shard_x, shard_y, from, new_owner);
+        emit!(ShardTransferred {
+            shard_x,
+            shard_y,
+            from,
+            to: new_owner,
+            price: 0,
+            timestamp: now as u64,
+        });
+        Ok(())
+    }
+
+    /// List a shard for sale at a fixed SOL price
+    /// While listed, transfer_shard is blocked so the listing can't be sold out from under a buyer
+    pub fn list_shard(
+        ctx: Context<ListShard>,
+        shard_x: u16,
+        shard_y: u16,
+        price: u64,
+    ) -> Result<()> {
+        require!(price > 0, PixelError::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        listing.shard_x = shard_x;
+        listing.shard_y = shard_y;
+        listing.seller = ctx.accounts.owner.key();
+        listing.price = price;
+        listing.bump = ctx.bumps.listing;
+
+        ctx.accounts.deed.last_activity = Clock::get()?.unix_timestamp;
+
+        msg!("Shard ({}, {}) listed for {} lamports", shard_x, shard_y, price);
+        Ok(())
+    }
+
+    /// Withdraw a listing; its rent goes back to the seller
+    pub fn cancel_listing(
+        _ctx: Context<CancelListing>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        msg!("Listing for shard ({}, {}) cancelled", shard_x, shard_y);
+        Ok(())
+    }
+
+    /// Buy a listed shard
+    /// The price is escrowed into the listing PDA, which is then closed to the seller,
+    /// so ownership and payment move together or not at all. Earnings accrued so far go to the seller
+    pub fn buy_shard(
+        ctx: Context<BuyShard>,
+        shard_x: u16,
+        shard_y: u16,
+        max_price: u64,
+    ) -> Result<()> {
+        let price = ctx.accounts.listing.price;
+        require!(price <= max_price, PixelError::ListingPriceChanged);
+
+        let foreign_pixels = committed_shard(&ctx.accounts.shard)?.foreign_pixels;
+        let tokens = settle_earnings(&mut ctx.accounts.earnings, shard_x, shard_y, ctx.bumps.earnings, foreign_pixels);
+        pay_earnings(
+            (shard_x, shard_y),
+            ctx.accounts.seller.key(),
+            tokens,
+            &ctx.accounts.config,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.seller_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.listing.to_account_info(),
+                },
+            ),
+            price,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let deed = &mut ctx.accounts.deed;
+        let from = deed.owner;
+        deed.owner = ctx.accounts.buyer.key();
+        deed.last_activity = now;
+
+        msg!("Shard ({}, {}) sold to {} for {} lamports", shard_x, shard_y, deed.owner, price);
+        emit!(ShardTransferred {
+            shard_x,
+            shard_y,
+            from,
+            to: deed.owner,
+            price,
+            timestamp: now as u64,
+        });
+        Ok(())
+    }
+
+    /// Mint the tokens a shard's owner has earned from other painters
+    /// Accrual happens on the ER (PixelShard::foreign_pixels); this runs on the base layer and
+    /// reads the last committed shard state, so it works whether or not the shard is delegated
+    pub fn claim_earnings(
+        ctx: Context<ClaimEarnings>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        let foreign_pixels = committed_shard(&ctx.accounts.shard)?.foreign_pixels;
+
+        ctx.accounts.deed.last_activity = Clock::get()?.unix_timestamp;
+
+        let earnings = &mut ctx.accounts.earnings;
+        earnings.shard_x = shard_x;
+        earnings.shard_y = shard_y;
+        earnings.bump = ctx.bumps.earnings;
+
+        // Leftover pixels below a whole token carry over to the next claim
+        let tokens = foreign_pixels.saturating_sub(earnings.claimed_pixels) / PIXELS_PER_TOKEN;
+        require!(tokens > 0, PixelError::NothingToClaim);
+        earnings.claimed_pixels += tokens * PIXELS_PER_TOKEN;
+
+        pay_earnings(
+            (shard_x, shard_y),
+            ctx.accounts.owner.key(),
+            tokens,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.mint),
+            Some(&ctx.accounts.owner_token_account),
+            Some(&ctx.accounts.token_program),
+        )
+    }
+
+    /// Take over a shard whose owner and painters have been inactive for the configured window
+    /// The new owner is the main wallet behind the signing session; the fee goes to the treasury
+    /// and earnings accrued so far to the previous owner
+    pub fn reclaim_shard(
+        ctx: Context<ReclaimShard>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config.params;
+        require!(config.reclaim_after_secs > 0, PixelError::ReclaimDisabled);
+
+        let session = read_session(&ctx.accounts.session)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Placements update the shard (possibly on the ER, read here as last committed);
+        // owner actions update the deed
+        let (shard_activity, foreign_pixels) = {
+            let shard = committed_shard(&ctx.accounts.shard)?;
+            (shard.last_activity, shard.foreign_pixels)
+        };
+        let deed = &mut ctx.accounts.deed;
+        let last_activity = shard_activity.max(deed.last_activity);
+        require!(
+            now.saturating_sub(last_activity) >= config.reclaim_after_secs,
+            PixelError::ShardStillActive
+        );
+        require!(deed.owner != session.main_address, PixelError::InvalidAuth);
+
+        let fee = config.reclaim_fee_lamports;
+        if fee > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
+        let tokens = settle_earnings(&mut ctx.accounts.earnings, shard_x, shard_y, ctx.bumps.earnings, foreign_pixels);
+        pay_earnings(
+            (shard_x, shard_y),
+            deed.owner,
+            tokens,
+            &ctx.accounts.config,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.previous_owner_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+
+        let previous_owner = deed.owner;
+        deed.owner = session.main_address;
+        deed.pass_price_lamports = 0;
+        deed.pass_price_tokens = 0;
+        deed.last_activity = now;
+
+        msg!("Shard ({}, {}) reclaimed by {}", shard_x, shard_y, deed.owner);
+        emit!(ShardReclaimed {
+            shard_x,
+            shard_y,
+            previous_owner,
+            new_owner: deed.owner,
+            fee,
+            timestamp: now as u64,
+        });
+        Ok(())
+    }
+
+    // ========================================
+    // Cooldown Passes
+    // ========================================
+
+    /// Set what other painters pay this shard's owner for a cooldown pass (0 = not for sale)
+    pub fn set_pass_price(
+        ctx: Context<SetPassPrice>,
+        _shard_x: u16,
+        _shard_y: u16,
+        price_lamports: u64,
+        price_tokens: u64,
+    ) -> Result<()> {
+        let deed = &mut ctx.accounts.deed;
+        deed.pass_price_lamports = price_lamports;
+        deed.pass_price_tokens = price_tokens;
+        deed.last_activity = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Buy (or extend) a cooldown pass for the signing main wallet
+    /// Shard passes pay the shard owner in SOL or project tokens at the deed's price;
+    /// global passes pay the treasury in SOL at the config price
+    pub fn buy_cooldown_pass(
+        ctx: Context<BuyCooldownPass>,
+        scope: PassScope,
+        payment: PassPayment,
+    ) -> Result<()> {
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        match scope {
+            PassScope::Global => {
+                require!(payment == PassPayment::Sol, PixelError::PassNotForSale);
+                let price = ctx.accounts.config.params.global_pass_price_lamports;
+                require!(price > 0, PixelError::PassNotForSale);
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        system_program,
+                        anchor_lang::system_program::Transfer {
+                            from: buyer,
+                            to: ctx.accounts.treasury.to_account_info(),
+                        },
+                    ),
+                    price,
+                )?;
+            }
+            PassScope::Shard { shard_x, shard_y } => {
+                let deed = ctx.accounts.deed.as_ref().ok_or(PixelError::MissingPassAccount)?;
+                require!(
+                    deed.shard_x == shard_x && deed.shard_y == shard_y,
+                    PixelError::ShardMismatch
+                );
+                require!(deed.owner != buyer.key(), PixelError::InvalidAuth);
+
+                match payment {
+                    PassPayment::Sol => {
+                        require!(deed.pass_price_lamports > 0, PixelError::PassNotForSale);
+                        let owner = ctx.accounts.shard_owner.as_ref().ok_or(PixelError::MissingPassAccount)?;
+                        require!(owner.key() == deed.owner, PixelError::NotShardOwner);
+                        anchor_lang::system_program::transfer(
+                            CpiContext::new(
+                                system_program,
+                                anchor_lang::system_program::Transfer {
+                                    from: buyer,
+                                    to: owner.to_account_info(),
+                                },
+                            ),
+                            deed.pass_price_lamports,
+                        )?;
+                    }
+                    PassPayment::Token => {
+                        require!(deed.pass_price_tokens > 0, PixelError::PassNotForSale);
+                        let (Some(from), Some(to), Some(token_program)) = (
+                            ctx.accounts.buyer_token_account.as_ref(),
+                            ctx.accounts.owner_token_account.as_ref(),
+                            ctx.accounts.token_program.as_ref(),
+                        ) else {
+                            return err!(PixelError::MissingPassAccount);
+                        };
+                        require!(to.owner == deed.owner, PixelError::NotShardOwner);
+                        let (mint, _) = Pubkey::find_program_address(&[MINT_SEED], &crate::ID);
+                        require!(from.mint == mint && to.mint == mint, PixelError::InvalidPassMint);
+                        token::transfer(
+                            CpiContext::new(
+                                token_program.to_account_info(),
+                                token::Transfer {
+                                    from: from.to_account_info(),
+                                    to: to.to_account_info(),
+                                    authority: buyer,
+                                },
+                            ),
+                            deed.pass_price_tokens,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        // Buying again extends an active pass rather than overwriting it
+        let now = Clock::get()?.unix_timestamp;
+        let pass = &mut ctx.accounts.pass;
+        pass.painter = ctx.accounts.buyer.key();
+        pass.scope = scope;
+        pass.valid_until = pass.valid_until.max(now) + COOLDOWN_PASS_DURATION;
+        pass.bump = ctx.bumps.pass;
+
+        msg!("Cooldown pass for {} valid until {}", pass.painter, pass.valid_until);
+        emit!(CooldownPassPurchased {
+            painter: pass.painter,
+            scope,
+            valid_until: pass.valid_until,
+            timestamp: now as u64,
+        });
+        Ok(())
+    }
+
+    // ========================================
+    // Pixel Placement
+    // ========================================
+
+    /// Place a pixel using global coordinates
+    /// px, py: 0 to 524,287 (global pixel coordinates)
+    /// color: 1-15 (0 is reserved for unset/transparent, 4-bit packing)
+    pub fn place_pixel(
+        ctx: Context<PlacePixel>,
+        _shard_x: u16,  // Used in seeds validation
+        _shard_y: u16,  // Used in seeds validation
+        px: u32,
+        py: u32,
+        color: u8
+    ) -> Result<()> {
+        require!(is_on_canvas(px, py), PixelError::InvalidPixelCoord);
+        let config = &ctx.accounts.config.params;
+        require!(is_valid_color(color, config.available_colors), PixelError::InvalidColor);
+        
+        // Calculate expected shard coordinates
+        let (expected_shard_x, expected_shard_y) = shard_of(px, py);
+        
+        // Verify the correct shard was passed
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
+        require!(
+            shard.shard_x() == expected_shard_x && shard.shard_y() == expected_shard_y,
+            PixelError::ShardMismatch
+        );
+
+        // Calculate local pixel position within the shard
+        let local_pixel_id = pixel_index(px, py);
+        
+        // 8-bit storage: 1 byte per pixel, direct indexing
+        shard.pixels_mut()[local_pixel_id] = color;
+        
+        msg!(
+            "Pixel ({}, {}) -> Shard ({}, {}) index {} = color {}",
+            px, py,
+            expected_shard_x, expected_shard_y,
+            local_pixel_id,
+            color
+        );
+        drop(shard);
+        ctx.accounts.settle_paint(1, true)?;
+
+        emit!(PixelChanged {
+            px,
+            py,
+            color,
+            painter: ctx.accounts.signer.key(),
+            main_wallet: ctx.accounts.session.main_address,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        Ok(())
     }
 
     /// Erase a pixel (set to 0/transparent)
+    /// Non-owners are rate-limited exactly like place_pixel, or rejected outright
+    /// when the config's erase_policy is OwnerOnly
     pub fn erase_pixel(
         ctx: Context<PlacePixel>,
         _shard_x: u16,
@@ -292,45 +1380,50 @@ pub mod magicplace {
         px: u32,
         py: u32,
     ) -> Result<()> {
-        require!(px < CANVAS_RES && py < CANVAS_RES, PixelError::InvalidPixelCoord);
+        require!(is_on_canvas(px, py), PixelError::InvalidPixelCoord);
         
-        let expected_shard_x = (px / SHARD_DIMENSION) as u16;
-        let expected_shard_y = (py / SHARD_DIMENSION) as u16;
+        let (expected_shard_x, expected_shard_y) = shard_of(px, py);
         
-        let shard = &mut ctx.accounts.shard;
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
         require!(
-            shard.shard_x == expected_shard_x && shard.shard_y == expected_shard_y,
+            shard.shard_x() == expected_shard_x && shard.shard_y() == expected_shard_y,
             PixelError::ShardMismatch
         );
+
+        let main_wallet = ctx.accounts.session.main_address;
+        if ctx.accounts.deed.owner != main_wallet {
+            require!(
+                ctx.accounts.config.params.erase_policy == ErasePolicy::RateLimited,
+                PixelError::EraseOwnerOnly
+            );
+        }
         
-        let local_x = px % SHARD_DIMENSION;
-        let local_y = py % SHARD_DIMENSION;
-        let local_pixel_id = (local_y * SHARD_DIMENSION + local_x) as usize;
+        let local_pixel_id = pixel_index(px, py);
         
         // 8-bit storage: direct indexing, set to 0 (transparent)
-        shard.pixels[local_pixel_id] = 0;
+        shard.pixels_mut()[local_pixel_id] = 0;
+        drop(shard);
+        // Erasing earns the owner nothing
+        ctx.accounts.settle_paint(1, false)?;
         
         msg!("Pixel ({}, {}) erased", px, py);
 
-        // Context is PlacePixel, which includes session
-        let session = &mut ctx.accounts.session;
-        emit!(PixelChanged {
+        emit!(PixelErased {
             px,
             py,
-            color: 0, // 0 = erased/transparent
             painter: ctx.accounts.signer.key(),
-            main_wallet: session.main_address,
+            main_wallet,
             timestamp: Clock::get()?.unix_timestamp as u64,
         });
 
         Ok(())
     }
 
-    /// Place multiple pixels in bulk (max 50 pixels per call)
+    /// Place multiple pixels in bulk, at most GameConfig.max_bulk_pixels per call
     /// All pixels must be within the same shard
     /// Each pixel is specified as (local_x, local_y, color) where:
-    /// - local_x: 0-89 (position within shard)
-    /// - local_y: 0-89 (position within shard)
+    /// - local_x: 0-89 (position within shard; 0-37 in the last shard column)
+    /// - local_y: 0-89 (position within shard; 0-37 in the last shard row)
     /// - color: 1-255 (0 is reserved for transparent)
     pub fn place_pixels_bulk(
         ctx: Context<PlacePixel>,
@@ -339,72 +1432,37 @@ pub mod magicplace {
         pixels: Vec<BulkPixel>,
     ) -> Result<()> {
         // Validate bulk size
-        require!(pixels.len() > 0, PixelError::EmptyBulkPixels);
-        require!(pixels.len() <= COOLDOWN_LIMIT as usize, PixelError::BulkTooLarge);
+        require!(!pixels.is_empty(), PixelError::EmptyBulkPixels);
+        let config = &ctx.accounts.config.params;
+        require!(pixels.len() <= config.max_bulk_pixels as usize, PixelError::BulkTooLarge);
         
-        let shard = &mut ctx.accounts.shard;
-        let session = &mut ctx.accounts.session;
-        let is_owner = shard.creator == session.main_address;
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
         
         // Verify shard coordinates match
         require!(
-            shard.shard_x == shard_x && shard.shard_y == shard_y,
+            shard.shard_x() == shard_x && shard.shard_y() == shard_y,
             PixelError::ShardMismatch
         );
         
-        // Handle cooldown for non-owners
-        if !is_owner {
-            let clock = Clock::get()?;
-            let now = clock.unix_timestamp as u64;
-            let pixels_to_place = pixels.len() as u8;
-            
-            // Check if cooldown has reset
-            if session.cooldown_counter >= COOLDOWN_LIMIT {
-                if now.saturating_sub(session.last_place_timestamp) >= COOLDOWN_PERIOD {
-                    session.cooldown_counter = 0;
-                } else {
-                    return err!(PixelError::Cooldown);
-                }
-            }
-            
-            // Check if we would exceed the limit
-            let new_counter = session.cooldown_counter.saturating_add(pixels_to_place);
-            require!(new_counter <= COOLDOWN_LIMIT, PixelError::BulkExceedsCooldown);
-            
-            // Update counter
-            session.cooldown_counter = new_counter;
-            
-            // If we hit the limit, record timestamp
-            if session.cooldown_counter >= COOLDOWN_LIMIT {
-                session.last_place_timestamp = now;
-            }
-        }
-        
-        // Calculate base global coordinates for this shard
-        let base_px = (shard_x as u32) * SHARD_DIMENSION;
-        let base_py = (shard_y as u32) * SHARD_DIMENSION;
         let timestamp = Clock::get()?.unix_timestamp as u64;
         let painter = ctx.accounts.signer.key();
-        let main_wallet = session.main_address;
+        let main_wallet = ctx.accounts.session.main_address;
         
         // Place each pixel
+        let extent = ShardExtent::of(shard_x, shard_y);
         for pixel in pixels.iter() {
             // Validate local coordinates
-            require!(
-                (pixel.local_x as u32) < SHARD_DIMENSION && (pixel.local_y as u32) < SHARD_DIMENSION,
-                PixelError::InvalidPixelCoord
-            );
-            require!(pixel.color > 0 && pixel.color <= AVAILABLE_COLORS, PixelError::InvalidColor);
+            require!(extent.contains(pixel.local_x, pixel.local_y), PixelError::InvalidPixelCoord);
+            require!(is_valid_color(pixel.color, config.available_colors), PixelError::InvalidColor);
             
             // Calculate local pixel index
-            let local_pixel_id = (pixel.local_y as u32 * SHARD_DIMENSION + pixel.local_x as u32) as usize;
+            let local_pixel_id = local_index(pixel.local_x, pixel.local_y);
             
             // Set the pixel color
-            shard.pixels[local_pixel_id] = pixel.color;
+            shard.pixels_mut()[local_pixel_id] = pixel.color;
             
             // Calculate global coordinates for event
-            let global_px = base_px + pixel.local_x as u32;
-            let global_py = base_py + pixel.local_y as u32;
+            let (global_px, global_py) = global_of(shard_x, shard_y, pixel.local_x, pixel.local_y);
             
             // Emit event for each pixel
             emit!(PixelChanged {
@@ -417,6 +1475,10 @@ pub mod magicplace {
             });
         }
         
+        drop(shard);
+        // Every pixel counts, even one repainted within the batch
+        ctx.accounts.settle_paint(pixels.len() as u32, true)?;
+        
         msg!(
             "Bulk placed {} pixels on shard ({}, {})",
             pixels.len(),
@@ -427,93 +1489,1776 @@ pub mod magicplace {
         Ok(())
     }
 
-    // ========================================
-    // MagicBlock Ephemeral Rollups Functions
-    // ========================================
+    /// Compare-and-swap variant of place_pixel: only paints if the pixel still has `expected_color`
+    /// On conflict either fails with PixelConflict or skips, per `on_conflict`;
+    /// returns the conflicting pixel (index 0) when skipped
+    pub fn place_pixel_checked(
+        ctx: Context<PlacePixel>,
+        shard_x: u16,
+        shard_y: u16,
+        pixel: CheckedPixel,
+        on_conflict: ConflictPolicy,
+    ) -> Result<Vec<PixelConflict>> {
+        require!(is_on_canvas(pixel.px, pixel.py), PixelError::InvalidPixelCoord);
+        let config = &ctx.accounts.config.params;
+        require!(is_valid_color(pixel.color, config.available_colors), PixelError::InvalidColor);
 
-    /// Commit shard state from ER to base layer
-    pub fn commit_shard(
-        ctx: Context<CommitShardInput>, 
-        _shard_x: u16, 
-        _shard_y: u16
-    ) -> Result<()> {
-        commit_accounts(
-            &ctx.accounts.payer,
-            vec![&ctx.accounts.shard.to_account_info()],
-            &ctx.accounts.magic_context,
-            &ctx.accounts.magic_program,
-        )?;
-        msg!("Shard committed to base layer");
-        Ok(())
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
+        require!(
+            shard.shard_x() == shard_x && shard.shard_y() == shard_y
+                && shard_of(pixel.px, pixel.py) == (shard_x, shard_y),
+            PixelError::ShardMismatch
+        );
+
+        let local_pixel_id = pixel_index(pixel.px, pixel.py);
+        let current_color = shard.pixels()[local_pixel_id];
+        if pixel.expected_color.is_some_and(|expected| expected != current_color) {
+            require!(on_conflict == ConflictPolicy::Skip, PixelError::PixelConflict);
+            msg!("Pixel ({}, {}) skipped: color is {}", pixel.px, pixel.py, current_color);
+            return Ok(vec![PixelConflict { index: 0, current_color }]);
+        }
+
+        shard.pixels_mut()[local_pixel_id] = pixel.color;
+        drop(shard);
+        ctx.accounts.settle_paint(1, true)?;
+
+        emit!(PixelChanged {
+            px: pixel.px,
+            py: pixel.py,
+            color: pixel.color,
+            painter: ctx.accounts.signer.key(),
+            main_wallet: ctx.accounts.session.main_address,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(vec![])
     }
-}
 
-// ========================================
-// Account Structs
-// ========================================
+    /// Compare-and-swap variant of place_pixels_bulk; each pixel may carry an `expected_color`
+    /// With ConflictPolicy::Fail any conflict aborts the whole batch; with Skip the conflicting
+    /// pixels are left alone and returned (by position in `pixels`), and only placed pixels
+    /// cost cooldown
+    pub fn place_pixels_bulk_checked(
+        ctx: Context<PlacePixel>,
+        shard_x: u16,
+        shard_y: u16,
+        pixels: Vec<CheckedBulkPixel>,
+        on_conflict: ConflictPolicy,
+    ) -> Result<Vec<PixelConflict>> {
+        require!(!pixels.is_empty(), PixelError::EmptyBulkPixels);
+        let config = &ctx.accounts.config.params;
+        require!(pixels.len() <= config.max_bulk_pixels as usize, PixelError::BulkTooLarge);
 
-/// Initialize a user session account (without delegation)
-/// 
-/// IMPORTANT: The transaction must include an Ed25519 verify instruction as the FIRST
-/// instruction, verifying that main_wallet signed the authorization message.
-#[derive(Accounts)]
-#[instruction(main_wallet: Pubkey, signature: [u8; 64])]
-pub struct InitializeUser<'info> {
-    /// Session account PDA derived from the MAIN wallet (not session key)
-    /// This ensures each main wallet has exactly one session account
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + SessionAccount::INIT_SPACE,
-        seeds = [b"session", authority.key().as_ref()],
-        bump
-    )]
-    pub user: Account<'info, SessionAccount>,
-    /// The session key that is authorized to act on behalf of main_wallet
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    /// CHECK: Instructions sysvar for Ed25519 signature verification
-    #[account(address = instructions::ID)]
-    pub instructions_sysvar: AccountInfo<'info>,
-}
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
+        require!(
+            shard.shard_x() == shard_x && shard.shard_y() == shard_y,
+            PixelError::ShardMismatch
+        );
 
-/// Delegate a user session account to Ephemeral Rollups
+        let timestamp = Clock::get()?.unix_timestamp as u64;
+        let painter = ctx.accounts.signer.key();
+        let main_wallet = ctx.accounts.session.main_address;
+
+        let mut conflicts = Vec::new();
+        let mut placed = 0;
+        let extent = ShardExtent::of(shard_x, shard_y);
+        for (index, pixel) in pixels.iter().enumerate() {
+            require!(extent.contains(pixel.local_x, pixel.local_y), PixelError::InvalidPixelCoord);
+            require!(is_valid_color(pixel.color, config.available_colors), PixelError::InvalidColor);
+
+            let local_pixel_id = local_index(pixel.local_x, pixel.local_y);
+            let current_color = shard.pixels()[local_pixel_id];
+            if pixel.expected_color.is_some_and(|expected| expected != current_color) {
+                require!(on_conflict == ConflictPolicy::Skip, PixelError::PixelConflict);
+                conflicts.push(PixelConflict { index: index as u16, current_color });
+                continue;
+            }
+
+            shard.pixels_mut()[local_pixel_id] = pixel.color;
+            placed += 1;
+            let (px, py) = global_of(shard_x, shard_y, pixel.local_x, pixel.local_y);
+            emit!(PixelChanged {
+                px,
+                py,
+                color: pixel.color,
+                painter,
+                main_wallet,
+                timestamp,
+            });
+        }
+        drop(shard);
+        ctx.accounts.settle_paint(placed, true)?;
+
+        msg!(
+            "Bulk placed {} pixels on shard ({}, {}), {} conflicts skipped",
+            placed, shard_x, shard_y, conflicts.len()
+        );
+        Ok(conflicts)
+    }
+
+    /// Fill a rectangle of one shard with a single color, in local coordinates
+    /// Cooldown and foreign-paint earnings count only pixels whose color actually changed
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_rect(
+        ctx: Context<PlacePixel>,
+        shard_x: u16,
+        shard_y: u16,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        color: u8,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config.params;
+        require!(is_valid_color(color, config.available_colors), PixelError::InvalidColor);
+        let extent = ShardExtent::of(shard_x, shard_y);
+        require!(extent.contains_rect(x, y, width, height), PixelError::InvalidPixelCoord);
+
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
+        require!(
+            shard.shard_x() == shard_x && shard.shard_y() == shard_y,
+            PixelError::ShardMismatch
+        );
+
+        let mut changed = 0;
+        for row in y..y + height {
+            changed += shard.paint_span(x, row, width, color);
+        }
+        drop(shard);
+        ctx.accounts.settle_paint(changed, true)?;
+
+        msg!(
+            "Filled {}x{} at ({}, {}) on shard ({}, {}), {} pixels changed",
+            width, height, x, y, shard_x, shard_y, changed
+        );
+        emit!(RectFilled {
+            shard_x,
+            shard_y,
+            x,
+            y,
+            width,
+            height,
+            color,
+            changed,
+            painter: ctx.accounts.signer.key(),
+            main_wallet: ctx.accounts.session.main_address,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Place run-length encoded pixels on one shard: each run paints `length` pixels of
+    /// `row`, starting at column `start`. Costs 4 bytes per run instead of 3 per pixel
+    /// Cooldown and foreign-paint earnings count only pixels whose color actually changed
+    pub fn place_pixel_runs(
+        ctx: Context<PlacePixel>,
+        shard_x: u16,
+        shard_y: u16,
+        runs: Vec<PixelRun>,
+    ) -> Result<()> {
+        require!(!runs.is_empty(), PixelError::EmptyBulkPixels);
+        let config = &ctx.accounts.config.params;
+
+        let mut shard = ShardView::load_mut(ctx.accounts.shard.as_ref())?;
+        require!(
+            shard.shard_x() == shard_x && shard.shard_y() == shard_y,
+            PixelError::ShardMismatch
+        );
+
+        let mut changed = 0;
+        let extent = ShardExtent::of(shard_x, shard_y);
+        for run in runs.iter() {
+            require!(extent.contains_span(run.row, run.start, run.length), PixelError::InvalidPixelCoord);
+            require!(is_valid_color(run.color, config.available_colors), PixelError::InvalidColor);
+            changed += shard.paint_span(run.start, run.row, run.length, run.color);
+        }
+        drop(shard);
+        ctx.accounts.settle_paint(changed, true)?;
+
+        msg!(
+            "Placed {} runs on shard ({}, {}), {} pixels changed",
+            runs.len(), shard_x, shard_y, changed
+        );
+        emit!(PixelRunsPlaced {
+            shard_x,
+            shard_y,
+            runs,
+            changed,
+            painter: ctx.accounts.signer.key(),
+            main_wallet: ctx.accounts.session.main_address,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Place pixels given in global coordinates, spread over any number of shards, atomically
+    /// remaining_accounts holds a (shard, deed) pair for every shard the pixels land in;
+    /// ownership, freezes, passes and foreign-paint earnings apply per shard as in place_pixels_bulk,
+    /// and the pixels that are not cooldown-free are charged to the profile together
+    pub fn place_pixels_cross_shard<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlacePixelsCrossShard<'info>>,
+        pixels: Vec<GlobalPixel>,
+    ) -> Result<()> {
+        require!(!pixels.is_empty(), PixelError::EmptyBulkPixels);
+        let config = &ctx.accounts.config.params;
+        require!(pixels.len() <= config.max_bulk_pixels as usize, PixelError::BulkTooLarge);
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+            PixelError::InvalidShardAccount
+        );
+
+        let session = &ctx.accounts.session;
+        let main_wallet = session.main_address;
+        let painter = ctx.accounts.signer.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        // (shard, painter owns it, painter holds a pass for it)
+        let mut targets = Vec::new();
+        for pair in ctx.remaining_accounts.chunks_exact(2) {
+            // A repeated shard would already be borrowed, so check before loading it
+            require!(
+                !ctx.remaining_accounts
+                    .iter()
+                    .step_by(2)
+                    .take(targets.len())
+                    .any(|loaded| loaded.key == pair[0].key),
+                PixelError::DuplicateShard
+            );
+            let shard = load_shard(&pair[0])?;
+            let deed = load_deed(&pair[1], shard.shard_x(), shard.shard_y())?;
+            require!(!deed.frozen, PixelError::ShardFrozen);
+
+            let is_owner = deed.owner == main_wallet;
+            let has_pass = ctx.accounts.pass.as_ref().is_some_and(|pass| {
+                pass.covers(&main_wallet, shard.shard_x(), shard.shard_y(), now)
+            });
+            targets.push((shard, is_owner, has_pass));
+        }
+
+        let mut charged: u8 = 0;
+        for pixel in pixels.iter() {
+            require!(is_on_canvas(pixel.px, pixel.py), PixelError::InvalidPixelCoord);
+            require!(is_valid_color(pixel.color, config.available_colors), PixelError::InvalidColor);
+
+            let (shard_x, shard_y) = shard_of(pixel.px, pixel.py);
+            let (shard, is_owner, has_pass) = targets
+                .iter_mut()
+                .find(|(shard, _, _)| shard.shard_x() == shard_x && shard.shard_y() == shard_y)
+                .ok_or(PixelError::ShardMismatch)?;
+
+            let local_pixel_id = pixel_index(pixel.px, pixel.py);
+            shard.pixels_mut()[local_pixel_id] = pixel.color;
+            // Paint under a pass is neither charged nor earns the owner anything, as in settle_paint
+            let foreign = !*is_owner && !*has_pass;
+            if foreign {
+                charged += 1;
+            }
+            if let Some(stats) = shard.stats_mut() {
+                stats.last_activity = now;
+                if !*is_owner {
+                    stats.last_foreign_paint = now;
+                }
+                if foreign {
+                    stats.foreign_pixels = stats.foreign_pixels.saturating_add(1);
+                }
+            }
+
+            emit!(PixelChanged {
+                px: pixel.px,
+                py: pixel.py,
+                color: pixel.color,
+                painter,
+                main_wallet,
+                timestamp: now as u64,
+            });
+        }
+
+        if charged > 0 {
+            ctx.accounts.profile.charge_cooldown(charged, config, now as u64)?;
+        }
+
+        msg!("Placed {} pixels across {} shards", pixels.len(), targets.len());
+        Ok(())
+    }
+
+    // ========================================
+    // Canvas Reads (results via return data)
+    // ========================================
+    // For CPI callers and simulation-based clients. The shard must be owned by
+    // this program on the layer being read, i.e. read delegated shards on the ER.
+
+    /// Read one pixel, in global coordinates, and its shard's creator
+    pub fn get_pixel(
+        ctx: Context<GetPixel>,
+        shard_x: u16,
+        shard_y: u16,
+        px: u32,
+        py: u32,
+    ) -> Result<PixelRead> {
+        require!(is_on_canvas(px, py), PixelError::InvalidPixelCoord);
+        require!(
+            shard_of(px, py) == (shard_x, shard_y),
+            PixelError::ShardMismatch
+        );
+
+        let shard = ShardView::load(ctx.accounts.shard.as_ref())?;
+        let local_pixel_id = pixel_index(px, py);
+        Ok(PixelRead {
+            color: shard.pixels()[local_pixel_id],
+            creator: shard.creator(),
+        })
+    }
+
+    /// Read a rectangle of one shard, in local coordinates, row by row
+    /// At most MAX_REGION_PIXELS (960) pixels per call
+    pub fn get_region(
+        ctx: Context<GetPixel>,
+        shard_x: u16,
+        shard_y: u16,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+    ) -> Result<RegionRead> {
+        let extent = ShardExtent::of(shard_x, shard_y);
+        require!(extent.contains_rect(x, y, width, height), PixelError::InvalidPixelCoord);
+        require!(
+            width as usize * height as usize <= MAX_REGION_PIXELS,
+            PixelError::RegionTooLarge
+        );
+
+        let shard = ShardView::load(ctx.accounts.shard.as_ref())?;
+        let mut colors = Vec::with_capacity(width as usize * height as usize);
+        for row in y..y + height {
+            let start = local_index(x, row);
+            colors.extend_from_slice(&shard.pixels()[start..start + width as usize]);
+        }
+        Ok(RegionRead {
+            creator: shard.creator(),
+            colors,
+        })
+    }
+
+    // ========================================
+    // MagicBlock Ephemeral Rollups Functions
+    // ========================================
+
+    /// Commit shard state from ER to base layer
+    pub fn commit_shard(
+        ctx: Context<CommitShardInput>, 
+        _shard_x: u16, 
+        _shard_y: u16
+    ) -> Result<()> {
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.shard.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Shard committed to base layer");
+        Ok(())
+    }
+
+    /// Commit several shards from ER to base layer in one instruction
+    /// Shard accounts are passed in remaining_accounts, in the same order as `shards`
+    pub fn commit_shards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CommitShards<'info>>,
+        shards: Vec<ShardCoord>,
+    ) -> Result<()> {
+        require!(!shards.is_empty(), PixelError::EmptyShardList);
+        require!(
+            shards.len() == ctx.remaining_accounts.len(),
+            PixelError::InvalidShardAccount
+        );
+
+        let mut accounts = Vec::with_capacity(shards.len());
+        for (coord, shard_info) in shards.iter().zip(ctx.remaining_accounts.iter()) {
+            require!(
+                !accounts.iter().any(|committed: &&AccountInfo| committed.key == shard_info.key),
+                PixelError::DuplicateShard
+            );
+            let shard = load_shard(shard_info)?;
+            require!(
+                shard.shard_x() == coord.shard_x && shard.shard_y() == coord.shard_y,
+                PixelError::InvalidShardAccount
+            );
+            accounts.push(shard_info);
+        }
+
+        commit_accounts(
+            &ctx.accounts.payer,
+            accounts,
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+
+        msg!("{} shards committed to base layer", shards.len());
+        emit!(ShardsCommitted {
+            shards,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+        Ok(())
+    }
+
+    /// Commit a shard and return it to the base layer, signed by the shard owner
+    /// Needed before anything that rewrites the shard account itself (closing, migrations)
+    pub fn undelegate_shard(
+        ctx: Context<UndelegateShard>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.owner,
+            vec![&ctx.accounts.shard.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Shard ({}, {}) scheduled for undelegation", shard_x, shard_y);
+        Ok(())
+    }
+
+    /// Maintenance path: the admin or moderator commits and undelegates any shard
+    pub fn commit_and_undelegate_shard(
+        ctx: Context<CommitAndUndelegateShard>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.moderator,
+            vec![&ctx.accounts.shard.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Shard ({}, {}) committed and scheduled for undelegation", shard_x, shard_y);
+        Ok(())
+    }
+
+    /// Commit a session (and optionally its wallet profile) and return it to the base layer
+    /// Signed by an unrevoked session key; leave the profile delegated while other sessions still paint
+    /// Only keys still registered on the profile may take it off the ER
+    pub fn undelegate_user(
+        ctx: Context<UndelegateUser>,
+        include_profile: bool,
+    ) -> Result<()> {
+        if include_profile {
+            require!(
+                ctx.accounts.profile.sessions.contains(&ctx.accounts.authority.key()),
+                PixelError::InvalidAuth
+            );
+        }
+
+        let session = ctx.accounts.session.to_account_info();
+        let profile = ctx.accounts.profile.to_account_info();
+        let mut accounts = vec![&session];
+        if include_profile {
+            accounts.push(&profile);
+        }
+
+        commit_and_undelegate_accounts(
+            &ctx.accounts.authority,
+            accounts,
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Session {} scheduled for undelegation", ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    /// Commit a wallet profile and return it to the base layer, signed by the main wallet
+    /// initialize_user and rotate_session write the profile on the base layer, so a wallet whose
+    /// profile is delegated runs this first and delegate_profile again once the session is registered
+    pub fn undelegate_profile(ctx: Context<UndelegateProfile>) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &ctx.accounts.main_wallet,
+            vec![&ctx.accounts.profile.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Wallet profile {} scheduled for undelegation", ctx.accounts.main_wallet.key());
+        Ok(())
+    }
+
+    /// Return a delegated baseline session to the base layer so migrate_session can upgrade it
+    /// (or close_baseline_session retire it)
+    /// Signed by its session key, like undelegate_user
+    pub fn undelegate_baseline_session(ctx: Context<UndelegateBaselineSession>) -> Result<()> {
+        require_baseline_session(&ctx.accounts.session)?;
+
+        commit_and_undelegate_accounts(
+            &ctx.accounts.authority,
+            vec![&ctx.accounts.session.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Baseline session {} scheduled for undelegation", ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    /// Return a delegated baseline shard to the base layer so migrate_shard can rewrite it
+    /// Permissionless like migrate_shard: baseline shards have no deed to name an owner
+    pub fn undelegate_baseline_shard(
+        ctx: Context<UndelegateBaselineShard>,
+        shard_x: u16,
+        shard_y: u16,
+    ) -> Result<()> {
+        require_baseline_shard(&ctx.accounts.shard)?;
+
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.shard.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Baseline shard ({}, {}) scheduled for undelegation", shard_x, shard_y);
+        Ok(())
+    }
+}
+
+// ========================================
+// Session Authorization
+// ========================================
+
+/// Cluster tag bound into session authorizations so a signature cannot be replayed across clusters
+/// Chosen by the `devnet`/`mainnet` build features and published in the IDL for clients to sign
+#[constant]
+pub const CLUSTER_TAG: &str = if cfg!(feature = "mainnet") {
+    "mainnet"
+} else if cfg!(feature = "devnet") {
+    "devnet"
+} else {
+    "localnet"
+};
+
+/// Size of the Ed25519 instruction preamble (signature count + padding)
+const ED25519_PREAMBLE_LEN: usize = 2;
+
+/// Size of one Ed25519 signature offsets header
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Instruction index value meaning "data lives in the Ed25519 instruction itself"
+const ED25519_CURRENT_IX: u16 = u16::MAX;
+
+/// Build the canonical message a main wallet signs to authorize a session key
+/// Clients must reproduce this byte-for-byte (UTF-8, `\n` separated)
+pub fn session_auth_message(authority: &Pubkey, nonce: u64, expires_at: i64) -> String {
+    format!(
+        "Magicplace session authorization\nprogram: {}\nsession: {}\ncluster: {}\nnonce: {}\nexpires: {}",
+        crate::ID,
+        authority,
+        CLUSTER_TAG,
+        nonce,
+        expires_at
+    )
+}
+
+/// Pubkey, signature and message covered by an Ed25519 verify instruction
+struct Ed25519Verified<'a> {
+    pubkey: &'a [u8],
+    signature: &'a [u8],
+    message: &'a [u8],
+}
+
+/// Parse a single-signature Ed25519 verify instruction
+/// Offsets must point into the instruction's own data, otherwise the bytes we read here
+/// are not the bytes the precompile actually verified
+fn parse_ed25519_instruction(data: &[u8]) -> Result<Ed25519Verified<'_>> {
+    require!(
+        data.len() >= ED25519_PREAMBLE_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        PixelError::InvalidEd25519Instruction
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let header = ED25519_PREAMBLE_LEN;
+    let signature_offset = read_u16(header) as usize;
+    let signature_ix = read_u16(header + 2);
+    let pubkey_offset = read_u16(header + 4) as usize;
+    let pubkey_ix = read_u16(header + 6);
+    let message_offset = read_u16(header + 8) as usize;
+    let message_size = read_u16(header + 10) as usize;
+    let message_ix = read_u16(header + 12);
+
+    require!(
+        signature_ix == ED25519_CURRENT_IX
+            && pubkey_ix == ED25519_CURRENT_IX
+            && message_ix == ED25519_CURRENT_IX,
+        PixelError::InvalidEd25519Instruction
+    );
+
+    let slice = |offset: usize, len: usize| -> Result<&[u8]> {
+        data.get(offset..offset + len)
+            .ok_or_else(|| error!(PixelError::InvalidEd25519Instruction))
+    };
+
+    Ok(Ed25519Verified {
+        pubkey: slice(pubkey_offset, 32)?,
+        signature: slice(signature_offset, 64)?,
+        message: slice(message_offset, message_size)?,
+    })
+}
+
+/// Read a live session that may be owned by magicplace or, while delegated, by the delegation program
+fn read_session(info: &AccountInfo) -> Result<SessionAccount> {
+    require!(
+        info.owner == &crate::ID || info.owner == &DELEGATION_PROGRAM_ID,
+        PixelError::InvalidAuth
+    );
+    let session = SessionAccount::try_deserialize(&mut &info.data.borrow()[..])?;
+    require!(!session.revoked, PixelError::SessionRevoked);
+    require!(
+        !session.is_expired(Clock::get()?.unix_timestamp),
+        PixelError::SessionExpired
+    );
+    Ok(session)
+}
+
+/// Layout version of session account data: 0 for the baseline layout, otherwise
+/// SessionAccount::version. Anything else is not a session
+fn session_version(data: &[u8]) -> Result<u8> {
+    let disc = SessionAccount::DISCRIMINATOR;
+    require!(data.len() >= disc.len() && &data[..disc.len()] == disc, PixelError::InvalidAuth);
+    if data.len() == BASELINE_SESSION_ACCOUNT_LEN {
+        return Ok(0);
+    }
+    require!(data.len() == 8 + SessionAccount::INIT_SPACE, PixelError::InvalidAuth);
+    let version = SessionAccount::try_deserialize(&mut &data[..])?.version;
+    require!((1..=SessionAccount::VERSION).contains(&version), PixelError::InvalidAuth);
+    Ok(version)
+}
+
+/// Check that a magicplace-owned session account is still in the baseline layout
+fn require_baseline_session(info: &AccountInfo) -> Result<()> {
+    require!(info.owner == &crate::ID, PixelError::NotBaselineSession);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() == BASELINE_SESSION_ACCOUNT_LEN && &data[..8] == SessionAccount::DISCRIMINATOR,
+        PixelError::NotBaselineSession
+    );
+    Ok(())
+}
+
+/// Layout version of shard account data: 0 for the baseline Borsh layout, otherwise the
+/// version byte of the zero-copy layout. Anything else is not a shard
+fn shard_version(data: &[u8]) -> Result<u8> {
+    let disc = PixelShard::DISCRIMINATOR;
+    require!(data.len() >= disc.len() && &data[..disc.len()] == disc, PixelError::InvalidShardAccount);
+    if data.len() == BASELINE_SHARD_ACCOUNT_LEN {
+        // The pixel Vec's length prefix; initialize_shard always filled it
+        require!(
+            data[12..16] == (PIXELS_PER_SHARD as u32).to_le_bytes(),
+            PixelError::InvalidShardAccount
+        );
+        return Ok(0);
+    }
+    require!(data.len() == 8 + PixelShard::INIT_SPACE, PixelError::InvalidShardAccount);
+    let version = bytemuck::from_bytes::<PixelShard>(&data[disc.len()..]).version;
+    require!((1..=PixelShard::VERSION).contains(&version), PixelError::InvalidShardAccount);
+    Ok(version)
+}
+
+/// Check that a magicplace-owned shard account is still in the baseline layout
+fn require_baseline_shard(info: &AccountInfo) -> Result<()> {
+    require!(info.owner == &crate::ID, PixelError::NotBaselineShard);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() == BASELINE_SHARD_ACCOUNT_LEN && &data[..8] == PixelShard::DISCRIMINATOR,
+        PixelError::NotBaselineShard
+    );
+    Ok(())
+}
+
+// ========================================
+// Shard Earnings
+// ========================================
+
+/// Close out a shard's earnings before its owner changes: returns the whole tokens the outgoing
+/// owner is still owed and restarts the count for the next owner (a partial token is dropped)
+fn settle_earnings(earnings: &mut ShardEarnings, shard_x: u16, shard_y: u16, bump: u8, foreign_pixels: u64) -> u64 {
+    earnings.shard_x = shard_x;
+    earnings.shard_y = shard_y;
+    earnings.bump = bump;
+    let tokens = foreign_pixels.saturating_sub(earnings.claimed_pixels) / PIXELS_PER_TOKEN;
+    earnings.claimed_pixels = earnings.claimed_pixels.max(foreign_pixels);
+    tokens
+}
+
+/// Mint `tokens` whole project tokens earned on a shard to `owner`'s token account
+/// The token accounts are only required when something is owed
+fn pay_earnings<'info>(
+    (shard_x, shard_y): (u16, u16),
+    owner: Pubkey,
+    tokens: u64,
+    config: &Account<'info, GameConfig>,
+    mint: Option<&Account<'info, Mint>>,
+    to: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+) -> Result<()> {
+    if tokens == 0 {
+        return Ok(());
+    }
+    let (Some(mint), Some(to), Some(token_program)) = (mint, to, token_program) else {
+        return err!(PixelError::MissingEarningsAccount);
+    };
+    let amount = tokens
+        .checked_mul(10u64.pow(TOKEN_DECIMALS as u32))
+        .ok_or(PixelError::EarningsOverflow)?;
+    let config_seeds: &[&[u8]] = &[CONFIG_SEED, &[config.bump]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo { mint: mint.to_account_info(), to: to.to_account_info(), authority: config.to_account_info() },
+            &[config_seeds],
+        ),
+        amount,
+    )?;
+
+    msg!("Shard ({}, {}) owner {} paid {} tokens", shard_x, shard_y, owner, tokens);
+    emit!(EarningsClaimed {
+        shard_x,
+        shard_y,
+        owner,
+        tokens,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+// ========================================
+// Shard Accounts from remaining_accounts
+// ========================================
+
+/// Check that shard data holds the current layout, so it can be viewed as a PixelShard
+/// A baseline shard shares the discriminator but is shorter, and AccountLoader would
+/// panic slicing it; it gets ShardNeedsMigration instead
+fn require_current_layout(data: &[u8]) -> Result<()> {
+    require!(shard_version(data)? == PixelShard::VERSION, PixelError::ShardNeedsMigration);
+    Ok(())
+}
+
+/// PDA bump of a shard in the current layout, for `bump = ...` constraints that run before the handler
+fn current_shard_bump(shard: &AccountLoader<PixelShard>) -> Result<u8> {
+    require_current_layout(&shard.as_ref().try_borrow_data()?)?;
+    Ok(shard.load()?.bump)
+}
+
+/// PDA bump of a shard in any layout, for accounts that go through ShardView
+fn shard_bump(shard: &AccountLoader<PixelShard>) -> Result<u8> {
+    Ok(ShardView::load(shard.as_ref())?.bump())
+}
+
+/// Load a writable shard passed outside the Accounts struct and check it sits at its PDA
+/// Writes go straight to the account data, so nothing needs writing back
+fn load_shard<'info>(info: &'info AccountInfo<'info>) -> Result<ShardView<RefMut<'info, [u8]>>> {
+    require!(info.is_writable, PixelError::InvalidShardAccount);
+    // Owner and discriminator checks, with Anchor's errors
+    AccountLoader::<PixelShard>::try_from(info)?;
+    let shard = ShardView::load_mut(info)?;
+    let expected = Pubkey::create_program_address(
+        &[
+            SHARD_SEED,
+            &shard.shard_x().to_le_bytes(),
+            &shard.shard_y().to_le_bytes(),
+            &[shard.bump()],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| PixelError::InvalidShardAccount)?;
+    require_keys_eq!(expected, info.key(), PixelError::InvalidShardAccount);
+    Ok(shard)
+}
+
+/// Read the last committed state of a shard that may be delegated, in any layout
+fn committed_shard_view<'a>(info: &'a AccountInfo) -> Result<ShardView<Ref<'a, [u8]>>> {
+    require!(
+        info.owner == &crate::ID || info.owner == &DELEGATION_PROGRAM_ID,
+        PixelError::InvalidShardAccount
+    );
+    ShardView::load(info)
+}
+
+/// Read the last committed state of a shard that may be delegated, without copying it
+/// Earnings only exist in the current layout, so older shards get ShardNeedsMigration
+fn committed_shard<'a>(info: &'a AccountInfo) -> Result<Ref<'a, PixelShard>> {
+    let shard = committed_shard_view(info)?;
+    require!(shard.version() == PixelShard::VERSION, PixelError::ShardNeedsMigration);
+    Ok(Ref::map(shard.data, |data| bytemuck::from_bytes(&data[PixelShard::DISCRIMINATOR.len()..])))
+}
+
+/// Load the ShardDeed for the given shard, passed outside the Accounts struct
+fn load_deed<'info>(
+    info: &'info AccountInfo<'info>,
+    shard_x: u16,
+    shard_y: u16,
+) -> Result<Account<'info, ShardDeed>> {
+    let deed = Account::<ShardDeed>::try_from(info)?;
+    require!(
+        deed.shard_x == shard_x && deed.shard_y == shard_y,
+        PixelError::InvalidShardAccount
+    );
+    let expected = Pubkey::create_program_address(
+        &[DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes(), &[deed.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| PixelError::InvalidShardAccount)?;
+    require_keys_eq!(expected, info.key(), PixelError::InvalidShardAccount);
+    Ok(deed)
+}
+
+/// Create a program-owned account at the PDA `seeds` (without bump), the way Anchor's `init` does
+/// Returns the bump; fails if the address is not the PDA or already holds data
+fn create_pda<'info>(
+    info: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u8> {
+    let (expected, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(expected, info.key(), PixelError::InvalidShardAccount);
+    require!(
+        info.is_writable && info.data_is_empty() && info.owner == &system_program::ID,
+        PixelError::ShardAlreadyClaimed
+    );
+
+    let bump_seed = [bump];
+    let signer_seeds = [seeds, &[&bump_seed[..]]].concat();
+    let signer = [&signer_seeds[..]];
+    let rent = Rent::get()?.minimum_balance(space);
+    let program = system_program.clone();
+    if info.lamports() == 0 {
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                program,
+                anchor_lang::system_program::CreateAccount { from: payer.clone(), to: info.clone() },
+                &signer,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Someone pre-funded the address; top it up and take it over instead
+        let shortfall = rent.saturating_sub(info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    program.clone(),
+                    anchor_lang::system_program::Transfer { from: payer.clone(), to: info.clone() },
+                ),
+                shortfall,
+            )?;
+        }
+        anchor_lang::system_program::allocate(
+            CpiContext::new_with_signer(
+                program.clone(),
+                anchor_lang::system_program::Allocate { account_to_allocate: info.clone() },
+                &signer,
+            ),
+            space as u64,
+        )?;
+        anchor_lang::system_program::assign(
+            CpiContext::new_with_signer(
+                program,
+                anchor_lang::system_program::Assign { account_to_assign: info.clone() },
+                &signer,
+            ),
+            &crate::ID,
+        )?;
+    }
+    Ok(bump)
+}
+
+// ========================================
+// Account Structs
+// ========================================
+
+/// Initialize a user session account (without delegation)
+/// 
+/// IMPORTANT: The transaction must include an Ed25519 verify instruction as the FIRST
+/// instruction, verifying that main_wallet signed the authorization message.
+#[derive(Accounts)]
+#[instruction(main_wallet: Pubkey, signature: [u8; 64], nonce: u64, expires_at: i64)]
+pub struct InitializeUser<'info> {
+    /// Session account PDA derived from the session key
+    /// A main wallet may register up to MAX_SESSIONS_PER_WALLET of these via its profile
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SessionAccount::INIT_SPACE,
+        seeds = [b"session", authority.key().as_ref()],
+        bump
+    )]
+    pub user: Account<'info, SessionAccount>,
+    /// Per-main-wallet profile holding cooldown state and the list of session keys
+    /// Must be on the base layer (not delegated) to register a new session; see undelegate_profile
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + WalletProfile::INIT_SPACE,
+        seeds = [PROFILE_SEED, main_wallet.as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, WalletProfile>,
+    /// The session key that is authorized to act on behalf of main_wallet
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Instructions sysvar for Ed25519 signature verification
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+/// Delegate a user session account to Ephemeral Rollups
 /// This should be called after initialize_user in a separate transaction
 #[delegate]
 #[derive(Accounts)]
-pub struct DelegateUser<'info> {
-    /// The session account to delegate
+pub struct DelegateUser<'info> {
+    /// The session account to delegate
+    #[account(
+        mut,
+        seeds = [b"session", authority.key().as_ref()],
+        bump = user.bump,
+    )]
+    pub user: Account<'info, SessionAccount>,
+    /// The session key authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The PDA to delegate - same as user, used for delegation CPI
+    #[account(mut, del, seeds = [b"session", authority.key().as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+/// Delegate a wallet profile to Ephemeral Rollups
+#[delegate]
+#[derive(Accounts)]
+#[instruction(main_wallet: Pubkey)]
+pub struct DelegateProfile<'info> {
+    /// A session key registered on the profile
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The profile PDA to delegate - validated by seeds, deserialized in the handler
+    #[account(mut, del, seeds = [PROFILE_SEED, main_wallet.as_ref()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GameConfig::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [TREASURY_SEED],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ PixelError::NotUpgradeAuthority,
+    )]
+    pub program: Program<'info, crate::program::Magicplace>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ PixelError::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// Must be the program's upgrade authority, so nobody can front-run the deployment
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ PixelError::NotAdmin,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct FreezeShard<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = moderator.key() == config.moderator || moderator.key() == config.admin
+            @ PixelError::NotModerator,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ PixelError::NotAdmin,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    /// CHECK: Any account chosen by the admin to receive the funds
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMint<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ PixelError::NotAdmin,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [MINT_SEED],
+        bump,
+        mint::decimals = TOKEN_DECIMALS,
+        mint::authority = config,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a session key, signed by its main wallet
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", authority.as_ref()],
+        bump = session.bump,
+        constraint = session.main_address == main_wallet.key() @ PixelError::InvalidAuth,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, main_wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    pub main_wallet: Signer<'info>,
+}
+
+/// Revoke a delegated session key on the ER and undelegate it
+#[commit]
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct RevokeAndUndelegateSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", authority.as_ref()],
+        bump = session.bump,
+        constraint = session.main_address == main_wallet.key() @ PixelError::InvalidAuth,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, main_wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(mut)]
+    pub main_wallet: Signer<'info>,
+}
+
+/// Change a session key's expiry, signed by its main wallet
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct SetSessionExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", authority.as_ref()],
+        bump = session.bump,
+        constraint = session.main_address == main_wallet.key() @ PixelError::InvalidAuth,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    pub main_wallet: Signer<'info>,
+}
+
+/// Close a revoked session on the base layer
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct CloseSession<'info> {
+    #[account(
+        mut,
+        close = main_wallet,
+        seeds = [b"session", authority.as_ref()],
+        bump = session.bump,
+        constraint = session.main_address == main_wallet.key() @ PixelError::InvalidAuth,
+        constraint = session.revoked @ PixelError::SessionNotRevoked,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(mut)]
+    pub main_wallet: Signer<'info>,
+}
+
+/// Swap an undelegated session key for a new one
+#[derive(Accounts)]
+#[instruction(old_authority: Pubkey, new_authority: Pubkey)]
+pub struct RotateSession<'info> {
+    #[account(
+        mut,
+        close = main_wallet,
+        seeds = [b"session", old_authority.as_ref()],
+        bump = old_session.bump,
+        constraint = old_session.main_address == main_wallet.key() @ PixelError::InvalidAuth,
+    )]
+    pub old_session: Account<'info, SessionAccount>,
+
+    #[account(
+        init,
+        payer = main_wallet,
+        space = 8 + SessionAccount::INIT_SPACE,
+        seeds = [b"session", new_authority.as_ref()],
+        bump
+    )]
+    pub new_session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, main_wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(mut)]
+    pub main_wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Combined initialization and delegation accounts struct
+/// This allows initializing a shard and delegating it to ER in a single transaction
+/// Initialize a shard (without delegation)
+/// Call delegate_shard separately after this to delegate to ER
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct InitializeShard<'info> {
+    /// The shard account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PixelShard::INIT_SPACE,
+        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    /// Ownership record for the shard, kept on the base layer
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ShardDeed::INIT_SPACE,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    /// CHECK: The session account, could be delegated. Verified by seeds and custom owner check.
+    #[account(
+        seeds = [b"session", authority.key().as_ref()],
+        bump,
+    )]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ PixelError::GamePaused,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// Receives the platform fee
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Shard in either layout, checked in the handler
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct MigrateShard<'info> {
+    /// CHECK: Verified by seeds; owner, discriminator and layout are checked in the handler
+    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Session at any version, signed by its main wallet; the layout is checked in the handler
+/// A delegated session fails the owner check on `session`
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct MigrateSession<'info> {
+    /// CHECK: Verified by seeds and owner; discriminator and layout are checked in the handler
+    #[account(mut, owner = crate::ID, seeds = [b"session", authority.as_ref()], bump)]
+    pub session: UncheckedAccount<'info>,
+
+    /// A baseline session is registered here, as initialize_user does; must not be delegated
+    #[account(
+        init_if_needed,
+        payer = main_wallet,
+        space = 8 + WalletProfile::INIT_SPACE,
+        seeds = [PROFILE_SEED, main_wallet.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(mut)]
+    pub main_wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Retire a baseline session on the base layer, signed by its session key, which gets the rent
+#[derive(Accounts)]
+pub struct CloseBaselineSession<'info> {
+    /// CHECK: Verified by seeds; owner and layout are checked in the handler
+    #[account(mut, seeds = [b"session", authority.key().as_ref()], bump)]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Close a shard and its deed; a delegated shard fails the owner check on `shard`
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct CloseShard<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = current_shard_bump(&shard)?,
+    )]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+        constraint = !deed.frozen @ PixelError::ShardFrozen,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    /// Must be absent: cancel the listing first
+    /// CHECK: Only checked for emptiness
+    #[account(
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump,
+        constraint = listing.data_is_empty() @ PixelError::ShardListed,
+    )]
+    pub listing: UncheckedAccount<'info>,
+
+    /// CHECK: The shard's earnings record, closed along with it if it exists
+    #[account(
+        mut,
+        seeds = [EARNINGS_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump,
+    )]
+    pub earnings: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Batch shard creation; the (shard, deed) pairs come in remaining_accounts
+#[derive(Accounts)]
+pub struct InitializeShards<'info> {
+    /// CHECK: The session account, could be delegated. Verified by seeds and custom owner check.
+    #[account(
+        seeds = [b"session", authority.key().as_ref()],
+        bump,
+    )]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ PixelError::GamePaused,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// Receives the platform fee
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Claim-if-needed and paint; the shard must still be on the base layer
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct ClaimAndPaint<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PixelShard::INIT_SPACE,
+        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ShardDeed::INIT_SPACE,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump,
+        constraint = !deed.frozen @ PixelError::ShardFrozen,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    /// CHECK: The session account, could be delegated. Verified by seeds and custom owner check.
+    #[account(
+        seeds = [b"session", authority.key().as_ref()],
+        bump,
+    )]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ PixelError::GamePaused,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// Receives the platform fee when the shard is claimed
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Delegate an existing shard to Ephemeral Rollups
+/// This should be called after initialize_shard in a separate transaction
+#[delegate]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct DelegateShard<'info> {
+    /// The authority requesting delegation
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: The shard PDA to delegate - validated by seeds constraint
+    #[account(mut, del, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub pda: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct PlacePixel<'info> {
+    #[account(
+        mut,
+        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = shard_bump(&shard)?
+    )]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    /// Read-only on the ER (cloned from the base layer); decides who paints cooldown-free
+    #[account(
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        constraint = !deed.frozen @ PixelError::ShardFrozen,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    #[account(
+        mut,
+        seeds = [b"session", signer.key().as_ref()],
+        bump = session.bump,
+        constraint = !session.revoked @ PixelError::SessionRevoked,
+        constraint = !session.is_expired(Clock::get()?.unix_timestamp) @ PixelError::SessionExpired,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, session.main_address.as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    /// Live game parameters, read-only on the ER
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ PixelError::GamePaused,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// Optional cooldown pass owned by the session's main wallet, read-only on the ER
+    pub pass: Option<Account<'info, CooldownPass>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+impl PlacePixel<'_> {
+    /// Apply ownership, pass and cooldown rules to `changed` freshly painted pixels
+    /// `accrue` is false for erasing, which earns the owner nothing
+    fn settle_paint(&mut self, changed: u32, accrue: bool) -> Result<()> {
+        if changed == 0 {
+            return Ok(());
+        }
+        let main_wallet = self.session.main_address;
+        let is_owner = self.deed.owner == main_wallet;
+        let now = Clock::get()?.unix_timestamp;
+        let mut shard = ShardView::load_mut(self.shard.as_ref())?;
+        let has_pass = self.pass.as_ref().is_some_and(|pass| {
+            pass.covers(&main_wallet, shard.shard_x(), shard.shard_y(), now)
+        });
+
+        // Pass holders paint without limit, so their paint earns the owner nothing; otherwise
+        // an owner could mint tokens by painting their own shard from a second wallet with a pass
+        if let Some(stats) = shard.stats_mut() {
+            if accrue && !is_owner {
+                if !has_pass {
+                    stats.foreign_pixels = stats.foreign_pixels.saturating_add(changed as u64);
+                }
+                stats.last_foreign_paint = now;
+            }
+            stats.last_activity = now;
+        }
+
+        if !is_owner && !has_pass {
+            let pixels = u8::try_from(changed).map_err(|_| PixelError::BulkExceedsCooldown)?;
+            self.profile.charge_cooldown(pixels, &self.config.params, now as u64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cross-shard placement; (shard, deed) pairs come in remaining_accounts
+#[derive(Accounts)]
+pub struct PlacePixelsCrossShard<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", signer.key().as_ref()],
+        bump = session.bump,
+        constraint = !session.revoked @ PixelError::SessionRevoked,
+        constraint = !session.is_expired(Clock::get()?.unix_timestamp) @ PixelError::SessionExpired,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, session.main_address.as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ PixelError::GamePaused,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// Optional cooldown pass owned by the session's main wallet, read-only on the ER
+    pub pass: Option<Account<'info, CooldownPass>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// Backfill the deed of a shard created before deeds; the shard may be delegated
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct InitializeDeed<'info> {
+    /// CHECK: Verified by seeds; owner and layout are checked by committed_shard_view
+    #[account(seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ShardDeed::INIT_SPACE,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct TransferShard<'info> {
+    /// CHECK: The shard PDA, possibly delegated. Verified by seeds and custom owner check;
+    /// only its committed foreign_pixels counter is read
+    #[account(seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    /// Must be absent: a listed shard can only change hands through buy_shard
+    /// CHECK: Only checked for emptiness
+    #[account(
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump,
+        constraint = listing.data_is_empty() @ PixelError::ShardListed,
+    )]
+    pub listing: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ShardEarnings::INIT_SPACE,
+        seeds = [EARNINGS_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub earnings: Account<'info, ShardEarnings>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    /// Only needed when earnings are owed, like the token accounts below
+    #[account(mut, seeds = [MINT_SEED], bump)]
+    pub mint: Option<Account<'info, Mint>>,
+
+    /// Receives the outgoing owner's unclaimed earnings
+    #[account(mut, token::mint = mint, token::authority = owner)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct ListShard<'info> {
+    #[account(
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ShardListing::INIT_SPACE,
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub listing: Account<'info, ShardListing>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct CancelListing<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = listing.bump,
+        has_one = seller @ PixelError::NotShardOwner,
+    )]
+    pub listing: Account<'info, ShardListing>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct BuyShard<'info> {
+    /// CHECK: The shard PDA, possibly delegated. Verified by seeds and custom owner check;
+    /// only its committed foreign_pixels counter is read
+    #[account(seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"session", authority.key().as_ref()],
-        bump = user.bump,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        constraint = deed.owner == listing.seller @ PixelError::NotShardOwner,
     )]
-    pub user: Account<'info, SessionAccount>,
-    /// The session key authority
+    pub deed: Account<'info, ShardDeed>,
+
+    /// Escrows the buyer's payment, then closes to the seller with price + rent
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, ShardListing>,
+
+    /// CHECK: Receives the sale proceeds; must match listing.seller
     #[account(mut)]
-    pub authority: Signer<'info>,
-    /// CHECK: The PDA to delegate - same as user, used for delegation CPI
-    #[account(mut, del, seeds = [b"session", authority.key().as_ref()], bump)]
-    pub pda: AccountInfo<'info>,
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + ShardEarnings::INIT_SPACE,
+        seeds = [EARNINGS_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub earnings: Account<'info, ShardEarnings>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    /// Only needed when earnings are owed, like the token accounts below
+    #[account(mut, seeds = [MINT_SEED], bump)]
+    pub mint: Option<Account<'info, Mint>>,
+
+    /// Receives the outgoing owner's unclaimed earnings
+    #[account(mut, token::mint = mint, token::authority = seller)]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut, constraint = buyer.key() != listing.seller @ PixelError::InvalidAuth)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Combined initialization and delegation accounts struct
-/// This allows initializing a shard and delegating it to ER in a single transaction
-/// Initialize a shard (without delegation)
-/// Call delegate_shard separately after this to delegate to ER
 #[derive(Accounts)]
 #[instruction(shard_x: u16, shard_y: u16)]
-pub struct InitializeShard<'info> {
-    /// The shard account to initialize
+pub struct ClaimEarnings<'info> {
+    /// CHECK: The shard PDA, possibly delegated. Verified by seeds and custom owner check;
+    /// only its committed foreign_pixels counter is read
+    #[account(seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
     #[account(
-        init,
-        payer = authority,
-        space = 8 + PixelShard::INIT_SPACE,
-        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ShardEarnings::INIT_SPACE,
+        seeds = [EARNINGS_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
         bump
     )]
-    pub shard: Account<'info, PixelShard>,
+    pub earnings: Account<'info, ShardEarnings>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut, seeds = [MINT_SEED], bump)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = mint, token::authority = owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct ReclaimShard<'info> {
+    /// CHECK: The shard PDA, possibly delegated. Verified by seeds and custom owner check;
+    /// only its committed last_activity and foreign_pixels are read
+    #[account(seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    /// Must be absent: listed shards are not abandoned
+    /// CHECK: Only checked for emptiness
+    #[account(
+        seeds = [LISTING_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump,
+        constraint = listing.data_is_empty() @ PixelError::ShardListed,
+    )]
+    pub listing: UncheckedAccount<'info>,
 
     /// CHECK: The session account, could be delegated. Verified by seeds and custom owner check.
     #[account(
@@ -522,101 +3267,479 @@ pub struct InitializeShard<'info> {
     )]
     pub session: UncheckedAccount<'info>,
 
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ShardEarnings::INIT_SPACE,
+        seeds = [EARNINGS_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump
+    )]
+    pub earnings: Account<'info, ShardEarnings>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    /// Only needed when earnings are owed, like the token accounts below
+    #[account(mut, seeds = [MINT_SEED], bump)]
+    pub mint: Option<Account<'info, Mint>>,
+
+    /// Receives the outgoing owner's unclaimed earnings
+    #[account(mut, token::mint = mint, token::authority = deed.owner)]
+    pub previous_owner_token_account: Option<Account<'info, TokenAccount>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
 
-/// Delegate an existing shard to Ephemeral Rollups
-/// This should be called after initialize_shard in a separate transaction
-#[delegate]
 #[derive(Accounts)]
 #[instruction(shard_x: u16, shard_y: u16)]
-pub struct DelegateShard<'info> {
-    /// The authority requesting delegation
+pub struct SetPassPrice<'info> {
+    #[account(
+        mut,
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(scope: PassScope)]
+pub struct BuyCooldownPass<'info> {
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + CooldownPass::INIT_SPACE,
+        seeds = [PASS_SEED, buyer.key().as_ref(), &scope.seed()],
+        bump
+    )]
+    pub pass: Account<'info, CooldownPass>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, GameConfig>,
+
+    /// Receives payment for global passes
+    #[account(mut, seeds = [TREASURY_SEED], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Deed of the shard in scope; required for shard passes
+    pub deed: Option<Account<'info, ShardDeed>>,
+
+    /// CHECK: Receives SOL for shard passes; must be deed.owner
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub shard_owner: Option<UncheckedAccount<'info>>,
 
-    /// CHECK: The shard PDA to delegate - validated by seeds constraint
+    #[account(mut)]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// Main wallet buying the pass
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Read-only access to a shard for get_pixel and get_region
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct GetPixel<'info> {
+    #[account(
+        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = shard_bump(&shard)?
+    )]
+    pub shard: AccountLoader<'info, PixelShard>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct DelegateShardInput<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The PDA to delegate - validated by seeds constraint
     #[account(mut, del, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
     pub pda: AccountInfo<'info>,
 }
 
-#[derive(Accounts)]
-#[instruction(shard_x: u16, shard_y: u16)]
-pub struct PlacePixel<'info> {
-    #[account(
-        mut,
-        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
-        bump = shard.bump
-    )]
-    pub shard: Account<'info, PixelShard>,
+#[commit]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct CommitShardInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump = shard_bump(&shard)?)]
+    pub shard: AccountLoader<'info, PixelShard>,
+}
+
+/// Commit a batch of shards; the shards themselves come in remaining_accounts
+#[commit]
+#[derive(Accounts)]
+pub struct CommitShards<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+/// Undelegate a shard, signed by the owner recorded on its deed
+#[commit]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct UndelegateShard<'info> {
+    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump = current_shard_bump(&shard)?)]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    /// Read-only on the ER (cloned from the base layer)
+    #[account(
+        seeds = [DEED_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
+        bump = deed.bump,
+        has_one = owner @ PixelError::NotShardOwner,
+    )]
+    pub deed: Account<'info, ShardDeed>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Commit and undelegate any shard, signed by the admin or moderator
+#[commit]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct CommitAndUndelegateShard<'info> {
+    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump = current_shard_bump(&shard)?)]
+    pub shard: AccountLoader<'info, PixelShard>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = moderator.key() == config.moderator || moderator.key() == config.admin
+            @ PixelError::NotModerator,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub moderator: Signer<'info>,
+}
+
+/// Undelegate a session account, signed by its session key
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateUser<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", authority.key().as_ref()],
+        bump = session.bump,
+        constraint = !session.revoked @ PixelError::SessionRevoked,
+    )]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, session.main_address.as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Undelegate a wallet profile, signed by its main wallet
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateProfile<'info> {
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, main_wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, WalletProfile>,
+
+    #[account(mut)]
+    pub main_wallet: Signer<'info>,
+}
+
+/// Undelegate a baseline session account, signed by its session key
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateBaselineSession<'info> {
+    /// CHECK: Verified by seeds; owner and layout are checked in the handler
+    #[account(mut, seeds = [b"session", authority.key().as_ref()], bump)]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Undelegate a baseline shard account, paid by anyone
+#[commit]
+#[derive(Accounts)]
+#[instruction(shard_x: u16, shard_y: u16)]
+pub struct UndelegateBaselineShard<'info> {
+    /// CHECK: Verified by seeds; owner and layout are checked in the handler
+    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
+    pub shard: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+// ========================================
+// Account Data
+// ========================================
+
+/// A single shard of the pixel canvas
+/// Each shard stores 8,100 pixels (90×90 grid) using 8-bit colors = ~8KB
+/// Up to 33,942,276 shards (5826×5826 grid) can cover the full 524,288×524,288 canvas
+/// Shards are created on-demand when users paint in new regions
+/// Zero-copy so a single pixel write touches one byte instead of re-serializing the whole shard;
+/// packed so the account carries no padding (8,170 bytes, 21 more than the baseline Borsh layout)
+#[account(zero_copy)]
+#[derive(InitSpace)]
+#[repr(C, packed)]
+pub struct PixelShard {
+    /// Running total of pixels placed by non-owners without a cooldown pass, paid out via claim_earnings
+    pub foreign_pixels: u64,
+    /// Unix timestamp of the last placement or erase on this shard
+    pub last_activity: i64,
+    /// Unix timestamp of the last pixel placed by a non-owner (0 = never)
+    pub last_foreign_paint: i64,
+    /// Creator of the shard (who paid for initialization)
+    pub creator: Pubkey,
+    /// Pixel data - 8-bit storage (1 byte per pixel)
+    /// Index = local_y * 90 + local_x
+    /// Value = color_index (0 = unset/transparent, 1-255 = palette colors)
+    pub pixels: [u8; PIXELS_PER_SHARD],
+    /// Shard X coordinate (0-5825)
+    pub shard_x: u16,
+    /// Shard Y coordinate (0-5825)
+    pub shard_y: u16,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Layout version, upgraded by migrate_shard
+    pub version: u8,
+}
+
+/// PixelShard as first deployed, read only by migrate_shard
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct BaselinePixelShard {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    #[max_len(PIXELS_PER_SHARD)]
+    pub pixels: Vec<u8>,
+    pub creator: Pubkey,
+    pub bump: u8,
+}
+
+/// Global settings governed by the admin
+/// Never delegated; the ER reads it as a cloned read-only account
+#[account]
+#[derive(InitSpace)]
+pub struct GameConfig {
+    pub admin: Pubkey,
+    /// May freeze and unfreeze individual shards
+    pub moderator: Pubkey,
+    /// Global emergency brake for placement and shard creation
+    pub paused: bool,
+    pub params: ConfigParams,
+    pub bump: u8,
+}
+
+/// Tunable game parameters, replaced as a whole by update_config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct ConfigParams {
+    /// Max pixels a non-owner may place before the cooldown kicks in
+    pub cooldown_limit: u8,
+    /// Seconds after hitting the limit before the burst counter resets
+    pub cooldown_period: u64,
+    /// Highest valid color index (colors are 1..=available_colors)
+    pub available_colors: u8,
+    /// Max pixels in a single place_pixels_bulk call
+    pub max_bulk_pixels: u8,
+    /// Platform fee in lamports paid to the treasury by initialize_shard
+    pub shard_fee_lamports: u64,
+    /// Per-shard discount on the fee for initialize_shards batches, in basis points per extra shard
+    pub batch_discount_bps: u16,
+    /// Price of a global cooldown pass in lamports, paid to the treasury (0 = not for sale)
+    pub global_pass_price_lamports: u64,
+    /// Seconds a shard must be inactive before reclaim_shard can take it (0 = disabled)
+    pub reclaim_after_secs: i64,
+    /// Fee in lamports paid to the treasury to reclaim a dead shard
+    pub reclaim_fee_lamports: u64,
+    /// Seconds since the last foreign paint before close_shard may close a painted shard
+    /// (0 = any time; blank shards can always be closed)
+    pub close_quiet_secs: i64,
+    /// Who may erase pixels on a shard they don't own
+    pub erase_policy: ErasePolicy,
+}
+
+/// Rules for erasing pixels on someone else's shard
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ErasePolicy {
+    /// Anyone may erase, charged against the same cooldown as placing
+    RateLimited,
+    /// Only the shard owner may erase
+    OwnerOnly,
+}
+
+impl Default for ConfigParams {
+    fn default() -> Self {
+        Self {
+            cooldown_limit: DEFAULT_COOLDOWN_LIMIT,
+            cooldown_period: DEFAULT_COOLDOWN_PERIOD,
+            available_colors: DEFAULT_AVAILABLE_COLORS,
+            max_bulk_pixels: DEFAULT_MAX_BULK_PIXELS,
+            shard_fee_lamports: 0,
+            batch_discount_bps: DEFAULT_BATCH_DISCOUNT_BPS,
+            global_pass_price_lamports: 0,
+            reclaim_after_secs: 0,
+            reclaim_fee_lamports: 0,
+            close_quiet_secs: 0,
+            erase_policy: ErasePolicy::RateLimited,
+        }
+    }
+}
+
+impl ConfigParams {
+    pub fn cooldown_rules(&self) -> CooldownRules {
+        CooldownRules {
+            limit: self.cooldown_limit,
+            period: self.cooldown_period,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.cooldown_limit > 0
+                && self.available_colors > 0
+                && self.max_bulk_pixels > 0
+                && self.batch_discount_bps <= MAX_BATCH_DISCOUNT_BPS
+                && self.reclaim_after_secs >= 0
+                && self.close_quiet_secs >= 0,
+            PixelError::InvalidConfig
+        );
+        Ok(())
+    }
+
+    /// Platform fee for creating `count` shards at once
+    /// Each shard beyond the first adds batch_discount_bps off the whole batch, up to MAX_BATCH_DISCOUNT_BPS
+    pub fn batch_shard_fee(&self, count: usize) -> u64 {
+        let extra = count.saturating_sub(1) as u64;
+        let discount_bps = (self.batch_discount_bps as u64 * extra).min(MAX_BATCH_DISCOUNT_BPS as u64);
+        let full = self.shard_fee_lamports as u128 * count as u128;
+        (full * (10_000 - discount_bps) as u128 / 10_000) as u64
+    }
+}
+
+/// Program-owned account that accumulates platform fees
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8,
+}
+
+/// Current owner of a shard
+/// Never delegated, so ownership can change on the base layer while the shard's pixels
+/// live on the ER; the ER reads it as a cloned read-only account during placement
+#[account]
+#[derive(InitSpace)]
+pub struct ShardDeed {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    /// Main wallet that owns the shard and paints on it without cooldown
+    pub owner: Pubkey,
+    /// Price of a cooldown pass for this shard in lamports (0 = not sold for SOL)
+    pub pass_price_lamports: u64,
+    /// Price of a cooldown pass for this shard in project token base units (0 = not sold for tokens)
+    pub pass_price_tokens: u64,
+    /// Unix timestamp of the owner's last action on the deed (transfer, listing, pricing, claim)
+    pub last_activity: i64,
+    /// Set by a moderator to make the shard read-only
+    pub frozen: bool,
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        seeds = [b"session", signer.key().as_ref()],
-        bump = session.bump,
-    )]
-    pub session: Account<'info, SessionAccount>,
+/// How much of a shard's foreign_pixels have already been paid out as tokens
+/// Base-layer only, so claiming never needs the shard itself to be writable
+#[account]
+#[derive(InitSpace)]
+pub struct ShardEarnings {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub claimed_pixels: u64,
+    pub bump: u8,
+}
 
-    #[account(mut)]
-    pub signer: Signer<'info>,
+/// Lets a painter skip cooldown on one shard or everywhere until valid_until
+#[account]
+#[derive(InitSpace)]
+pub struct CooldownPass {
+    /// Main wallet the pass belongs to
+    pub painter: Pubkey,
+    pub scope: PassScope,
+    pub valid_until: i64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-#[instruction(shard_x: u16, shard_y: u16)]
-pub struct GetPixel<'info> {
-    #[account(
-        seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()],
-        bump = shard.bump
-    )]
-    pub shard: Account<'info, PixelShard>,
+impl CooldownPass {
+    pub fn covers(&self, painter: &Pubkey, shard_x: u16, shard_y: u16, now: i64) -> bool {
+        &self.painter == painter
+            && now < self.valid_until
+            && match self.scope {
+                PassScope::Global => true,
+                PassScope::Shard { shard_x: x, shard_y: y } => x == shard_x && y == shard_y,
+            }
+    }
 }
 
-#[delegate]
-#[derive(Accounts)]
-#[instruction(shard_x: u16, shard_y: u16)]
-pub struct DelegateShardInput<'info> {
-    pub payer: Signer<'info>,
-    /// CHECK: The PDA to delegate - validated by seeds constraint
-    #[account(mut, del, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump)]
-    pub pda: AccountInfo<'info>,
+/// Where a cooldown pass applies
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PassScope {
+    Global,
+    Shard { shard_x: u16, shard_y: u16 },
 }
 
-#[commit]
-#[derive(Accounts)]
-#[instruction(shard_x: u16, shard_y: u16)]
-pub struct CommitShardInput<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(mut, seeds = [SHARD_SEED, &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], bump = shard.bump)]
-    pub shard: Account<'info, PixelShard>,
+impl PassScope {
+    /// PDA seed bytes; shard coordinates never reach u16::MAX so the global scope can't collide
+    pub fn seed(&self) -> [u8; 4] {
+        let (x, y) = match self {
+            PassScope::Global => (u16::MAX, u16::MAX),
+            PassScope::Shard { shard_x, shard_y } => (*shard_x, *shard_y),
+        };
+        let mut seed = [0u8; 4];
+        seed[..2].copy_from_slice(&x.to_le_bytes());
+        seed[2..].copy_from_slice(&y.to_le_bytes());
+        seed
+    }
 }
 
-// ========================================
-// Account Data
-// ========================================
+/// Currency used to buy a cooldown pass
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PassPayment {
+    Sol,
+    Token,
+}
 
-/// A single shard of the pixel canvas
-/// Each shard stores 8,100 pixels (90×90 grid) using 8-bit colors = ~8KB
-/// Up to 33,942,276 shards (5826×5826 grid) can cover the full 524,288×524,288 canvas
-/// Shards are created on-demand when users paint in new regions
+/// A fixed-price sale offer for a shard
 #[account]
 #[derive(InitSpace)]
-pub struct PixelShard {
-    /// Shard X coordinate (0-5825)
+pub struct ShardListing {
     pub shard_x: u16,
-    /// Shard Y coordinate (0-5825)
     pub shard_y: u16,
-    /// Pixel data - 8-bit storage (1 byte per pixel)
-    /// Index = local_y * 90 + local_x
-    /// Value = color_index (0 = unset/transparent, 1-255 = palette colors)
-    #[max_len(8100)]
-    pub pixels: Vec<u8>,
-    /// Creator of the shard (who paid for initialization)
-    pub creator: Pubkey,
-    /// PDA bump seed
+    /// Owner at listing time, who receives the proceeds
+    pub seller: Pubkey,
+    /// Asking price in lamports
+    pub price: u64,
     pub bump: u8,
 }
 
@@ -627,13 +3750,268 @@ pub struct PixelShard {
 pub struct SessionAccount {
     pub main_address: Pubkey,
     pub authority: Pubkey,
+    /// Nonce of the main wallet authorization that created this session
+    pub auth_nonce: u64,
+    /// Unix timestamp after which the session stops working (0 = never)
+    pub expires_at: i64,
+    /// Set by the main wallet to disable a leaked or retired session key
+    pub revoked: bool,
+    pub bump: u8,
+    /// Layout version, upgraded by migrate_session
+    pub version: u8,
+}
+
+/// SessionAccount as first deployed, only ever closed by close_baseline_session
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct BaselineSessionAccount {
+    pub main_address: Pubkey,
+    pub authority: Pubkey,
+    pub cooldown_counter: u8,
+    pub last_place_timestamp: u64,
+    pub bump: u8,
+}
+
+impl PixelShard {
+    /// Current layout version, stamped on new shards and by migrate_shard
+    pub const VERSION: u8 = 1;
+}
+
+/// A shard's account data read through the layout its version calls for
+/// Baseline shards keep painting and reading until migrate_shard upgrades them, but have no
+/// room for activity or earnings, so those only start counting once the shard is migrated
+pub struct ShardView<D> {
+    data: D,
+    /// 0 for the baseline Borsh layout, otherwise PixelShard::version
+    version: u8,
+}
+
+impl<'a> ShardView<Ref<'a, [u8]>> {
+    /// Borrow a shard for reading; owner checks are left to the caller
+    pub fn load(info: &'a AccountInfo) -> Result<Self> {
+        let data = Ref::map(info.try_borrow_data()?, |data| &**data);
+        let version = shard_version(&data)?;
+        Ok(Self { data, version })
+    }
+}
+
+impl<'a> ShardView<RefMut<'a, [u8]>> {
+    /// Borrow a shard for painting; owner checks are left to the caller
+    pub fn load_mut(info: &'a AccountInfo) -> Result<Self> {
+        let data = RefMut::map(info.try_borrow_mut_data()?, |data| &mut **data);
+        let version = shard_version(&data)?;
+        Ok(Self { data, version })
+    }
+}
+
+impl<D: Deref<Target = [u8]>> ShardView<D> {
+    // Baseline offsets, discriminator included: shard_x, shard_y and the pixel Vec's
+    // length prefix come first, the creator and bump after the pixels
+    const BASELINE_PIXELS: usize = 8 + 2 + 2 + 4;
+    const BASELINE_CREATOR: usize = Self::BASELINE_PIXELS + PIXELS_PER_SHARD;
+    const BASELINE_BUMP: usize = Self::BASELINE_CREATOR + 32;
+
+    fn current(&self) -> &PixelShard {
+        bytemuck::from_bytes(&self.data[PixelShard::DISCRIMINATOR.len()..])
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn shard_x(&self) -> u16 {
+        match self.version {
+            0 => u16::from_le_bytes([self.data[8], self.data[9]]),
+            _ => self.current().shard_x,
+        }
+    }
+
+    pub fn shard_y(&self) -> u16 {
+        match self.version {
+            0 => u16::from_le_bytes([self.data[10], self.data[11]]),
+            _ => self.current().shard_y,
+        }
+    }
+
+    pub fn creator(&self) -> Pubkey {
+        match self.version {
+            0 => Pubkey::new_from_array(self.data[Self::BASELINE_CREATOR..Self::BASELINE_BUMP].try_into().unwrap()),
+            _ => self.current().creator,
+        }
+    }
+
+    pub fn bump(&self) -> u8 {
+        match self.version {
+            0 => self.data[Self::BASELINE_BUMP],
+            _ => self.current().bump,
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        match self.version {
+            0 => &self.data[Self::BASELINE_PIXELS..Self::BASELINE_CREATOR],
+            _ => &self.current().pixels,
+        }
+    }
+}
+
+impl<D: DerefMut<Target = [u8]>> ShardView<D> {
+    fn current_mut(&mut self) -> &mut PixelShard {
+        bytemuck::from_bytes_mut(&mut self.data[PixelShard::DISCRIMINATOR.len()..])
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        match self.version {
+            0 => &mut self.data[Self::BASELINE_PIXELS..Self::BASELINE_CREATOR],
+            _ => &mut self.current_mut().pixels,
+        }
+    }
+
+    /// The whole current-layout shard, for activity and earnings; None for a baseline shard
+    pub fn stats_mut(&mut self) -> Option<&mut PixelShard> {
+        match self.version {
+            0 => None,
+            _ => Some(self.current_mut()),
+        }
+    }
+
+    /// Paint `length` pixels of row `local_y` from column `local_x`, returning how many changed
+    /// Callers validate that the span lies inside the shard
+    pub fn paint_span(&mut self, local_x: u8, local_y: u8, length: u8, color: u8) -> u32 {
+        let start = local_index(local_x, local_y);
+        let mut changed = 0;
+        for pixel in self.pixels_mut()[start..start + length as usize].iter_mut() {
+            if *pixel != color {
+                *pixel = color;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+impl SessionAccount {
+    /// Current layout version, stamped on new sessions and by migrate_session
+    pub const VERSION: u8 = 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// Authoritative per-main-wallet state, seeded by the main wallet address
+/// Cooldown lives here so minting extra session keys can't reset it
+#[account]
+#[derive(InitSpace)]
+pub struct WalletProfile {
+    pub main_address: Pubkey,
+    /// Session key authorities currently registered for this wallet
+    #[max_len(MAX_SESSIONS_PER_WALLET)]
+    pub sessions: Vec<Pubkey>,
+    /// Highest authorization nonce consumed; new sessions must use a larger one
+    pub last_auth_nonce: u64,
     pub cooldown_counter: u8,
     pub last_place_timestamp: u64,
     pub bump: u8,
 }
 
+impl WalletProfile {
+    /// Charge `pixels` against the burst limit, resetting it once the cooldown period has passed
+    pub fn charge_cooldown(&mut self, pixels: u8, config: &ConfigParams, now: u64) -> Result<()> {
+        let mut cooldown = Cooldown {
+            counter: self.cooldown_counter,
+            last_place_timestamp: self.last_place_timestamp,
+        };
+        cooldown.charge(pixels, config.cooldown_rules(), now).map_err(PixelError::from)?;
+        self.cooldown_counter = cooldown.counter;
+        self.last_place_timestamp = cooldown.last_place_timestamp;
+        Ok(())
+    }
+}
+
+/// Shard coordinates, used to address shards passed in remaining_accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ShardCoord {
+    pub shard_x: u16,
+    pub shard_y: u16,
+}
+
+/// Pixel data for cross-shard placement, in global canvas coordinates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GlobalPixel {
+    pub px: u32,
+    pub py: u32,
+    /// Color index (1-255, 0 is reserved for transparent)
+    pub color: u8,
+}
+
+/// Result of get_pixel
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PixelRead {
+    pub color: u8,
+    pub creator: Pubkey,
+}
+
+/// Result of get_region
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RegionRead {
+    pub creator: Pubkey,
+    /// Colors of the region, row by row (index = row * width + column)
+    pub colors: Vec<u8>,
+}
+
+/// What a compare-and-swap placement does when a pixel no longer has its expected color
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Abort the whole instruction with PixelConflict
+    Fail,
+    /// Leave the pixel alone, report it in the return data and carry on
+    Skip,
+}
+
+/// Single pixel for compare-and-swap placement, in global coordinates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CheckedPixel {
+    pub px: u32,
+    pub py: u32,
+    pub color: u8,
+    /// Only paint if the pixel currently has this color (None = always paint)
+    pub expected_color: Option<u8>,
+}
+
+/// Bulk pixel for compare-and-swap placement, in local shard coordinates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CheckedBulkPixel {
+    pub local_x: u8,
+    pub local_y: u8,
+    pub color: u8,
+    /// Only paint if the pixel currently has this color (None = always paint)
+    pub expected_color: Option<u8>,
+}
+
+/// A pixel skipped by compare-and-swap placement, returned via return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PixelConflict {
+    /// Position of the pixel in the instruction's input
+    pub index: u16,
+    /// Color the pixel actually has
+    pub current_color: u8,
+}
+
+/// A horizontal run of same-colored pixels, in local shard coordinates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PixelRun {
+    /// Row within the shard (0-89)
+    pub row: u8,
+    /// First column of the run (0-89)
+    pub start: u8,
+    /// Number of pixels in the run; start + length must not exceed 90
+    pub length: u8,
+    /// Color index (1-255, 0 is reserved for transparent)
+    pub color: u8,
+}
+
 /// Pixel data for bulk placement
-/// Uses local coordinates within a shard (0-89)
+/// Uses local coordinates within a shard (0-89), limited to the on-canvas part of edge shards
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct BulkPixel {
     /// Local X coordinate within shard (0-89)
@@ -664,10 +4042,103 @@ pub enum PixelError {
     Cooldown,
     #[msg("Bulk pixels array is empty")]
     EmptyBulkPixels,
-    #[msg("Bulk pixels exceeds maximum limit of 50")]
+    #[msg("Bulk pixels exceeds the configured maximum")]
     BulkTooLarge,
     #[msg("Bulk placement would exceed cooldown limit")]
     BulkExceedsCooldown,
+    #[msg("First instruction must be a single-signature Ed25519 verify over its own data")]
+    InvalidEd25519Instruction,
+    #[msg("Ed25519 signer does not match the main wallet")]
+    AuthPubkeyMismatch,
+    #[msg("Ed25519 signature does not match the provided signature")]
+    AuthSignatureMismatch,
+    #[msg("Signed message is not the canonical session authorization")]
+    AuthMessageMismatch,
+    #[msg("Session authorization has expired")]
+    AuthExpired,
+    #[msg("Authorization nonce must be greater than the last one used")]
+    AuthNonceReused,
+    #[msg("Main wallet already has the maximum number of session keys")]
+    TooManySessions,
+    #[msg("Session key has been revoked")]
+    SessionRevoked,
+    #[msg("Session key has expired")]
+    SessionExpired,
+    #[msg("Session must be revoked before it can be closed")]
+    SessionNotRevoked,
+    #[msg("Signer does not own this shard")]
+    NotShardOwner,
+    #[msg("Shard is listed for sale; cancel the listing first")]
+    ShardListed,
+    #[msg("Listing price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Listing price is above the buyer's maximum")]
+    ListingPriceChanged,
+    #[msg("Signer is not the config admin")]
+    NotAdmin,
+    #[msg("Treasury balance too low for this withdrawal")]
+    InsufficientTreasury,
+    #[msg("Account is not a valid shard")]
+    InvalidShardAccount,
+    #[msg("No earnings to claim yet")]
+    NothingToClaim,
+    #[msg("Cooldown pass is not for sale in this currency")]
+    PassNotForSale,
+    #[msg("An account required for this cooldown pass purchase is missing")]
+    MissingPassAccount,
+    #[msg("Cooldown passes can only be paid in the project token")]
+    InvalidPassMint,
+    #[msg("Invalid config value")]
+    InvalidConfig,
+    #[msg("Reclaiming shards is disabled")]
+    ReclaimDisabled,
+    #[msg("Shard has been active within the reclaim window")]
+    ShardStillActive,
+    #[msg("Game is paused")]
+    GamePaused,
+    #[msg("Shard is frozen by a moderator")]
+    ShardFrozen,
+    #[msg("Signer is not a moderator")]
+    NotModerator,
+    #[msg("Only the shard owner may erase pixels")]
+    EraseOwnerOnly,
+    #[msg("No shards given")]
+    EmptyShardList,
+    #[msg("The same shard was given more than once")]
+    DuplicateShard,
+    #[msg("Pixel color differs from the expected color")]
+    PixelConflict,
+    #[msg("Region exceeds the maximum readable size")]
+    RegionTooLarge,
+    #[msg("Shard has already been claimed")]
+    ShardAlreadyClaimed,
+    #[msg("Too many shards in one call")]
+    TooManyShards,
+    #[msg("Shards must form one contiguous block")]
+    ShardsNotContiguous,
+    #[msg("Shard must be blank or free of foreign paint for the close window")]
+    ShardNotQuiet,
+    #[msg("Session is not in the baseline layout")]
+    NotBaselineSession,
+    #[msg("Shard is not in the baseline layout")]
+    NotBaselineShard,
+    #[msg("Only the program's upgrade authority can initialize the config")]
+    NotUpgradeAuthority,
+    #[msg("Earnings are owed: pass the mint, the receiving token account and the token program")]
+    MissingEarningsAccount,
+    #[msg("Earnings exceed the largest mintable token amount")]
+    EarningsOverflow,
+    #[msg("Shard is in an older layout: run migrate_shard first")]
+    ShardNeedsMigration,
+}
+
+impl From<CooldownError> for PixelError {
+    fn from(error: CooldownError) -> Self {
+        match error {
+            CooldownError::CoolingDown => PixelError::Cooldown,
+            CooldownError::ExceedsLimit => PixelError::BulkExceedsCooldown,
+        }
+    }
 }
 
 // ========================================
@@ -684,6 +4155,15 @@ pub struct PixelChanged {
     pub timestamp: u64,
 }
 
+#[event]
+pub struct PixelErased {
+    pub px: u32,
+    pub py: u32,
+    pub painter: Pubkey,
+    pub main_wallet: Pubkey,
+    pub timestamp: u64,
+}
+
 #[event]
 pub struct ShardInitialized {
     pub shard_x: u16,
@@ -691,4 +4171,108 @@ pub struct ShardInitialized {
     pub creator: Pubkey,
     pub main_wallet: Pubkey,
     pub timestamp: u64,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub main_wallet: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShardTransferred {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    /// Sale price in lamports (0 for a direct transfer)
+    pub price: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct EarningsClaimed {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub owner: Pubkey,
+    pub tokens: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct CooldownPassPurchased {
+    pub painter: Pubkey,
+    pub scope: PassScope,
+    pub valid_until: i64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShardReclaimed {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    /// Fee paid to the treasury in lamports
+    pub fee: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub params: ConfigParams,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShardFreezeChanged {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub frozen: bool,
+    pub moderator: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShardsCommitted {
+    pub shards: Vec<ShardCoord>,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct RectFilled {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub x: u8,
+    pub y: u8,
+    pub width: u8,
+    pub height: u8,
+    pub color: u8,
+    pub changed: u32,
+    pub painter: Pubkey,
+    pub main_wallet: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PixelRunsPlaced {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub runs: Vec<PixelRun>,
+    pub changed: u32,
+    pub painter: Pubkey,
+    pub main_wallet: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShardClosed {
+    pub shard_x: u16,
+    pub shard_y: u16,
+    pub owner: Pubkey,
+    /// Rent returned to the owner for the shard, deed and earnings accounts
+    pub refunded: u64,
+    pub timestamp: u64,
+}
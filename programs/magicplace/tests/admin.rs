@@ -0,0 +1,140 @@
+//! Admin controls: config updates, the pause switch and treasury withdrawals
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{instruction::Instruction, system_instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use magicplace::{ConfigParams, GameConfig, PixelError};
+use solana_sdk::signature::{Keypair, Signer};
+
+fn update_config_ix(admin: Pubkey, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UpdateConfig { config: config_pda(), admin }.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn withdraw_treasury_ix(admin: Pubkey, recipient: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::WithdrawTreasury { config: config_pda(), treasury: treasury_pda(), recipient, admin }
+            .to_account_metas(None),
+        data: magicplace::instruction::WithdrawTreasury { amount }.data(),
+    }
+}
+
+fn initialize_config_ix(admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::InitializeConfig {
+            config: config_pda(),
+            treasury: treasury_pda(),
+            program: magicplace::ID,
+            program_data: program_data_pda(),
+            admin,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::InitializeConfig {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn initialize_config_requires_the_upgrade_authority() {
+    let (deployer, stranger) = (Keypair::new(), Keypair::new());
+    let mut test = program_test_with_upgrade_authority(Some(deployer.pubkey()));
+    test.add_account(deployer.pubkey(), funded_account());
+    test.add_account(stranger.pubkey(), funded_account());
+    let mut ctx = test.start_with_context().await;
+
+    let result = send(&mut ctx, &[initialize_config_ix(stranger.pubkey())], &[&stranger]).await;
+    assert_pixel_error(result, PixelError::NotUpgradeAuthority);
+    assert!(ctx.banks_client.get_account(config_pda()).await.unwrap().is_none());
+
+    send(&mut ctx, &[initialize_config_ix(deployer.pubkey())], &[&deployer]).await.unwrap();
+    let config: GameConfig = fetch(&mut ctx, config_pda()).await;
+    assert_eq!((config.admin, config.moderator), (deployer.pubkey(), deployer.pubkey()));
+}
+
+#[tokio::test]
+async fn immutable_programs_have_no_one_to_initialize_the_config() {
+    let deployer = Keypair::new();
+    let mut test = program_test();
+    test.add_account(deployer.pubkey(), funded_account());
+    let mut ctx = test.start_with_context().await;
+
+    let result = send(&mut ctx, &[initialize_config_ix(deployer.pubkey())], &[&deployer]).await;
+    assert_pixel_error(result, PixelError::NotUpgradeAuthority);
+}
+
+#[tokio::test]
+async fn only_the_admin_sets_valid_params() {
+    let mut world = World::new().await;
+    let (admin, moderator) = (world.admin.insecure_clone(), world.moderator.insecure_clone());
+    let update = |signer: Pubkey, params| update_config_ix(signer, magicplace::instruction::UpdateConfig { params });
+    let params = ConfigParams { cooldown_limit: 5, cooldown_period: 30, ..ConfigParams::default() };
+
+    let result = send(&mut world.ctx, &[update(moderator.pubkey(), params)], &[&moderator]).await;
+    assert_pixel_error(result, PixelError::NotAdmin);
+
+    let invalid = [
+        ConfigParams { cooldown_limit: 0, ..params },
+        ConfigParams { available_colors: 0, ..params },
+        ConfigParams { max_bulk_pixels: 0, ..params },
+        ConfigParams { batch_discount_bps: 2501, ..params },
+        ConfigParams { reclaim_after_secs: -1, ..params },
+        ConfigParams { close_quiet_secs: -1, ..params },
+    ];
+    for params in invalid {
+        let result = send(&mut world.ctx, &[update(admin.pubkey(), params)], &[&admin]).await;
+        assert_pixel_error(result, PixelError::InvalidConfig);
+    }
+
+    send(&mut world.ctx, &[update(admin.pubkey(), params)], &[&admin]).await.unwrap();
+    let config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    assert_eq!((config.params.cooldown_limit, config.params.cooldown_period), (5, 30));
+}
+
+#[tokio::test]
+async fn pausing_stops_placement_and_shard_creation() {
+    let mut world = World::new().await;
+    let (admin, moderator) = (world.admin.insecure_clone(), world.moderator.insecure_clone());
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let set_paused = |signer: Pubkey, paused| update_config_ix(signer, magicplace::instruction::SetPaused { paused });
+
+    let result = send(&mut world.ctx, &[set_paused(moderator.pubkey(), true)], &[&moderator]).await;
+    assert_pixel_error(result, PixelError::NotAdmin);
+    send(&mut world.ctx, &[set_paused(admin.pubkey(), true)], &[&admin]).await.unwrap();
+
+    let place = || place_pixel_ix(session_key.pubkey(), owner, SHARD, 90, 180, 3);
+    assert_pixel_error(send(&mut world.ctx, &[place()], &[&session_key]).await, PixelError::GamePaused);
+    let create = initialize_shard_ix(session_key.pubkey(), (7, 7));
+    assert_pixel_error(send(&mut world.ctx, &[create], &[&session_key]).await, PixelError::GamePaused);
+
+    send(&mut world.ctx, &[set_paused(admin.pubkey(), false)], &[&admin]).await.unwrap();
+    refresh_blockhash(&mut world.ctx).await;
+    send(&mut world.ctx, &[place()], &[&session_key]).await.unwrap();
+}
+
+#[tokio::test]
+async fn treasury_withdrawals_keep_it_rent_exempt() {
+    let mut world = World::new().await;
+    let (admin, moderator) = (world.admin.insecure_clone(), world.moderator.insecure_clone());
+    let recipient = Pubkey::new_unique();
+    let fees = system_instruction::transfer(&admin.pubkey(), &treasury_pda(), 5_000_000);
+    send(&mut world.ctx, &[fees], &[&admin]).await.unwrap();
+
+    let result = send(&mut world.ctx, &[withdraw_treasury_ix(moderator.pubkey(), recipient, 1)], &[&moderator]).await;
+    assert_pixel_error(result, PixelError::NotAdmin);
+    let result = send(&mut world.ctx, &[withdraw_treasury_ix(admin.pubkey(), recipient, 5_000_001)], &[&admin]).await;
+    assert_pixel_error(result, PixelError::InsufficientTreasury);
+
+    let rent_floor = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap() - 5_000_000;
+    send(&mut world.ctx, &[withdraw_treasury_ix(admin.pubkey(), recipient, 5_000_000)], &[&admin]).await.unwrap();
+    assert_eq!(world.ctx.banks_client.get_balance(recipient).await.unwrap(), 5_000_000);
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), rent_floor);
+}
@@ -0,0 +1,687 @@
+//! Shared harness for the integration tests.
+//!
+//! magicplace always runs from its SBF build, `magicplace.so`: `cargo test-sbf` builds it and
+//! points SBF_OUT_DIR at it, and a plain `cargo test` picks up the one `anchor build` or
+//! `cargo build-sbf` left in target/deploy. Without it every test fails with those instructions.
+//! The program is deployed through the upgradeable loader as on a real cluster. The
+//! delegation program and the magic program are replaced by small builtin stand-ins that
+//! only do what the program relies on: taking ownership of delegated accounts, recording
+//! scheduled commits, and handing accounts back through `process_undelegation`.
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::{AccountInfo, Clock, ProgramError, Pubkey, Rent};
+use anchor_lang::solana_program::{
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, AnchorDeserialize, AnchorSerialize, Discriminator, InstructionData, Space,
+    ToAccountMetas,
+};
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+use ephemeral_rollups_sdk::pda::UNDELEGATE_BUFFER_TAG;
+use magicplace::{
+    BaselinePixelShard, ConfigParams, GameConfig, GlobalPixel, PixelShard, SessionAccount, ShardDeed, Treasury, WalletProfile,
+};
+use solana_program_test::{
+    find_file, processor, read_file, BanksClientError, BanksTransactionResultWithMetadata, ProgramTest,
+    ProgramTestBanksClientExt, ProgramTestContext,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    program::invoke_signed,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::path::{Path, PathBuf};
+
+pub const DELEGATION_PROGRAM_ID: Pubkey = ephemeral_rollups_sdk::id();
+
+/// Test-only instruction tag on the delegation stand-in: undelegate an account on the base layer
+pub const FINALIZE_UNDELEGATION_TAG: [u8; 8] = [0xff; 8];
+
+/// ScheduleCommit / ScheduleCommitAndUndelegate as recorded in the magic context
+pub const SCHEDULE_COMMIT: u8 = 1;
+pub const SCHEDULE_COMMIT_AND_UNDELEGATE: u8 = 2;
+
+const MAGIC_CONTEXT_LEN: usize = 2 + 32 * 16;
+
+fn delegation_stand_in(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match data.get(..8) {
+        // Delegate: the SDK has zeroed the PDA and assigned it to us; restore its data from the buffer
+        Some(tag) if tag == [0u8; 8] => {
+            let (pda, buffer) = (&accounts[1], &accounts[3]);
+            pda.try_borrow_mut_data()?.copy_from_slice(&buffer.try_borrow_data()?);
+            Ok(())
+        }
+        Some(tag) if tag == FINALIZE_UNDELEGATION_TAG => {
+            finalize_undelegation(program_id, accounts, &data[8..])
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Mirrors the delegation program's undelegate: stash the data in a buffer, close the
+/// delegated account and let the owner program recreate it from the buffer
+fn finalize_undelegation(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [pda, buffer, payer, owner_program, system] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let account_seeds = Vec::<Vec<u8>>::try_from_slice(data)?;
+
+    let (_, bump) = Pubkey::find_program_address(&[UNDELEGATE_BUFFER_TAG, pda.key.as_ref()], program_id);
+    let buffer_seeds: &[&[u8]] = &[UNDELEGATE_BUFFER_TAG, pda.key.as_ref(), &[bump]];
+    let len = pda.data_len();
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            buffer.key,
+            Rent::get()?.minimum_balance(len),
+            len as u64,
+            program_id,
+        ),
+        &[payer.clone(), buffer.clone(), system.clone()],
+        &[buffer_seeds],
+    )?;
+    buffer.try_borrow_mut_data()?.copy_from_slice(&pda.try_borrow_data()?);
+
+    move_all_lamports(pda, payer)?;
+    pda.resize(0)?;
+    pda.assign(&system_program::ID);
+
+    let ix = Instruction {
+        program_id: *owner_program.key,
+        accounts: vec![
+            AccountMeta::new(*pda.key, false),
+            AccountMeta::new_readonly(*buffer.key, true),
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: magicplace::instruction::ProcessUndelegation { account_seeds }.data(),
+    };
+    invoke_signed(
+        &ix,
+        &[pda.clone(), buffer.clone(), payer.clone(), system.clone()],
+        &[buffer_seeds],
+    )?;
+
+    move_all_lamports(buffer, payer)?;
+    buffer.resize(0)
+}
+
+fn move_all_lamports(from: &AccountInfo, to: &AccountInfo) -> ProgramResult {
+    **to.try_borrow_mut_lamports()? += from.lamports();
+    **from.try_borrow_mut_lamports()? = 0;
+    Ok(())
+}
+
+/// Records the last scheduled commit in the magic context: [kind, count, pubkeys...]
+fn magic_stand_in(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [payer, context, committed @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let kind = match data.get(..4).map(|tag| u32::from_le_bytes(tag.try_into().unwrap())) {
+        Some(1) => SCHEDULE_COMMIT,
+        Some(2) => SCHEDULE_COMMIT_AND_UNDELEGATE,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+
+    let mut record = context.try_borrow_mut_data()?;
+    record.fill(0);
+    record[0] = kind;
+    record[1] = committed.len() as u8;
+    for (slot, account) in record[2..].chunks_exact_mut(32).zip(committed) {
+        slot.copy_from_slice(account.key.as_ref());
+    }
+    Ok(())
+}
+
+pub fn program_data_pda() -> Pubkey {
+    bpf_loader_upgradeable::get_program_data_address(&magicplace::ID)
+}
+
+/// Where `anchor build` and `cargo build-sbf` leave the program
+const DEPLOY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy");
+
+/// Path of the SBF build: SBF_OUT_DIR (set by `cargo test-sbf`) first, then target/deploy
+fn program_so() -> PathBuf {
+    find_file("magicplace.so")
+        .or_else(|| Some(Path::new(DEPLOY_DIR).join("magicplace.so")).filter(|path| path.exists()))
+        .unwrap_or_else(|| {
+            panic!(
+                "magicplace.so not found in SBF_OUT_DIR or {DEPLOY_DIR}. The integration tests run the \
+                 program's SBF build: run `anchor build` (or `cargo build-sbf`) before `cargo test`, or \
+                 run `cargo test-sbf`, which builds it first"
+            )
+        })
+}
+
+/// magicplace's ProgramData: the loader header naming `upgrade_authority`, then the ELF
+pub fn program_data_account(upgrade_authority: Option<Pubkey>) -> Account {
+    let elf = read_file(program_so());
+    let mut data = bincode_program_data(upgrade_authority);
+    data.extend_from_slice(&elf);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::ID,
+        ..Account::default()
+    }
+}
+
+/// bincode of UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address }
+fn bincode_program_data(upgrade_authority: Option<Pubkey>) -> Vec<u8> {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    match upgrade_authority {
+        Some(authority) => {
+            data.push(1);
+            data.extend_from_slice(authority.as_ref());
+        }
+        None => data.extend_from_slice(&[0; 33]),
+    }
+    data
+}
+
+/// magicplace from its SBF build, upgradeable by `upgrade_authority`, next to the stand-ins
+pub fn program_test_with_upgrade_authority(upgrade_authority: Option<Pubkey>) -> ProgramTest {
+    let mut test = ProgramTest::default();
+    // bincode of UpgradeableLoaderState::Program { programdata_address }
+    let mut program = 2u32.to_le_bytes().to_vec();
+    program.extend_from_slice(program_data_pda().as_ref());
+    test.add_genesis_account(
+        magicplace::ID,
+        Account {
+            lamports: Rent::default().minimum_balance(program.len()),
+            data: program,
+            owner: bpf_loader_upgradeable::ID,
+            executable: true,
+            ..Account::default()
+        },
+    );
+    test.add_genesis_account(program_data_pda(), program_data_account(upgrade_authority));
+
+    // The stand-ins have no SBF build, so they stay builtins
+    test.prefer_bpf(false);
+    test.add_program("delegation_stand_in", DELEGATION_PROGRAM_ID, processor!(delegation_stand_in));
+    test.add_program("magic_stand_in", MAGIC_PROGRAM_ID, processor!(magic_stand_in));
+    test.add_account(
+        MAGIC_CONTEXT_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(MAGIC_CONTEXT_LEN),
+            data: vec![0; MAGIC_CONTEXT_LEN],
+            owner: MAGIC_PROGRAM_ID,
+            ..Account::default()
+        },
+    );
+    test
+}
+
+pub fn program_test() -> ProgramTest {
+    program_test_with_upgrade_authority(None)
+}
+
+/// A rent-exempt magicplace account holding `value`, padded to `space`
+pub fn program_account<T: AccountSerialize>(value: &T, space: usize) -> Account {
+    program_account_owned_by(value, space, magicplace::ID)
+}
+
+pub fn program_account_owned_by<T: AccountSerialize>(value: &T, space: usize, owner: Pubkey) -> Account {
+    let mut data = Vec::with_capacity(space);
+    value.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    Account {
+        lamports: Rent::default().minimum_balance(space),
+        data,
+        owner,
+        ..Account::default()
+    }
+}
+
+/// A rent-exempt zero-copy PixelShard account
+pub fn zero_copy_shard_account(shard: &PixelShard) -> Account {
+    let mut data = PixelShard::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(shard));
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: magicplace::ID,
+        ..Account::default()
+    }
+}
+
+pub fn funded_account() -> Account {
+    Account {
+        lamports: 10_000_000_000,
+        owner: system_program::ID,
+        ..Account::default()
+    }
+}
+
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn fetch_shard(ctx: &mut ProgramTestContext, address: Pubkey) -> PixelShard {
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(&account.data[..8], PixelShard::DISCRIMINATOR);
+    bytemuck::pod_read_unaligned(&account.data[8..])
+}
+
+async fn transaction(ctx: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    match signers.first() {
+        Some(payer) => Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), signers, blockhash),
+        None => Transaction::new_signed_with_payer(instructions, Some(&ctx.payer.pubkey()), &[&ctx.payer], blockhash),
+    }
+}
+
+/// Wait for a blockhash newer than the bank's latest, so resending an identical transaction
+/// is processed again rather than answered from the status cache
+///
+/// `ProgramTestContext::get_new_latest_blockhash` compares against the context's cached
+/// blockhash, which the bank may already have moved past, so it can hand back the one the
+/// previous send used.
+pub async fn refresh_blockhash(ctx: &mut ProgramTestContext) {
+    let latest = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    ctx.last_blockhash = ctx.banks_client.get_new_latest_blockhash(&latest).await.unwrap();
+}
+
+/// Send a transaction; the first signer pays the fee (the context payer when there are none)
+///
+/// Goes through the same direct path as `send_with_metadata`: `process_transaction` queues on
+/// the banks server's own thread and can report success before that batch releases its account
+/// locks, so a direct send right after it would fail with `AccountInUse`.
+pub async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    send_with_metadata(ctx, instructions, signers).await.result.map_err(BanksClientError::from)
+}
+
+/// Send a transaction and keep its logs, compute units and return data
+pub async fn send_with_metadata(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> BanksTransactionResultWithMetadata {
+    let tx = transaction(ctx, instructions, signers).await;
+    ctx.banks_client.process_transaction_with_metadata(tx).await.unwrap()
+}
+
+/// Send a successful transaction and decode the return data of its last instruction
+pub async fn send_for_return<T: AnchorDeserialize>(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> T {
+    let result = send_with_metadata(ctx, instructions, signers).await;
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.expect("no return data");
+    assert_eq!(return_data.program_id, magicplace::ID);
+    T::try_from_slice(&return_data.data).unwrap()
+}
+
+/// Assert that a transaction failed with the given program error
+pub fn assert_pixel_error(result: Result<(), BanksClientError>, error: magicplace::PixelError) {
+    assert_pixel_error_at(result, 0, error);
+}
+
+/// Assert that the instruction at `index` failed with the given program error
+pub fn assert_pixel_error_at(result: Result<(), BanksClientError>, index: u8, error: magicplace::PixelError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(index, InstructionError::Custom(u32::from(error))),
+    );
+}
+
+/// Assert that a transaction failed with the given Anchor framework error
+pub fn assert_anchor_error(result: Result<(), BanksClientError>, error: anchor_lang::error::ErrorCode) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(u32::from(error))),
+    );
+}
+
+/// The kind and accounts of the last commit the program scheduled with the magic program
+pub async fn last_scheduled_commit(ctx: &mut ProgramTestContext) -> (u8, Vec<Pubkey>) {
+    let account = ctx.banks_client.get_account(MAGIC_CONTEXT_ID).await.unwrap().unwrap();
+    let count = account.data[1] as usize;
+    let keys = account.data[2..]
+        .chunks_exact(32)
+        .take(count)
+        .map(|key| Pubkey::try_from(key).unwrap())
+        .collect();
+    (account.data[0], keys)
+}
+
+/// Emulate the validator cloning a delegated account into the ER: same data, owned by magicplace
+pub async fn clone_into_er(ctx: &mut ProgramTestContext, address: Pubkey) {
+    let mut account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    account.owner = magicplace::ID;
+    ctx.set_account(&address, &account.into());
+}
+
+/// Emulate the validator committing ER state back: same data, owned by the delegation program
+pub async fn commit_to_base(ctx: &mut ProgramTestContext, address: Pubkey) {
+    let mut account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    account.owner = DELEGATION_PROGRAM_ID;
+    ctx.set_account(&address, &account.into());
+}
+
+/// Undelegate `address` on the base layer through the delegation stand-in
+pub fn finalize_undelegation_ix(address: Pubkey, payer: Pubkey, seeds: Vec<Vec<u8>>) -> Instruction {
+    let (buffer, _) = Pubkey::find_program_address(
+        &[UNDELEGATE_BUFFER_TAG, address.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    );
+    let mut data = FINALIZE_UNDELEGATION_TAG.to_vec();
+    seeds.serialize(&mut data).unwrap();
+    Instruction {
+        program_id: DELEGATION_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new(buffer, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(magicplace::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &magicplace::ID).0
+}
+
+pub fn treasury_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &magicplace::ID).0
+}
+
+pub fn shard_pda(shard_x: u16, shard_y: u16) -> Pubkey {
+    Pubkey::find_program_address(&[b"shard", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID).0
+}
+
+pub fn shard_seeds(shard_x: u16, shard_y: u16) -> Vec<Vec<u8>> {
+    vec![b"shard".to_vec(), shard_x.to_le_bytes().to_vec(), shard_y.to_le_bytes().to_vec()]
+}
+
+pub fn deed_pda(shard_x: u16, shard_y: u16) -> Pubkey {
+    Pubkey::find_program_address(&[b"deed", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID).0
+}
+
+pub fn listing_pda(shard_x: u16, shard_y: u16) -> Pubkey {
+    Pubkey::find_program_address(&[b"listing", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID).0
+}
+
+pub fn earnings_pda(shard_x: u16, shard_y: u16) -> Pubkey {
+    Pubkey::find_program_address(&[b"earnings", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID).0
+}
+
+pub fn session_pda(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"session", authority.as_ref()], &magicplace::ID).0
+}
+
+pub fn profile_pda(main_wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"wallet", main_wallet.as_ref()], &magicplace::ID).0
+}
+
+/// Shard preloaded by `World`
+pub const SHARD: (u16, u16) = (1, 2);
+
+/// A running test bank preloaded with the game config, one owned shard and one session
+pub struct World {
+    pub ctx: ProgramTestContext,
+    pub admin: Keypair,
+    pub moderator: Keypair,
+    /// Main wallet owning `SHARD`
+    pub owner: Keypair,
+    /// Session key registered for `owner`
+    pub session_key: Keypair,
+}
+
+impl World {
+    pub async fn new() -> Self {
+        let (admin, moderator, owner, session_key) = (Keypair::new(), Keypair::new(), Keypair::new(), Keypair::new());
+        let (shard_x, shard_y) = SHARD;
+        let bump = |address: Pubkey, seeds: &[&[u8]]| {
+            let (expected, bump) = Pubkey::find_program_address(seeds, &magicplace::ID);
+            assert_eq!(expected, address);
+            bump
+        };
+
+        let mut test = program_test();
+        for key in [&admin, &moderator, &owner, &session_key] {
+            test.add_account(key.pubkey(), funded_account());
+        }
+
+        let config = GameConfig {
+            admin: admin.pubkey(),
+            moderator: moderator.pubkey(),
+            paused: false,
+            params: ConfigParams::default(),
+            bump: bump(config_pda(), &[b"config"]),
+        };
+        test.add_account(config_pda(), program_account(&config, 8 + GameConfig::INIT_SPACE));
+        let treasury = Treasury { bump: bump(treasury_pda(), &[b"treasury"]) };
+        test.add_account(treasury_pda(), program_account(&treasury, 8 + Treasury::INIT_SPACE));
+
+        test.add_account(shard_pda(shard_x, shard_y), shard_account(shard_x, shard_y, owner.pubkey()));
+        test.add_account(deed_pda(shard_x, shard_y), deed_account(shard_x, shard_y, owner.pubkey()));
+
+        let session = SessionAccount {
+            main_address: owner.pubkey(),
+            authority: session_key.pubkey(),
+            auth_nonce: 1,
+            expires_at: 0,
+            revoked: false,
+            bump: bump(session_pda(&session_key.pubkey()), &[b"session", session_key.pubkey().as_ref()]),
+            version: SessionAccount::VERSION,
+        };
+        test.add_account(session_pda(&session_key.pubkey()), program_account(&session, 8 + SessionAccount::INIT_SPACE));
+
+        let profile = WalletProfile {
+            main_address: owner.pubkey(),
+            sessions: vec![session_key.pubkey()],
+            last_auth_nonce: 1,
+            cooldown_counter: 0,
+            last_place_timestamp: 0,
+            bump: bump(profile_pda(&owner.pubkey()), &[b"wallet", owner.pubkey().as_ref()]),
+        };
+        test.add_account(profile_pda(&owner.pubkey()), program_account(&profile, 8 + WalletProfile::INIT_SPACE));
+
+        let ctx = test.start_with_context().await;
+        World { ctx, admin, moderator, owner, session_key }
+    }
+
+    /// Add another blank shard and its deed to the running bank
+    pub fn add_shard(&mut self, shard_x: u16, shard_y: u16, owner: Pubkey) {
+        self.ctx.set_account(&shard_pda(shard_x, shard_y), &shard_account(shard_x, shard_y, owner).into());
+        self.ctx.set_account(&deed_pda(shard_x, shard_y), &deed_account(shard_x, shard_y, owner).into());
+    }
+
+    /// Move the bank's clock to `unix_timestamp`
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    /// Overwrite a shard in the running bank, at the address its coordinates give
+    pub fn set_shard(&mut self, shard: &PixelShard) {
+        let address = shard_pda(shard.shard_x, shard.shard_y);
+        self.ctx.set_account(&address, &zero_copy_shard_account(shard).into());
+    }
+
+    /// Overwrite a magicplace account in the running bank
+    pub fn set<T: AccountSerialize + Space>(&mut self, address: Pubkey, value: &T) {
+        self.ctx.set_account(&address, &program_account(value, 8 + T::INIT_SPACE).into());
+    }
+}
+
+/// A funded session key acting for a fresh main wallet
+pub fn add_painter(world: &mut World) -> (Keypair, Pubkey) {
+    let (session_key, main_wallet) = (Keypair::new(), Pubkey::new_unique());
+    let address = session_pda(&session_key.pubkey());
+    let (_, bump) = Pubkey::find_program_address(&[b"session", session_key.pubkey().as_ref()], &magicplace::ID);
+    world.ctx.set_account(&session_key.pubkey(), &funded_account().into());
+    world.set(
+        address,
+        &SessionAccount {
+            main_address: main_wallet,
+            authority: session_key.pubkey(),
+            auth_nonce: 1,
+            expires_at: 0,
+            revoked: false,
+            bump,
+            version: SessionAccount::VERSION,
+        },
+    );
+    (session_key, main_wallet)
+}
+
+/// A blank shard created by `creator`
+pub fn shard_account(shard_x: u16, shard_y: u16, creator: Pubkey) -> Account {
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"shard", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID);
+    let shard = PixelShard { shard_x, shard_y, creator, bump, version: PixelShard::VERSION, ..bytemuck::Zeroable::zeroed() };
+    zero_copy_shard_account(&shard)
+}
+
+/// A shard in the Borsh layout as first deployed, holding `lamports`
+pub fn baseline_shard_account((shard_x, shard_y): (u16, u16), creator: Pubkey, pixels: &[u8], lamports: u64) -> Account {
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"shard", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID);
+    let shard = BaselinePixelShard { shard_x, shard_y, pixels: pixels.to_vec(), creator, bump };
+    let mut data = PixelShard::DISCRIMINATOR.to_vec();
+    shard.serialize(&mut data).unwrap();
+    Account { lamports, data, owner: magicplace::ID, ..Account::default() }
+}
+
+/// An unlisted, unfrozen deed held by `owner`
+pub fn deed_account(shard_x: u16, shard_y: u16, owner: Pubkey) -> Account {
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"deed", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID);
+    let deed = ShardDeed {
+        shard_x,
+        shard_y,
+        owner,
+        pass_price_lamports: 0,
+        pass_price_tokens: 0,
+        last_activity: 0,
+        frozen: false,
+        bump,
+    };
+    program_account(&deed, 8 + ShardDeed::INIT_SPACE)
+}
+
+pub fn initialize_shard_ix(authority: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::InitializeShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            session: session_pda(&authority),
+            config: config_pda(),
+            treasury: treasury_pda(),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::InitializeShard { shard_x, shard_y }.data(),
+    }
+}
+
+pub fn migrate_shard_ix(payer: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::MigrateShard {
+            shard: shard_pda(shard_x, shard_y),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::MigrateShard { shard_x, shard_y }.data(),
+    }
+}
+
+pub fn migrate_session_ix(main_wallet: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::MigrateSession {
+            session: session_pda(&authority),
+            profile: profile_pda(&main_wallet),
+            main_wallet,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::MigrateSession { authority }.data(),
+    }
+}
+
+/// Accounts for the single-shard painting instructions (place_pixel, fill_rect, ...)
+pub fn place_pixel_accounts(signer: Pubkey, main_wallet: Pubkey, (shard_x, shard_y): (u16, u16)) -> Vec<AccountMeta> {
+    magicplace::accounts::PlacePixel {
+        shard: shard_pda(shard_x, shard_y),
+        deed: deed_pda(shard_x, shard_y),
+        session: session_pda(&signer),
+        profile: profile_pda(&main_wallet),
+        config: config_pda(),
+        pass: None,
+        signer,
+    }
+    .to_account_metas(None)
+}
+
+pub fn place_pixel_ix(signer: Pubkey, main_wallet: Pubkey, shard: (u16, u16), px: u32, py: u32, color: u8) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: place_pixel_accounts(signer, main_wallet, shard),
+        data: magicplace::instruction::PlacePixel { _shard_x: shard.0, _shard_y: shard.1, px, py, color }.data(),
+    }
+}
+
+/// A single-shard painting instruction with the given data
+pub fn paint_ix(signer: Pubkey, main_wallet: Pubkey, shard: (u16, u16), data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: place_pixel_accounts(signer, main_wallet, shard),
+        data: data.data(),
+    }
+}
+
+/// Cross-shard placement with a (shard, deed) pair for each of `shards`
+pub fn place_pixels_cross_shard_ix(
+    signer: Pubkey,
+    main_wallet: Pubkey,
+    shards: &[(u16, u16)],
+    pixels: Vec<GlobalPixel>,
+) -> Instruction {
+    let mut accounts = magicplace::accounts::PlacePixelsCrossShard {
+        session: session_pda(&signer),
+        profile: profile_pda(&main_wallet),
+        config: config_pda(),
+        pass: None,
+        signer,
+    }
+    .to_account_metas(None);
+    for &(shard_x, shard_y) in shards {
+        accounts.push(AccountMeta::new(shard_pda(shard_x, shard_y), false));
+        accounts.push(AccountMeta::new_readonly(deed_pda(shard_x, shard_y), false));
+    }
+    Instruction {
+        program_id: magicplace::ID,
+        accounts,
+        data: magicplace::instruction::PlacePixelsCrossShard { pixels }.data(),
+    }
+}
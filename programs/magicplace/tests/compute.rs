@@ -0,0 +1,70 @@
+//! Compute units and rent for the hot placement paths
+//!
+//! `cargo test --test compute -- --nocapture` prints the units each path consumed on the SBF
+//! build. Nothing is asserted on them: they move with the toolchain and the SDK versions.
+//!
+//! The shard layout is packed, but the earnings fields still make it 21 bytes larger than the
+//! baseline Borsh layout, so rent rises by 146,160 lamports per shard (`Rent::default()`):
+//!
+//! | layout            | bytes | rent       |
+//! |-------------------|-------|------------|
+//! | baseline Borsh    | 8149  | 57,607,920 |
+//! | zero-copy, packed | 8170  | 57,754,080 |
+
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use anchor_lang::prelude::Rent;
+use magicplace::{BulkPixel, PixelRun, PixelShard, BASELINE_SHARD_ACCOUNT_LEN};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Run `ix` and return the compute units it consumed
+async fn units(world: &mut World, signer: &Keypair, ix: Instruction) -> u64 {
+    let result = send_with_metadata(&mut world.ctx, &[ix], &[signer]).await;
+    result.result.unwrap();
+    result.metadata.unwrap().compute_units_consumed
+}
+
+#[tokio::test]
+async fn placement_compute_units() {
+    let mut world = World::new().await;
+    let signer = world.session_key.insecure_clone();
+    let (session_key, owner) = (signer.pubkey(), world.owner.pubkey());
+    let (shard_x, shard_y) = SHARD;
+    let (base_x, base_y) = (shard_x as u32 * 90, shard_y as u32 * 90);
+
+    // (path, consumed)
+    let mut report = Vec::new();
+    let place = place_pixel_ix(session_key, owner, SHARD, base_x, base_y, 3);
+    report.push(("place_pixel", units(&mut world, &signer, place).await));
+
+    let pixels = (0..60).map(|i| BulkPixel { local_x: i, local_y: 1, color: 4 }).collect();
+    let bulk = paint_ix(session_key, owner, SHARD, magicplace::instruction::PlacePixelsBulk { shard_x, shard_y, pixels });
+    report.push(("place_pixels_bulk (60 pixels)", units(&mut world, &signer, bulk).await));
+
+    let runs = (0..10).map(|row| PixelRun { row, start: 0, length: 90, color: 5 }).collect();
+    let runs = paint_ix(session_key, owner, SHARD, magicplace::instruction::PlacePixelRuns { shard_x, shard_y, runs });
+    report.push(("place_pixel_runs (10 rows of 90)", units(&mut world, &signer, runs).await));
+
+    let read = Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::GetPixel { shard: shard_pda(shard_x, shard_y) }.to_account_metas(None),
+        data: magicplace::instruction::GetPixel { shard_x, shard_y, px: base_x, py: base_y }.data(),
+    };
+    report.push(("get_pixel", units(&mut world, &signer, read).await));
+
+    for (name, units) in report {
+        println!("{name}: {units} CU");
+    }
+}
+
+#[test]
+fn zero_copy_shard_rent() {
+    let rent = |len| Rent::default().minimum_balance(len);
+    let zero_copy = 8 + std::mem::size_of::<PixelShard>();
+    assert_eq!((BASELINE_SHARD_ACCOUNT_LEN, zero_copy), (8149, 8170));
+    assert_eq!(rent(BASELINE_SHARD_ACCOUNT_LEN), 57_607_920);
+    assert_eq!(rent(zero_copy), 57_754_080);
+}
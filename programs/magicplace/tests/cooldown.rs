@@ -0,0 +1,255 @@
+//! Cooldown timing, bulk limits, erasing and cooldown passes for non-owners
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    system_program,
+};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::*;
+use magicplace::{
+    BulkPixel, ConfigParams, CooldownPass, ErasePolicy, GameConfig, GlobalPixel, PassPayment, PassScope, PixelError,
+    ShardDeed, WalletProfile,
+};
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+
+/// Shard to the right of `SHARD`, owned by someone else
+const NEIGHBOUR: (u16, u16) = (SHARD.0 + 1, SHARD.1);
+
+/// Clock time the cooldown tests start at
+const NOW: i64 = 1_000_000;
+
+async fn world_with_neighbour() -> World {
+    let mut world = World::new().await;
+    world.add_shard(NEIGHBOUR.0, NEIGHBOUR.1, Pubkey::new_unique());
+    world.warp_to(NOW).await;
+    world
+}
+
+/// `count` pixels along row `row` of a shard
+fn row(row: u8, count: u8, color: u8) -> Vec<BulkPixel> {
+    (0..count).map(|local_x| BulkPixel { local_x, local_y: row, color }).collect()
+}
+
+fn bulk(shard: (u16, u16), pixels: Vec<BulkPixel>) -> magicplace::instruction::PlacePixelsBulk {
+    magicplace::instruction::PlacePixelsBulk { shard_x: shard.0, shard_y: shard.1, pixels }
+}
+
+fn erase_ix(signer: Pubkey, main_wallet: Pubkey, shard: (u16, u16), px: u32, py: u32) -> Instruction {
+    paint_ix(signer, main_wallet, shard, magicplace::instruction::ErasePixel { _shard_x: shard.0, _shard_y: shard.1, px, py })
+}
+
+fn pass_pda(buyer: &Pubkey, scope: PassScope) -> Pubkey {
+    Pubkey::find_program_address(&[b"pass", buyer.as_ref(), &scope.seed()], &magicplace::ID).0
+}
+
+/// Optional accounts for buy_cooldown_pass; anything left `None` is omitted
+#[derive(Default)]
+struct PassAccounts {
+    deed: Option<Pubkey>,
+    shard_owner: Option<Pubkey>,
+    buyer_token_account: Option<Pubkey>,
+    owner_token_account: Option<Pubkey>,
+}
+
+fn buy_pass_ix(buyer: Pubkey, scope: PassScope, payment: PassPayment, optional: PassAccounts) -> Instruction {
+    let token_program = optional.buyer_token_account.map(|_| spl_token::ID);
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::BuyCooldownPass {
+            pass: pass_pda(&buyer, scope),
+            config: config_pda(),
+            treasury: treasury_pda(),
+            deed: optional.deed,
+            shard_owner: optional.shard_owner,
+            buyer_token_account: optional.buyer_token_account,
+            owner_token_account: optional.owner_token_account,
+            token_program,
+            buyer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::BuyCooldownPass { scope, payment }.data(),
+    }
+}
+
+/// A token account of `mint` held by `owner`
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let state = spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: 10_000_000, data, owner: spl_token::ID, ..Account::default() }
+}
+
+#[tokio::test]
+async fn a_spent_burst_cools_down_for_the_configured_period() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let place = |px| place_pixel_ix(session_key.pubkey(), owner, NEIGHBOUR, px, 181, 3);
+
+    // The default burst is 60 pixels and the cooldown 15 seconds
+    let burst = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, bulk(NEIGHBOUR, row(0, 60, 2)));
+    send(&mut world.ctx, &[burst], &[&session_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!((profile.cooldown_counter, profile.last_place_timestamp), (60, NOW as u64));
+
+    assert_pixel_error(send(&mut world.ctx, &[place(180)], &[&session_key]).await, PixelError::Cooldown);
+    world.warp_to(NOW + 14).await;
+    assert_pixel_error(send(&mut world.ctx, &[place(181)], &[&session_key]).await, PixelError::Cooldown);
+
+    // Owners never cool down, even mid-cooldown elsewhere
+    let own = place_pixel_ix(session_key.pubkey(), owner, SHARD, 90, 180, 3);
+    send(&mut world.ctx, &[own], &[&session_key]).await.unwrap();
+
+    world.warp_to(NOW + 15).await;
+    send(&mut world.ctx, &[place(182)], &[&session_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 1);
+}
+
+#[tokio::test]
+async fn bulk_calls_are_capped_by_the_config() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    // The cap applies to owners too
+    let too_many = paint_ix(session_key.pubkey(), owner, SHARD, bulk(SHARD, row(0, 61, 2)));
+    assert_pixel_error(send(&mut world.ctx, &[too_many], &[&session_key]).await, PixelError::BulkTooLarge);
+
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { max_bulk_pixels: 10, ..config.params };
+    world.set(config_pda(), &config);
+    let over = paint_ix(session_key.pubkey(), owner, SHARD, bulk(SHARD, row(1, 11, 2)));
+    assert_pixel_error(send(&mut world.ctx, &[over], &[&session_key]).await, PixelError::BulkTooLarge);
+    let at_cap = paint_ix(session_key.pubkey(), owner, SHARD, bulk(SHARD, row(1, 10, 2)));
+    send(&mut world.ctx, &[at_cap], &[&session_key]).await.unwrap();
+}
+
+#[tokio::test]
+async fn erase_policy_decides_who_may_erase_foreign_pixels() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    let foreign = erase_ix(session_key.pubkey(), owner, NEIGHBOUR, 180, 180);
+    send(&mut world.ctx, &[foreign], &[&session_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 1);
+
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { erase_policy: ErasePolicy::OwnerOnly, ..config.params };
+    world.set(config_pda(), &config);
+    let foreign = erase_ix(session_key.pubkey(), owner, NEIGHBOUR, 181, 180);
+    assert_pixel_error(send(&mut world.ctx, &[foreign], &[&session_key]).await, PixelError::EraseOwnerOnly);
+    let own = erase_ix(session_key.pubkey(), owner, SHARD, 90, 180);
+    send(&mut world.ctx, &[own], &[&session_key]).await.unwrap();
+}
+
+#[tokio::test]
+async fn a_global_pass_skips_the_cooldown() {
+    let mut world = world_with_neighbour().await;
+    let (owner, session_key) = (world.owner.insecure_clone(), world.session_key.insecure_clone());
+    let buy = |payment| buy_pass_ix(owner.pubkey(), PassScope::Global, payment, PassAccounts::default());
+
+    // Not for sale until the admin prices it, and only ever for SOL
+    assert_pixel_error(send(&mut world.ctx, &[buy(PassPayment::Sol)], &[&owner]).await, PixelError::PassNotForSale);
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { global_pass_price_lamports: 1_000_000, ..config.params };
+    world.set(config_pda(), &config);
+    assert_pixel_error(send(&mut world.ctx, &[buy(PassPayment::Token)], &[&owner]).await, PixelError::PassNotForSale);
+
+    refresh_blockhash(&mut world.ctx).await;
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+    send(&mut world.ctx, &[buy(PassPayment::Sol)], &[&owner]).await.unwrap();
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before + 1_000_000);
+    let pass_address = pass_pda(&owner.pubkey(), PassScope::Global);
+    let pass: CooldownPass = fetch(&mut world.ctx, pass_address).await;
+    assert_eq!(pass.valid_until, NOW + 3 * 60 * 60);
+
+    let with_pass = |pixels| Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::PlacePixel {
+            shard: shard_pda(NEIGHBOUR.0, NEIGHBOUR.1),
+            deed: deed_pda(NEIGHBOUR.0, NEIGHBOUR.1),
+            session: session_pda(&session_key.pubkey()),
+            profile: profile_pda(&owner.pubkey()),
+            config: config_pda(),
+            pass: Some(pass_address),
+            signer: session_key.pubkey(),
+        }
+        .to_account_metas(None),
+        data: bulk(NEIGHBOUR, pixels).data(),
+    };
+    send(&mut world.ctx, &[with_pass(row(0, 60, 2)), with_pass(row(1, 60, 2))], &[&session_key]).await.unwrap();
+    let mut cross_shard = place_pixels_cross_shard_ix(
+        session_key.pubkey(),
+        owner.pubkey(),
+        &[NEIGHBOUR],
+        vec![GlobalPixel { px: NEIGHBOUR.0 as u32 * 90, py: NEIGHBOUR.1 as u32 * 90 + 2, color: 2 }],
+    );
+    cross_shard.accounts[3] = AccountMeta::new_readonly(pass_address, false);
+    send(&mut world.ctx, &[cross_shard], &[&session_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner.pubkey())).await;
+    assert_eq!(profile.cooldown_counter, 0);
+    // Paint under a pass earns the shard owner nothing
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!({ shard.foreign_pixels }, 0);
+
+    // Once it lapses the usual rules apply again
+    world.warp_to(NOW + 3 * 60 * 60).await;
+    send(&mut world.ctx, &[with_pass(row(2, 60, 2))], &[&session_key]).await.unwrap();
+    let result = send(&mut world.ctx, &[with_pass(row(3, 1, 2))], &[&session_key]).await;
+    assert_pixel_error(result, PixelError::Cooldown);
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!({ shard.foreign_pixels }, 60);
+}
+
+#[tokio::test]
+async fn shard_passes_need_a_price_and_the_payment_accounts() {
+    let mut world = world_with_neighbour().await;
+    let buyer = world.owner.insecure_clone();
+    let scope = PassScope::Shard { shard_x: NEIGHBOUR.0, shard_y: NEIGHBOUR.1 };
+    let deed_address = deed_pda(NEIGHBOUR.0, NEIGHBOUR.1);
+    let mut deed: ShardDeed = fetch(&mut world.ctx, deed_address).await;
+    let with_deed = || PassAccounts { deed: Some(deed_address), ..PassAccounts::default() };
+
+    let no_deed = buy_pass_ix(buyer.pubkey(), scope, PassPayment::Sol, PassAccounts::default());
+    assert_pixel_error(send(&mut world.ctx, &[no_deed], &[&buyer]).await, PixelError::MissingPassAccount);
+    let accounts = PassAccounts { shard_owner: Some(deed.owner), ..with_deed() };
+    let unpriced = buy_pass_ix(buyer.pubkey(), scope, PassPayment::Token, accounts);
+    assert_pixel_error(send(&mut world.ctx, &[unpriced], &[&buyer]).await, PixelError::PassNotForSale);
+
+    deed.pass_price_lamports = 1_000_000;
+    deed.pass_price_tokens = 5;
+    world.set(deed_address, &deed);
+    let no_owner = buy_pass_ix(buyer.pubkey(), scope, PassPayment::Sol, with_deed());
+    assert_pixel_error(send(&mut world.ctx, &[no_owner], &[&buyer]).await, PixelError::MissingPassAccount);
+    let no_token_accounts = buy_pass_ix(buyer.pubkey(), scope, PassPayment::Token, with_deed());
+    assert_pixel_error(send(&mut world.ctx, &[no_token_accounts], &[&buyer]).await, PixelError::MissingPassAccount);
+
+    // Token passes are paid in the project token only
+    let other_mint = Pubkey::new_unique();
+    let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+    world.ctx.set_account(&from, &token_account(other_mint, buyer.pubkey(), 10).into());
+    world.ctx.set_account(&to, &token_account(other_mint, deed.owner, 0).into());
+    let accounts = PassAccounts { buyer_token_account: Some(from), owner_token_account: Some(to), ..with_deed() };
+    let wrong_mint = buy_pass_ix(buyer.pubkey(), scope, PassPayment::Token, accounts);
+    assert_pixel_error(send(&mut world.ctx, &[wrong_mint], &[&buyer]).await, PixelError::InvalidPassMint);
+
+    let accounts = PassAccounts { shard_owner: Some(deed.owner), ..with_deed() };
+    send(&mut world.ctx, &[buy_pass_ix(buyer.pubkey(), scope, PassPayment::Sol, accounts)], &[&buyer]).await.unwrap();
+    assert_eq!(world.ctx.banks_client.get_balance(deed.owner).await.unwrap(), 1_000_000);
+}
@@ -0,0 +1,436 @@
+//! Delegation round trips against the local stand-ins of the delegation and magic programs
+
+mod common;
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    system_program,
+};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+use ephemeral_rollups_sdk::pda::{DELEGATE_BUFFER_TAG, DELEGATION_METADATA_TAG, DELEGATION_RECORD_TAG};
+use magicplace::{PixelError, PixelShard, SessionAccount, ShardCoord, WalletProfile, BASELINE_SHARD_ACCOUNT_LEN};
+use solana_sdk::signature::Signer;
+
+fn delegation_pdas(pda: &Pubkey) -> (Pubkey, Pubkey, Pubkey) {
+    (
+        Pubkey::find_program_address(&[DELEGATE_BUFFER_TAG, pda.as_ref()], &magicplace::ID).0,
+        Pubkey::find_program_address(&[DELEGATION_RECORD_TAG, pda.as_ref()], &DELEGATION_PROGRAM_ID).0,
+        Pubkey::find_program_address(&[DELEGATION_METADATA_TAG, pda.as_ref()], &DELEGATION_PROGRAM_ID).0,
+    )
+}
+
+fn delegate_shard_ix(authority: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    let pda = shard_pda(shard_x, shard_y);
+    let (buffer_pda, delegation_record_pda, delegation_metadata_pda) = delegation_pdas(&pda);
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::DelegateShard {
+            authority,
+            buffer_pda,
+            delegation_record_pda,
+            delegation_metadata_pda,
+            pda,
+            owner_program: magicplace::ID,
+            delegation_program: DELEGATION_PROGRAM_ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::DelegateShard { shard_x, shard_y }.data(),
+    }
+}
+
+fn delegate_user_ix(authority: Pubkey, main_wallet: Pubkey) -> Instruction {
+    let pda = session_pda(&authority);
+    let (buffer_pda, delegation_record_pda, delegation_metadata_pda) = delegation_pdas(&pda);
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::DelegateUser {
+            user: pda,
+            authority,
+            buffer_pda,
+            delegation_record_pda,
+            delegation_metadata_pda,
+            pda,
+            owner_program: magicplace::ID,
+            delegation_program: DELEGATION_PROGRAM_ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::DelegateUser { main_wallet }.data(),
+    }
+}
+
+fn delegate_profile_ix(authority: Pubkey, main_wallet: Pubkey) -> Instruction {
+    let pda = profile_pda(&main_wallet);
+    let (buffer_pda, delegation_record_pda, delegation_metadata_pda) = delegation_pdas(&pda);
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::DelegateProfile {
+            authority,
+            buffer_pda,
+            delegation_record_pda,
+            delegation_metadata_pda,
+            pda,
+            owner_program: magicplace::ID,
+            delegation_program: DELEGATION_PROGRAM_ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::DelegateProfile { main_wallet }.data(),
+    }
+}
+
+fn undelegate_shard_ix(owner: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UndelegateShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            owner,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::UndelegateShard { shard_x, shard_y }.data(),
+    }
+}
+
+fn commit_and_undelegate_shard_ix(moderator: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::CommitAndUndelegateShard {
+            shard: shard_pda(shard_x, shard_y),
+            config: config_pda(),
+            moderator,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::CommitAndUndelegateShard { shard_x, shard_y }.data(),
+    }
+}
+
+fn undelegate_baseline_shard_ix(payer: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UndelegateBaselineShard {
+            shard: shard_pda(shard_x, shard_y),
+            payer,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::UndelegateBaselineShard { shard_x, shard_y }.data(),
+    }
+}
+
+fn undelegate_user_ix(authority: Pubkey, main_wallet: Pubkey, include_profile: bool) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UndelegateUser {
+            session: session_pda(&authority),
+            profile: profile_pda(&main_wallet),
+            authority,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::UndelegateUser { include_profile }.data(),
+    }
+}
+
+fn undelegate_profile_ix(main_wallet: Pubkey, profile: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UndelegateProfile {
+            profile,
+            main_wallet,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::UndelegateProfile {}.data(),
+    }
+}
+
+fn commit_shards_ix(payer: Pubkey, shards: &[(u16, u16)], accounts: &[Pubkey]) -> Instruction {
+    let mut metas = magicplace::accounts::CommitShards {
+        payer,
+        magic_program: MAGIC_PROGRAM_ID,
+        magic_context: MAGIC_CONTEXT_ID,
+    }
+    .to_account_metas(None);
+    metas.extend(accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    let shards = shards
+        .iter()
+        .map(|&(shard_x, shard_y)| ShardCoord { shard_x, shard_y })
+        .collect();
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: metas,
+        data: magicplace::instruction::CommitShards { shards }.data(),
+    }
+}
+
+async fn owner_of(world: &mut World, address: Pubkey) -> Pubkey {
+    world.ctx.banks_client.get_account(address).await.unwrap().unwrap().owner
+}
+
+#[tokio::test]
+async fn shard_round_trips_through_delegation() {
+    let mut world = World::new().await;
+    let shard = shard_pda(SHARD.0, SHARD.1);
+    let owner = world.owner.insecure_clone();
+    let session_key = world.session_key.insecure_clone();
+
+    send(&mut world.ctx, &[delegate_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await.unwrap();
+    assert_eq!(owner_of(&mut world, shard).await, DELEGATION_PROGRAM_ID);
+    let delegated = fetch_shard(&mut world.ctx, shard).await;
+    assert_eq!((delegated.shard_x, delegated.shard_y), SHARD);
+
+    // Paint on the ER, then hand the shard back
+    clone_into_er(&mut world.ctx, shard).await;
+    let paint = place_pixel_ix(session_key.pubkey(), owner.pubkey(), SHARD, 95, 185, 7);
+    send(&mut world.ctx, &[paint], &[&session_key]).await.unwrap();
+    send(&mut world.ctx, &[undelegate_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await.unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![shard]));
+
+    commit_to_base(&mut world.ctx, shard).await;
+    let finalize = finalize_undelegation_ix(shard, owner.pubkey(), shard_seeds(SHARD.0, SHARD.1));
+    send(&mut world.ctx, &[finalize], &[&owner]).await.unwrap();
+
+    assert_eq!(owner_of(&mut world, shard).await, magicplace::ID);
+    let returned = fetch_shard(&mut world.ctx, shard).await;
+    assert_eq!(returned.pixels[5 * 90 + 5], 7);
+    assert_eq!(returned.creator, owner.pubkey());
+}
+
+#[tokio::test]
+async fn delegated_baseline_shards_come_back_to_be_migrated() {
+    let mut world = World::new().await;
+    let shard = shard_pda(SHARD.0, SHARD.1);
+    let (owner, payer) = (world.owner.insecure_clone(), world.session_key.insecure_clone());
+    let mut pixels = vec![0u8; 90 * 90];
+    pixels[42] = 6;
+    let lamports = Rent::default().minimum_balance(BASELINE_SHARD_ACCOUNT_LEN);
+    let mut baseline = baseline_shard_account(SHARD, owner.pubkey(), &pixels, lamports);
+    baseline.owner = DELEGATION_PROGRAM_ID;
+    world.ctx.set_account(&shard, &baseline.into());
+
+    let result = send(&mut world.ctx, &[migrate_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await;
+    assert_pixel_error(result, PixelError::InvalidShardAccount);
+
+    // The zero-copy paths can't read the baseline layout
+    clone_into_er(&mut world.ctx, shard).await;
+    let result = send(&mut world.ctx, &[undelegate_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await;
+    assert_pixel_error(result, PixelError::ShardNeedsMigration);
+
+    send(&mut world.ctx, &[undelegate_baseline_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await.unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![shard]));
+
+    commit_to_base(&mut world.ctx, shard).await;
+    let finalize = finalize_undelegation_ix(shard, payer.pubkey(), shard_seeds(SHARD.0, SHARD.1));
+    send(&mut world.ctx, &[finalize], &[&payer]).await.unwrap();
+    assert_eq!(owner_of(&mut world, shard).await, magicplace::ID);
+
+    refresh_blockhash(&mut world.ctx).await;
+    send(&mut world.ctx, &[migrate_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await.unwrap();
+    let migrated = fetch_shard(&mut world.ctx, shard).await;
+    assert_eq!((migrated.pixels[42], migrated.creator, migrated.version), (6, owner.pubkey(), PixelShard::VERSION));
+}
+
+#[tokio::test]
+async fn undelegate_baseline_shard_rejects_current_shards() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+
+    let result = send(&mut world.ctx, &[undelegate_baseline_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await;
+    assert_pixel_error(result, PixelError::NotBaselineShard);
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (0, vec![]));
+}
+
+#[tokio::test]
+async fn undelegate_shard_requires_the_deed_owner() {
+    // Preloaded accounts are owned by magicplace, which is how the ER sees a delegated shard
+    let mut world = World::new().await;
+    let stranger = world.session_key.insecure_clone();
+
+    let result = send(&mut world.ctx, &[undelegate_shard_ix(stranger.pubkey(), SHARD)], &[&stranger]).await;
+    assert_pixel_error(result, PixelError::NotShardOwner);
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (0, vec![]));
+}
+
+#[tokio::test]
+async fn commit_and_undelegate_shard_requires_the_moderator() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+
+    let result = send(&mut world.ctx, &[commit_and_undelegate_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await;
+    assert_pixel_error(result, PixelError::NotModerator);
+}
+
+#[tokio::test]
+async fn undelegate_user_requires_the_session_key() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let session_key = world.session_key.pubkey();
+
+    // The main wallet cannot undelegate a session on its behalf
+    let mut ix = undelegate_user_ix(session_key, owner.pubkey(), true);
+    ix.accounts[2] = AccountMeta::new(owner.pubkey(), true);
+    let result = send(&mut world.ctx, &[ix], &[&owner]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+}
+
+#[tokio::test]
+async fn undelegate_user_rejects_revoked_and_unregistered_keys() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let session_key = world.session_key.insecure_clone();
+    let (session, profile) = (session_pda(&session_key.pubkey()), profile_pda(&owner.pubkey()));
+
+    // A key dropped from the profile may hand back its own session, but not the shared profile
+    let mut wallet: WalletProfile = fetch(&mut world.ctx, profile).await;
+    wallet.sessions.retain(|key| *key != session_key.pubkey());
+    world.set(profile, &wallet);
+    let result = send(&mut world.ctx, &[undelegate_user_ix(session_key.pubkey(), owner.pubkey(), true)], &[&session_key]).await;
+    assert_pixel_error(result, PixelError::InvalidAuth);
+    send(&mut world.ctx, &[undelegate_user_ix(session_key.pubkey(), owner.pubkey(), false)], &[&session_key])
+        .await
+        .unwrap();
+
+    // A revoked key can do neither
+    let mut revoked: SessionAccount = fetch(&mut world.ctx, session).await;
+    revoked.revoked = true;
+    world.set(session, &revoked);
+    for include_profile in [false, true] {
+        refresh_blockhash(&mut world.ctx).await;
+        let ix = undelegate_user_ix(session_key.pubkey(), owner.pubkey(), include_profile);
+        assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::SessionRevoked);
+    }
+}
+
+#[tokio::test]
+async fn moderator_and_admin_can_commit_and_undelegate_any_shard() {
+    let mut world = World::new().await;
+    let shard = shard_pda(SHARD.0, SHARD.1);
+    let moderator = world.moderator.insecure_clone();
+    let admin = world.admin.insecure_clone();
+
+    for signer in [&moderator, &admin] {
+        send(&mut world.ctx, &[commit_and_undelegate_shard_ix(signer.pubkey(), SHARD)], &[signer]).await.unwrap();
+        assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![shard]));
+    }
+}
+
+#[tokio::test]
+async fn session_and_profile_round_trip_through_delegation() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let session_key = world.session_key.insecure_clone();
+    let session = session_pda(&session_key.pubkey());
+    let profile = profile_pda(&owner.pubkey());
+
+    let delegate = [
+        delegate_user_ix(session_key.pubkey(), owner.pubkey()),
+        delegate_profile_ix(session_key.pubkey(), owner.pubkey()),
+    ];
+    send(&mut world.ctx, &delegate, &[&session_key]).await.unwrap();
+    assert_eq!(owner_of(&mut world, session).await, DELEGATION_PROGRAM_ID);
+    assert_eq!(owner_of(&mut world, profile).await, DELEGATION_PROGRAM_ID);
+
+    clone_into_er(&mut world.ctx, session).await;
+    clone_into_er(&mut world.ctx, profile).await;
+
+    // Without the profile only the session is handed back
+    send(&mut world.ctx, &[undelegate_user_ix(session_key.pubkey(), owner.pubkey(), false)], &[&session_key])
+        .await
+        .unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![session]));
+
+    send(&mut world.ctx, &[undelegate_user_ix(session_key.pubkey(), owner.pubkey(), true)], &[&session_key])
+        .await
+        .unwrap();
+    assert_eq!(
+        last_scheduled_commit(&mut world.ctx).await,
+        (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![session, profile]),
+    );
+
+    for (address, seeds) in [
+        (session, vec![b"session".to_vec(), session_key.pubkey().to_bytes().to_vec()]),
+        (profile, vec![b"wallet".to_vec(), owner.pubkey().to_bytes().to_vec()]),
+    ] {
+        commit_to_base(&mut world.ctx, address).await;
+        let finalize = finalize_undelegation_ix(address, session_key.pubkey(), seeds);
+        send(&mut world.ctx, &[finalize], &[&session_key]).await.unwrap();
+        assert_eq!(owner_of(&mut world, address).await, magicplace::ID);
+    }
+
+    let session_account: SessionAccount = fetch(&mut world.ctx, session).await;
+    assert_eq!(session_account.main_address, owner.pubkey());
+    let profile_account: WalletProfile = fetch(&mut world.ctx, profile).await;
+    assert_eq!(profile_account.sessions, vec![session_key.pubkey()]);
+}
+
+#[tokio::test]
+async fn main_wallet_brings_its_profile_back_to_register_sessions() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let session_key = world.session_key.insecure_clone();
+    let profile = profile_pda(&owner.pubkey());
+
+    send(&mut world.ctx, &[delegate_profile_ix(session_key.pubkey(), owner.pubkey())], &[&session_key])
+        .await
+        .unwrap();
+    clone_into_er(&mut world.ctx, profile).await;
+
+    // Only the wallet the profile belongs to can hand it back
+    let result = send(&mut world.ctx, &[undelegate_profile_ix(session_key.pubkey(), profile)], &[&session_key]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+
+    send(&mut world.ctx, &[undelegate_profile_ix(owner.pubkey(), profile)], &[&owner]).await.unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![profile]));
+
+    commit_to_base(&mut world.ctx, profile).await;
+    let finalize = finalize_undelegation_ix(profile, owner.pubkey(), vec![b"wallet".to_vec(), owner.pubkey().to_bytes().to_vec()]);
+    send(&mut world.ctx, &[finalize], &[&owner]).await.unwrap();
+    assert_eq!(owner_of(&mut world, profile).await, magicplace::ID);
+    let profile_account: WalletProfile = fetch(&mut world.ctx, profile).await;
+    assert_eq!(profile_account.sessions, vec![session_key.pubkey()]);
+}
+
+#[tokio::test]
+async fn commit_shards_validates_every_account() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+    let shard = shard_pda(SHARD.0, SHARD.1);
+
+    let empty = commit_shards_ix(payer.pubkey(), &[], &[]);
+    assert_pixel_error(send(&mut world.ctx, &[empty], &[&payer]).await, PixelError::EmptyShardList);
+
+    let missing_account = commit_shards_ix(payer.pubkey(), &[SHARD], &[]);
+    assert_pixel_error(send(&mut world.ctx, &[missing_account], &[&payer]).await, PixelError::InvalidShardAccount);
+
+    let wrong_coords = commit_shards_ix(payer.pubkey(), &[(SHARD.0 + 1, SHARD.1)], &[shard]);
+    assert_pixel_error(send(&mut world.ctx, &[wrong_coords], &[&payer]).await, PixelError::InvalidShardAccount);
+
+    let duplicate = commit_shards_ix(payer.pubkey(), &[SHARD, SHARD], &[shard, shard]);
+    assert_pixel_error(send(&mut world.ctx, &[duplicate], &[&payer]).await, PixelError::DuplicateShard);
+
+    let not_a_shard = commit_shards_ix(payer.pubkey(), &[SHARD], &[deed_pda(SHARD.0, SHARD.1)]);
+    assert_anchor_error(send(&mut world.ctx, &[not_a_shard], &[&payer]).await, ErrorCode::AccountDiscriminatorMismatch);
+}
+
+#[tokio::test]
+async fn commit_shards_schedules_one_commit() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+    let shard = shard_pda(SHARD.0, SHARD.1);
+
+    send(&mut world.ctx, &[commit_shards_ix(payer.pubkey(), &[SHARD], &[shard])], &[&payer]).await.unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT, vec![shard]));
+}
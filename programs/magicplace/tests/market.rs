@@ -0,0 +1,332 @@
+//! Shard ownership changes: transfers, sales, earnings claims and reclaiming abandoned shards
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{instruction::Instruction, program_option::COption, program_pack::Pack, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use common::*;
+use magicplace::{ConfigParams, GameConfig, PixelError, ShardDeed, ShardEarnings};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Shard to the right of `SHARD`, owned by someone else
+const NEIGHBOUR: (u16, u16) = (SHARD.0 + 1, SHARD.1);
+
+/// Clock time the market tests run at
+const NOW: i64 = 1_000_000;
+
+/// `owner_token_account` and the accounts minting into it are only needed when earnings are owed
+fn transfer_shard_ix(
+    owner: Pubkey,
+    (shard_x, shard_y): (u16, u16),
+    new_owner: Pubkey,
+    owner_token_account: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::TransferShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            listing: listing_pda(shard_x, shard_y),
+            earnings: earnings_pda(shard_x, shard_y),
+            config: config_pda(),
+            mint: owner_token_account.map(|_| mint_pda()),
+            owner_token_account,
+            owner,
+            token_program: owner_token_account.map(|_| spl_token::ID),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::TransferShard { shard_x, shard_y, new_owner }.data(),
+    }
+}
+
+fn list_shard_ix(owner: Pubkey, (shard_x, shard_y): (u16, u16), price: u64) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::ListShard {
+            deed: deed_pda(shard_x, shard_y),
+            listing: listing_pda(shard_x, shard_y),
+            owner,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::ListShard { shard_x, shard_y, price }.data(),
+    }
+}
+
+fn buy_shard_ix(
+    buyer: Pubkey,
+    seller: Pubkey,
+    (shard_x, shard_y): (u16, u16),
+    max_price: u64,
+    seller_token_account: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::BuyShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            listing: listing_pda(shard_x, shard_y),
+            seller,
+            earnings: earnings_pda(shard_x, shard_y),
+            config: config_pda(),
+            mint: seller_token_account.map(|_| mint_pda()),
+            seller_token_account,
+            buyer,
+            token_program: seller_token_account.map(|_| spl_token::ID),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::BuyShard { shard_x, shard_y, max_price }.data(),
+    }
+}
+
+fn mint_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"mint"], &magicplace::ID).0
+}
+
+fn claim_earnings_ix(owner: Pubkey, (shard_x, shard_y): (u16, u16), owner_token_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::ClaimEarnings {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            earnings: earnings_pda(shard_x, shard_y),
+            config: config_pda(),
+            mint: mint_pda(),
+            owner_token_account,
+            owner,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::ClaimEarnings { shard_x, shard_y }.data(),
+    }
+}
+
+fn reclaim_shard_ix(
+    authority: Pubkey,
+    (shard_x, shard_y): (u16, u16),
+    previous_owner_token_account: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::ReclaimShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            listing: listing_pda(shard_x, shard_y),
+            session: session_pda(&authority),
+            treasury: treasury_pda(),
+            earnings: earnings_pda(shard_x, shard_y),
+            config: config_pda(),
+            mint: previous_owner_token_account.map(|_| mint_pda()),
+            previous_owner_token_account,
+            authority,
+            token_program: previous_owner_token_account.map(|_| spl_token::ID),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::ReclaimShard { shard_x, shard_y }.data(),
+    }
+}
+
+/// A packed SPL token account or mint owned by the token program
+fn token_program_account<T: Pack>(state: T) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: 10_000_000, data, owner: spl_token::ID, ..Account::default() }
+}
+
+/// Where `owner`'s project tokens go in these tests; `add_token_account` creates it
+fn token_account_of(owner: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"tokens", owner.as_ref()], &spl_token::ID).0
+}
+
+/// A world whose project token mint exists, so ownership changes can pay out earnings
+async fn market_world() -> World {
+    let mut world = World::new().await;
+    let mint = spl_token::state::Mint {
+        mint_authority: COption::Some(config_pda()),
+        decimals: 6,
+        is_initialized: true,
+        ..Default::default()
+    };
+    world.ctx.set_account(&mint_pda(), &token_program_account(mint).into());
+    let owner = world.owner.pubkey();
+    add_token_account(&mut world, owner);
+    world
+}
+
+fn add_token_account(world: &mut World, owner: Pubkey) {
+    let state = spl_token::state::Account {
+        mint: mint_pda(),
+        owner,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    world.ctx.set_account(&token_account_of(owner), &token_program_account(state).into());
+}
+
+async fn token_balance(world: &mut World, owner: Pubkey) -> u64 {
+    let account = world.ctx.banks_client.get_account(token_account_of(owner)).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+#[tokio::test]
+async fn transfers_need_a_new_owner() {
+    let mut world = market_world().await;
+    let owner = world.owner.insecure_clone();
+
+    let nobody = transfer_shard_ix(owner.pubkey(), SHARD, Pubkey::default(), None);
+    assert_pixel_error(send(&mut world.ctx, &[nobody], &[&owner]).await, PixelError::InvalidAuth);
+
+    // Nothing is owed, so no token accounts are needed
+    let new_owner = Pubkey::new_unique();
+    send(&mut world.ctx, &[transfer_shard_ix(owner.pubkey(), SHARD, new_owner, None)], &[&owner]).await.unwrap();
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(deed.owner, new_owner);
+}
+
+#[tokio::test]
+async fn sales_need_a_price_within_the_buyers_maximum() {
+    let mut world = market_world().await;
+    let owner = world.owner.insecure_clone();
+    let buyer = Keypair::new();
+    world.ctx.set_account(&buyer.pubkey(), &funded_account().into());
+
+    let free = list_shard_ix(owner.pubkey(), SHARD, 0);
+    assert_pixel_error(send(&mut world.ctx, &[free], &[&owner]).await, PixelError::InvalidPrice);
+    send(&mut world.ctx, &[list_shard_ix(owner.pubkey(), SHARD, 2_000_000_000)], &[&owner]).await.unwrap();
+    let seller_before = world.ctx.banks_client.get_balance(owner.pubkey()).await.unwrap();
+    let listing_rent = world.ctx.banks_client.get_balance(listing_pda(SHARD.0, SHARD.1)).await.unwrap();
+
+    // The seller raised the price after the buyer looked
+    let stale = buy_shard_ix(buyer.pubkey(), owner.pubkey(), SHARD, 1_000_000_000, None);
+    assert_pixel_error(send(&mut world.ctx, &[stale], &[&buyer]).await, PixelError::ListingPriceChanged);
+
+    let buy = buy_shard_ix(buyer.pubkey(), owner.pubkey(), SHARD, 2_000_000_000, None);
+    send(&mut world.ctx, &[buy], &[&buyer]).await.unwrap();
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(deed.owner, buyer.pubkey());
+    assert_eq!(
+        world.ctx.banks_client.get_balance(owner.pubkey()).await.unwrap(),
+        seller_before + 2_000_000_000 + listing_rent,
+    );
+}
+
+#[tokio::test]
+async fn earnings_are_paid_in_whole_tokens() {
+    let mut world = market_world().await;
+    let owner = world.owner.insecure_clone();
+    let token_account = token_account_of(owner.pubkey());
+
+    // Ten foreign pixels earn one token
+    let mut shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    shard.foreign_pixels = 9;
+    world.set_shard(&shard);
+    let claim = || claim_earnings_ix(owner.pubkey(), SHARD, token_account);
+    assert_pixel_error(send(&mut world.ctx, &[claim()], &[&owner]).await, PixelError::NothingToClaim);
+
+    shard.foreign_pixels = 25;
+    world.set_shard(&shard);
+    refresh_blockhash(&mut world.ctx).await;
+    send(&mut world.ctx, &[claim()], &[&owner]).await.unwrap();
+    assert_eq!(token_balance(&mut world, owner.pubkey()).await, 2_000_000);
+    let earnings: ShardEarnings = fetch(&mut world.ctx, earnings_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(earnings.claimed_pixels, 20);
+
+    // The five pixels left over wait for the next claim
+    refresh_blockhash(&mut world.ctx).await;
+    assert_pixel_error(send(&mut world.ctx, &[claim()], &[&owner]).await, PixelError::NothingToClaim);
+
+    // More tokens than fit in base units are an overflow, not an empty claim
+    shard.foreign_pixels = u64::MAX;
+    world.set_shard(&shard);
+    refresh_blockhash(&mut world.ctx).await;
+    assert_pixel_error(send(&mut world.ctx, &[claim()], &[&owner]).await, PixelError::EarningsOverflow);
+}
+
+#[tokio::test]
+async fn only_abandoned_shards_can_be_reclaimed() {
+    let mut world = market_world().await;
+    let session_key = world.session_key.insecure_clone();
+    let abandoner = Pubkey::new_unique();
+    world.add_shard(NEIGHBOUR.0, NEIGHBOUR.1, abandoner);
+    add_token_account(&mut world, abandoner);
+    world.warp_to(NOW).await;
+
+    let reclaim = || reclaim_shard_ix(session_key.pubkey(), NEIGHBOUR, Some(token_account_of(abandoner)));
+    assert_pixel_error(send(&mut world.ctx, &[reclaim()], &[&session_key]).await, PixelError::ReclaimDisabled);
+
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { reclaim_after_secs: 3600, reclaim_fee_lamports: 1_000_000, ..config.params };
+    world.set(config_pda(), &config);
+    let mut deed: ShardDeed = fetch(&mut world.ctx, deed_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    deed.last_activity = NOW - 1000;
+    world.set(deed_pda(NEIGHBOUR.0, NEIGHBOUR.1), &deed);
+    let mut shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    shard.foreign_pixels = 30;
+    world.set_shard(&shard);
+    refresh_blockhash(&mut world.ctx).await;
+    assert_pixel_error(send(&mut world.ctx, &[reclaim()], &[&session_key]).await, PixelError::ShardStillActive);
+
+    // Idle long enough, but already ours
+    let own = reclaim_shard_ix(session_key.pubkey(), SHARD, None);
+    assert_pixel_error(send(&mut world.ctx, &[own], &[&session_key]).await, PixelError::InvalidAuth);
+
+    world.warp_to(NOW + 2600).await;
+    refresh_blockhash(&mut world.ctx).await;
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+    // Earnings are owed, so the previous owner's token account has to come along
+    let unpaid = reclaim_shard_ix(session_key.pubkey(), NEIGHBOUR, None);
+    assert_pixel_error(send(&mut world.ctx, &[unpaid], &[&session_key]).await, PixelError::MissingEarningsAccount);
+    send(&mut world.ctx, &[reclaim()], &[&session_key]).await.unwrap();
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!((deed.owner, deed.last_activity), (world.owner.pubkey(), NOW + 2600));
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before + 1_000_000);
+    // The earnings the abandoned shard had accrued still reach its previous owner
+    assert_eq!(token_balance(&mut world, abandoner).await, 3_000_000);
+}
+
+#[tokio::test]
+async fn ownership_changes_pay_out_earnings_first() {
+    let mut world = market_world().await;
+    let owner = world.owner.insecure_clone();
+    let heir = Keypair::new();
+    world.ctx.set_account(&heir.pubkey(), &funded_account().into());
+    add_token_account(&mut world, heir.pubkey());
+    let mut shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    shard.foreign_pixels = 25;
+    world.set_shard(&shard);
+
+    // The giver keeps what was earned on their watch; the partial token is dropped
+    let unpaid = transfer_shard_ix(owner.pubkey(), SHARD, heir.pubkey(), None);
+    assert_pixel_error(send(&mut world.ctx, &[unpaid], &[&owner]).await, PixelError::MissingEarningsAccount);
+    let transfer = transfer_shard_ix(owner.pubkey(), SHARD, heir.pubkey(), Some(token_account_of(owner.pubkey())));
+    send(&mut world.ctx, &[transfer], &[&owner]).await.unwrap();
+    assert_eq!(token_balance(&mut world, owner.pubkey()).await, 2_000_000);
+    let earnings: ShardEarnings = fetch(&mut world.ctx, earnings_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(earnings.claimed_pixels, 25);
+    let claim = claim_earnings_ix(heir.pubkey(), SHARD, token_account_of(heir.pubkey()));
+    assert_pixel_error(send(&mut world.ctx, &[claim], &[&heir]).await, PixelError::NothingToClaim);
+
+    // A sale pays the seller the same way
+    shard.foreign_pixels = 37;
+    world.set_shard(&shard);
+    send(&mut world.ctx, &[list_shard_ix(heir.pubkey(), SHARD, 1_000)], &[&heir]).await.unwrap();
+    let unpaid = buy_shard_ix(owner.pubkey(), heir.pubkey(), SHARD, 1_000, None);
+    assert_pixel_error(send(&mut world.ctx, &[unpaid], &[&owner]).await, PixelError::MissingEarningsAccount);
+    let buy = buy_shard_ix(owner.pubkey(), heir.pubkey(), SHARD, 1_000, Some(token_account_of(heir.pubkey())));
+    send(&mut world.ctx, &[buy], &[&owner]).await.unwrap();
+    assert_eq!(token_balance(&mut world, heir.pubkey()).await, 1_000_000);
+    let earnings: ShardEarnings = fetch(&mut world.ctx, earnings_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(earnings.claimed_pixels, 37);
+
+    // Only the owner's own token account can receive the payout
+    let to_someone_else = transfer_shard_ix(owner.pubkey(), SHARD, heir.pubkey(), Some(token_account_of(heir.pubkey())));
+    let result = send(&mut world.ctx, &[to_someone_else], &[&owner]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::ConstraintTokenOwner);
+}
@@ -0,0 +1,156 @@
+//! Account layout versions and in-place upgrades
+
+mod common;
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AnchorSerialize, Discriminator, InstructionData, Space, ToAccountMetas};
+use common::*;
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+use magicplace::{BaselineSessionAccount, PixelError, SessionAccount, WalletProfile, BASELINE_SESSION_ACCOUNT_LEN};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// A session for `authority` on behalf of `main_address` in the layout first deployed, owned by `owner`
+fn baseline_session_account(authority: Pubkey, main_address: Pubkey, owner: Pubkey) -> Account {
+    let (_, bump) = Pubkey::find_program_address(&[b"session", authority.as_ref()], &magicplace::ID);
+    let session = BaselineSessionAccount {
+        main_address,
+        authority,
+        cooldown_counter: 3,
+        last_place_timestamp: 1_700_000_000,
+        bump,
+    };
+    let mut data = SessionAccount::DISCRIMINATOR.to_vec();
+    session.serialize(&mut data).unwrap();
+    Account { lamports: Rent::default().minimum_balance(data.len()), data, owner, ..Account::default() }
+}
+
+fn close_baseline_session_ix(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::CloseBaselineSession { session: session_pda(&authority), authority }
+            .to_account_metas(None),
+        data: magicplace::instruction::CloseBaselineSession {}.data(),
+    }
+}
+
+fn undelegate_baseline_session_ix(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::UndelegateBaselineSession {
+            session: session_pda(&authority),
+            authority,
+            magic_program: MAGIC_PROGRAM_ID,
+            magic_context: MAGIC_CONTEXT_ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::UndelegateBaselineSession {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn migrate_session_leaves_current_sessions_alone() {
+    let mut world = World::new().await;
+    let (owner, session_key) = (world.owner.insecure_clone(), world.session_key.insecure_clone());
+    let address = session_pda(&session_key.pubkey());
+    let before = world.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+
+    let result =
+        send(&mut world.ctx, &[migrate_session_ix(session_key.pubkey(), session_key.pubkey())], &[&session_key]).await;
+    assert_pixel_error(result, PixelError::InvalidAuth);
+
+    send(&mut world.ctx, &[migrate_session_ix(owner.pubkey(), session_key.pubkey())], &[&owner]).await.unwrap();
+    let after = world.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(after.data, before.data);
+    assert_eq!(after.lamports, before.lamports);
+}
+
+#[tokio::test]
+async fn migrate_session_rejects_delegated_sessions() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let address = session_pda(&session_key.pubkey());
+    let mut delegated = world.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    delegated.owner = DELEGATION_PROGRAM_ID;
+    world.ctx.set_account(&address, &delegated.into());
+
+    let owner = world.owner.insecure_clone();
+    let result = send(&mut world.ctx, &[migrate_session_ix(owner.pubkey(), session_key.pubkey())], &[&owner]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::ConstraintOwner);
+}
+
+#[tokio::test]
+async fn baseline_sessions_are_migrated_by_their_main_wallet() {
+    let mut world = World::new().await;
+    let (owner, stranger) = (world.owner.insecure_clone(), world.session_key.insecure_clone());
+    let key = Keypair::new();
+    world.ctx.set_account(&key.pubkey(), &funded_account().into());
+    let address = session_pda(&key.pubkey());
+    world.ctx.set_account(&address, &baseline_session_account(key.pubkey(), owner.pubkey(), magicplace::ID).into());
+
+    // The baseline authorization could be replayed, so only the main wallet can vouch for the key
+    let result = send(&mut world.ctx, &[migrate_session_ix(stranger.pubkey(), key.pubkey())], &[&stranger]).await;
+    assert_pixel_error(result, PixelError::InvalidAuth);
+
+    send(&mut world.ctx, &[migrate_session_ix(owner.pubkey(), key.pubkey())], &[&owner]).await.unwrap();
+    let account = world.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), 8 + SessionAccount::INIT_SPACE);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+    let session: SessionAccount = fetch(&mut world.ctx, address).await;
+    assert_eq!((session.main_address, session.authority), (owner.pubkey(), key.pubkey()));
+    assert_eq!((session.auth_nonce, session.expires_at, session.revoked), (0, 0, false));
+    assert_eq!(session.version, SessionAccount::VERSION);
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner.pubkey())).await;
+    assert_eq!(profile.sessions, vec![stranger.pubkey(), key.pubkey()]);
+
+    // The migrated key paints like a freshly registered one
+    let paint = place_pixel_ix(key.pubkey(), owner.pubkey(), SHARD, 90, 180, 4);
+    send(&mut world.ctx, &[paint], &[&key]).await.unwrap();
+    assert_eq!(fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await.pixels[0], 4);
+}
+
+#[tokio::test]
+async fn baseline_sessions_are_closed_with_a_refund_to_their_key() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let payer = world.ctx.payer.insecure_clone();
+    let retired = Keypair::new();
+    let baseline = baseline_session_account(retired.pubkey(), Pubkey::new_unique(), magicplace::ID);
+    assert_eq!(baseline.data.len(), BASELINE_SESSION_ACCOUNT_LEN);
+    assert_eq!(BASELINE_SESSION_ACCOUNT_LEN, 82);
+    let rent = baseline.lamports;
+    world.ctx.set_account(&session_pda(&retired.pubkey()), &baseline.into());
+
+    // Current sessions are not touched
+    let result = send(&mut world.ctx, &[close_baseline_session_ix(session_key.pubkey())], &[&session_key]).await;
+    assert_pixel_error(result, PixelError::NotBaselineSession);
+
+    send(&mut world.ctx, &[close_baseline_session_ix(retired.pubkey())], &[&payer, &retired]).await.unwrap();
+    assert!(world.ctx.banks_client.get_account(session_pda(&retired.pubkey())).await.unwrap().is_none());
+    assert_eq!(world.ctx.banks_client.get_balance(retired.pubkey()).await.unwrap(), rent);
+}
+
+#[tokio::test]
+async fn delegated_baseline_sessions_come_back_to_be_closed() {
+    let mut world = World::new().await;
+    let retired = Keypair::new();
+    world.ctx.set_account(&retired.pubkey(), &funded_account().into());
+    let address = session_pda(&retired.pubkey());
+    let baseline = baseline_session_account(retired.pubkey(), Pubkey::new_unique(), DELEGATION_PROGRAM_ID);
+    world.ctx.set_account(&address, &baseline.into());
+
+    let result = send(&mut world.ctx, &[close_baseline_session_ix(retired.pubkey())], &[&retired]).await;
+    assert_pixel_error(result, PixelError::NotBaselineSession);
+
+    clone_into_er(&mut world.ctx, address).await;
+    send(&mut world.ctx, &[undelegate_baseline_session_ix(retired.pubkey())], &[&retired]).await.unwrap();
+    assert_eq!(last_scheduled_commit(&mut world.ctx).await, (SCHEDULE_COMMIT_AND_UNDELEGATE, vec![address]));
+
+    commit_to_base(&mut world.ctx, address).await;
+    let seeds = vec![b"session".to_vec(), retired.pubkey().to_bytes().to_vec()];
+    send(&mut world.ctx, &[finalize_undelegation_ix(address, retired.pubkey(), seeds)], &[&retired]).await.unwrap();
+    refresh_blockhash(&mut world.ctx).await;
+    send(&mut world.ctx, &[close_baseline_session_ix(retired.pubkey())], &[&retired]).await.unwrap();
+    assert!(world.ctx.banks_client.get_account(address).await.unwrap().is_none());
+}
@@ -0,0 +1,318 @@
+//! Pixel placement rules
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use magicplace::{
+    BulkPixel, CheckedBulkPixel, CheckedPixel, ConflictPolicy, GlobalPixel, PixelConflict, PixelError, PixelRun, ShardDeed,
+    WalletProfile,
+};
+use solana_sdk::signature::Signer;
+
+fn pixel(px: u32, py: u32, color: u8) -> GlobalPixel {
+    GlobalPixel { px, py, color }
+}
+
+/// Shard to the right of `SHARD`, owned by someone else
+const NEIGHBOUR: (u16, u16) = (SHARD.0 + 1, SHARD.1);
+
+async fn world_with_neighbour() -> World {
+    let mut world = World::new().await;
+    world.add_shard(NEIGHBOUR.0, NEIGHBOUR.1, Pubkey::new_unique());
+    world
+}
+
+#[tokio::test]
+async fn cross_shard_bulk_applies_rules_per_shard() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    // x = 179 is the last column of SHARD, x = 180 the first of NEIGHBOUR
+    let pixels = vec![pixel(179, 185, 3), pixel(180, 185, 4), pixel(181, 186, 5)];
+    let ix = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[SHARD, NEIGHBOUR], pixels);
+    send(&mut world.ctx, &[ix], &[&session_key]).await.unwrap();
+
+    let own = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(own.pixels[5 * 90 + 89], 3);
+    assert_eq!({ own.foreign_pixels }, 0);
+
+    let foreign = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(foreign.pixels[5 * 90], 4);
+    assert_eq!(foreign.pixels[6 * 90 + 1], 5);
+    assert_eq!({ foreign.foreign_pixels }, 2);
+
+    // Only the pixels on the shard the painter does not own cost cooldown
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 2);
+}
+
+#[tokio::test]
+async fn cross_shard_bulk_is_atomic() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    // The second pixel lands in a shard that was not passed
+    let pixels = vec![pixel(179, 185, 3), pixel(180, 185, 4)];
+    let ix = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[SHARD], pixels);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::ShardMismatch);
+
+    let own = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(own.pixels[5 * 90 + 89], 0);
+}
+
+#[tokio::test]
+async fn cross_shard_bulk_rejects_bad_shard_accounts() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let pixels = || vec![pixel(179, 185, 3)];
+
+    let duplicate = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[SHARD, SHARD], pixels());
+    assert_pixel_error(send(&mut world.ctx, &[duplicate], &[&session_key]).await, PixelError::DuplicateShard);
+
+    // A shard paired with another shard's deed
+    let mut mismatched = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[SHARD], pixels());
+    mismatched.accounts[6].pubkey = deed_pda(NEIGHBOUR.0, NEIGHBOUR.1);
+    assert_pixel_error(send(&mut world.ctx, &[mismatched], &[&session_key]).await, PixelError::InvalidShardAccount);
+
+    let mut deed: ShardDeed = fetch(&mut world.ctx, deed_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    deed.frozen = true;
+    world.set(deed_pda(NEIGHBOUR.0, NEIGHBOUR.1), &deed);
+    let frozen = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[SHARD, NEIGHBOUR], pixels());
+    assert_pixel_error(send(&mut world.ctx, &[frozen], &[&session_key]).await, PixelError::ShardFrozen);
+}
+
+#[tokio::test]
+async fn cross_shard_bulk_charges_cooldown_once_for_all_shards() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let third: (u16, u16) = (NEIGHBOUR.0 + 1, NEIGHBOUR.1);
+    world.add_shard(third.0, third.1, Pubkey::new_unique());
+
+    let mut profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    profile.cooldown_counter = 59;
+    world.set(profile_pda(&owner), &profile);
+
+    // One foreign pixel on each of two shards would take the counter past the limit of 60
+    let pixels = vec![pixel(180, 185, 4), pixel(270, 185, 4)];
+    let ix = place_pixels_cross_shard_ix(session_key.pubkey(), owner, &[NEIGHBOUR, third], pixels);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::BulkExceedsCooldown);
+}
+
+fn fill_rect(shard: (u16, u16), x: u8, y: u8, width: u8, height: u8, color: u8) -> magicplace::instruction::FillRect {
+    magicplace::instruction::FillRect { shard_x: shard.0, shard_y: shard.1, x, y, width, height, color }
+}
+
+fn run(row: u8, start: u8, length: u8, color: u8) -> PixelRun {
+    PixelRun { row, start, length, color }
+}
+
+#[tokio::test]
+async fn fill_rect_charges_only_changed_pixels() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    let first = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, fill_rect(NEIGHBOUR, 0, 0, 2, 2, 4));
+    send(&mut world.ctx, &[first], &[&session_key]).await.unwrap();
+
+    // Overlaps the first fill in four pixels that already have the color
+    let second = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, fill_rect(NEIGHBOUR, 0, 0, 3, 2, 4));
+    send(&mut world.ctx, &[second], &[&session_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(&shard.pixels[..3], &[4, 4, 4]);
+    assert_eq!(&shard.pixels[90..93], &[4, 4, 4]);
+    assert_eq!(shard.pixels[3], 0);
+    assert_eq!({ shard.foreign_pixels }, 6);
+
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 6);
+}
+
+#[tokio::test]
+async fn owner_fills_whole_shard_without_cooldown() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    let own = paint_ix(session_key.pubkey(), owner, SHARD, fill_rect(SHARD, 0, 0, 90, 90, 9));
+    send(&mut world.ctx, &[own], &[&session_key]).await.unwrap();
+    let shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert!(shard.pixels.iter().all(|&pixel| pixel == 9));
+
+    // The same fill on someone else's shard exceeds any cooldown budget
+    let foreign = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, fill_rect(NEIGHBOUR, 0, 0, 90, 90, 9));
+    assert_pixel_error(send(&mut world.ctx, &[foreign], &[&session_key]).await, PixelError::BulkExceedsCooldown);
+
+    let out_of_shard = paint_ix(session_key.pubkey(), owner, SHARD, fill_rect(SHARD, 80, 0, 11, 1, 9));
+    assert_pixel_error(send(&mut world.ctx, &[out_of_shard], &[&session_key]).await, PixelError::InvalidPixelCoord);
+}
+
+#[tokio::test]
+async fn pixel_runs_paint_rows() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let runs = |runs| magicplace::instruction::PlacePixelRuns { shard_x: NEIGHBOUR.0, shard_y: NEIGHBOUR.1, runs };
+
+    let ix = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, runs(vec![run(0, 10, 5, 2), run(3, 0, 2, 7), run(0, 12, 1, 2)]));
+    send(&mut world.ctx, &[ix], &[&session_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(&shard.pixels[9..16], &[0, 2, 2, 2, 2, 2, 0]);
+    assert_eq!(&shard.pixels[270..273], &[7, 7, 0]);
+    // The last run repaints a pixel of the first with the same color
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 7);
+
+    let empty = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, runs(vec![]));
+    assert_pixel_error(send(&mut world.ctx, &[empty], &[&session_key]).await, PixelError::EmptyBulkPixels);
+
+    let overflow = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, runs(vec![run(0, 85, 6, 2)]));
+    assert_pixel_error(send(&mut world.ctx, &[overflow], &[&session_key]).await, PixelError::InvalidPixelCoord);
+
+    let blank = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, runs(vec![run(0, 0, 1, 0)]));
+    assert_pixel_error(send(&mut world.ctx, &[blank], &[&session_key]).await, PixelError::InvalidColor);
+}
+
+#[tokio::test]
+async fn edge_shards_only_paint_on_the_canvas() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    // The last shard column covers global x 524,250..524,340, of which only 38 pixels are on the canvas
+    let edge = (5825, 0);
+    world.add_shard(edge.0, edge.1, owner);
+    let bulk = |pixels| magicplace::instruction::PlacePixelsBulk { shard_x: edge.0, shard_y: edge.1, pixels };
+    let runs = |runs| magicplace::instruction::PlacePixelRuns { shard_x: edge.0, shard_y: edge.1, runs };
+
+    let inside = vec![
+        paint_ix(session_key.pubkey(), owner, edge, bulk(vec![BulkPixel { local_x: 37, local_y: 89, color: 3 }])),
+        paint_ix(session_key.pubkey(), owner, edge, fill_rect(edge, 0, 0, 38, 2, 4)),
+        paint_ix(session_key.pubkey(), owner, edge, runs(vec![run(5, 30, 8, 5)])),
+    ];
+    send(&mut world.ctx, &inside, &[&session_key]).await.unwrap();
+    let shard = fetch_shard(&mut world.ctx, shard_pda(edge.0, edge.1)).await;
+    assert_eq!(shard.pixels[89 * 90 + 37], 3);
+    assert_eq!((shard.pixels[37], shard.pixels[90 + 37], shard.pixels[38]), (4, 4, 0));
+    assert_eq!(&shard.pixels[5 * 90 + 29..5 * 90 + 39], &[0, 5, 5, 5, 5, 5, 5, 5, 5, 0]);
+
+    let off_canvas = [
+        paint_ix(session_key.pubkey(), owner, edge, bulk(vec![BulkPixel { local_x: 38, local_y: 0, color: 3 }])),
+        paint_ix(session_key.pubkey(), owner, edge, fill_rect(edge, 30, 10, 9, 1, 4)),
+        paint_ix(session_key.pubkey(), owner, edge, runs(vec![run(6, 37, 2, 5)])),
+    ];
+    for ix in off_canvas {
+        assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::InvalidPixelCoord);
+    }
+}
+
+fn checked(local_x: u8, local_y: u8, color: u8, expected_color: Option<u8>) -> CheckedBulkPixel {
+    CheckedBulkPixel { local_x, local_y, color, expected_color }
+}
+
+#[tokio::test]
+async fn checked_bulk_skips_conflicts() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    // Someone painted (0, 0) since the client last looked
+    let fresh_edit = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, fill_rect(NEIGHBOUR, 0, 0, 1, 1, 5));
+    send(&mut world.ctx, &[fresh_edit], &[&session_key]).await.unwrap();
+
+    let pixels = vec![checked(0, 0, 2, Some(0)), checked(1, 0, 2, Some(0)), checked(2, 0, 3, None)];
+    let data = magicplace::instruction::PlacePixelsBulkChecked {
+        shard_x: NEIGHBOUR.0,
+        shard_y: NEIGHBOUR.1,
+        pixels,
+        on_conflict: ConflictPolicy::Skip,
+    };
+    let ix = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, data);
+    send(&mut world.ctx, &[ix], &[&session_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(&shard.pixels[..3], &[5, 2, 3]);
+    // One pixel for the fresh edit, two for the placed pixels
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 3);
+}
+
+#[tokio::test]
+async fn checked_bulk_can_fail_on_conflict() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    let data = magicplace::instruction::PlacePixelsBulkChecked {
+        shard_x: NEIGHBOUR.0,
+        shard_y: NEIGHBOUR.1,
+        pixels: vec![checked(0, 0, 2, Some(0)), checked(1, 0, 2, Some(7))],
+        on_conflict: ConflictPolicy::Fail,
+    };
+    let ix = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, data);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::PixelConflict);
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(shard.pixels[0], 0);
+}
+
+#[tokio::test]
+async fn checked_single_pixel_compares_before_painting() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let place = |color, expected_color, on_conflict| {
+        let pixel = CheckedPixel { px: 180, py: 180, color, expected_color };
+        let data = magicplace::instruction::PlacePixelChecked { shard_x: NEIGHBOUR.0, shard_y: NEIGHBOUR.1, pixel, on_conflict };
+        paint_ix(session_key.pubkey(), owner, NEIGHBOUR, data)
+    };
+
+    send(&mut world.ctx, &[place(6, Some(0), ConflictPolicy::Fail)], &[&session_key]).await.unwrap();
+
+    // The pixel is now 6, so expecting 0 again conflicts
+    send(&mut world.ctx, &[place(7, Some(0), ConflictPolicy::Skip)], &[&session_key]).await.unwrap();
+    let result = send(&mut world.ctx, &[place(7, Some(0), ConflictPolicy::Fail)], &[&session_key]).await;
+    assert_pixel_error(result, PixelError::PixelConflict);
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(NEIGHBOUR.0, NEIGHBOUR.1)).await;
+    assert_eq!(shard.pixels[0], 6);
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner)).await;
+    assert_eq!(profile.cooldown_counter, 1);
+}
+
+#[tokio::test]
+async fn checked_placement_returns_conflicts() {
+    let mut world = world_with_neighbour().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+
+    let fresh_edit = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, fill_rect(NEIGHBOUR, 0, 0, 1, 1, 5));
+    send(&mut world.ctx, &[fresh_edit], &[&session_key]).await.unwrap();
+
+    let data = magicplace::instruction::PlacePixelsBulkChecked {
+        shard_x: NEIGHBOUR.0,
+        shard_y: NEIGHBOUR.1,
+        pixels: vec![checked(1, 0, 2, Some(0)), checked(0, 0, 2, Some(0))],
+        on_conflict: ConflictPolicy::Skip,
+    };
+    let ix = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, data);
+    let conflicts: Vec<PixelConflict> = send_for_return(&mut world.ctx, &[ix], &[&session_key]).await;
+    assert_eq!(conflicts, vec![PixelConflict { index: 1, current_color: 5 }]);
+
+    let pixel = CheckedPixel { px: 180, py: 180, color: 6, expected_color: Some(0) };
+    let data = magicplace::instruction::PlacePixelChecked {
+        shard_x: NEIGHBOUR.0,
+        shard_y: NEIGHBOUR.1,
+        pixel,
+        on_conflict: ConflictPolicy::Skip,
+    };
+    let ix = paint_ix(session_key.pubkey(), owner, NEIGHBOUR, data);
+    let conflicts: Vec<PixelConflict> = send_for_return(&mut world.ctx, &[ix], &[&session_key]).await;
+    assert_eq!(conflicts, vec![PixelConflict { index: 0, current_color: 5 }]);
+}
@@ -0,0 +1,104 @@
+//! get_pixel and get_region
+
+mod common;
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use magicplace::{PixelError, PixelRead, RegionRead, BASELINE_SHARD_ACCOUNT_LEN};
+use solana_sdk::signature::Signer;
+
+fn read_ix((shard_x, shard_y): (u16, u16), data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::GetPixel { shard: shard_pda(shard_x, shard_y) }.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn get_pixel(px: u32, py: u32) -> Instruction {
+    read_ix(SHARD, magicplace::instruction::GetPixel { shard_x: SHARD.0, shard_y: SHARD.1, px, py })
+}
+
+fn get_region(x: u8, y: u8, width: u8, height: u8) -> Instruction {
+    let data = magicplace::instruction::GetRegion { shard_x: SHARD.0, shard_y: SHARD.1, x, y, width, height };
+    read_ix(SHARD, data)
+}
+
+/// Paint a 2x2 square at local (1, 1) of `SHARD` with colors 1-4
+async fn painted_world() -> World {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let owner = world.owner.pubkey();
+    let (base_x, base_y) = (SHARD.0 as u32 * 90, SHARD.1 as u32 * 90);
+    let paint: Vec<_> = [(1, 1, 1), (2, 1, 2), (1, 2, 3), (2, 2, 4)]
+        .into_iter()
+        .map(|(x, y, color)| place_pixel_ix(session_key.pubkey(), owner, SHARD, base_x + x, base_y + y, color))
+        .collect();
+    send(&mut world.ctx, &paint, &[&session_key]).await.unwrap();
+    world
+}
+
+#[tokio::test]
+async fn reads_validate_coordinates() {
+    let mut world = World::new().await;
+
+    send(&mut world.ctx, &[get_pixel(90, 180)], &[]).await.unwrap();
+    send(&mut world.ctx, &[get_region(0, 0, 32, 30)], &[]).await.unwrap();
+
+    // (0, 0) lies in shard (0, 0), not SHARD
+    assert_pixel_error(send(&mut world.ctx, &[get_pixel(0, 0)], &[]).await, PixelError::ShardMismatch);
+    assert_pixel_error(send(&mut world.ctx, &[get_region(80, 0, 11, 1)], &[]).await, PixelError::InvalidPixelCoord);
+    assert_pixel_error(send(&mut world.ctx, &[get_region(0, 0, 0, 1)], &[]).await, PixelError::InvalidPixelCoord);
+    assert_pixel_error(send(&mut world.ctx, &[get_region(0, 0, 31, 31)], &[]).await, PixelError::RegionTooLarge);
+}
+
+#[tokio::test]
+async fn reads_return_colors_and_creator() {
+    let mut world = painted_world().await;
+    let creator = world.owner.pubkey();
+
+    let pixel: PixelRead = send_for_return(&mut world.ctx, &[get_pixel(92, 181)], &[]).await;
+    assert_eq!(pixel, PixelRead { color: 2, creator });
+
+    let region: RegionRead = send_for_return(&mut world.ctx, &[get_region(0, 1, 3, 2)], &[]).await;
+    assert_eq!(region, RegionRead { creator, colors: vec![0, 1, 2, 0, 3, 4] });
+
+    // A full-size read fits in the return data
+    let region: RegionRead = send_for_return(&mut world.ctx, &[get_region(0, 0, 32, 30)], &[]).await;
+    assert_eq!(region.colors.len(), 960);
+    assert_ne!(region.creator, Pubkey::default());
+}
+
+#[tokio::test]
+async fn baseline_shards_are_painted_and_read_until_migrated() {
+    let mut world = World::new().await;
+    let (owner, session_key) = (world.owner.pubkey(), world.session_key.insecure_clone());
+    let (painter, painter_wallet) = add_painter(&mut world);
+    let mut pixels = vec![0u8; 90 * 90];
+    pixels[90] = 7;
+    let lamports = Rent::default().minimum_balance(BASELINE_SHARD_ACCOUNT_LEN);
+    let address = shard_pda(SHARD.0, SHARD.1);
+    world.ctx.set_account(&address, &baseline_shard_account(SHARD, owner, &pixels, lamports).into());
+
+    let paint = place_pixel_ix(session_key.pubkey(), owner, SHARD, 91, 181, 3);
+    send(&mut world.ctx, &[paint], &[&session_key]).await.unwrap();
+    let paint = place_pixel_ix(painter.pubkey(), painter_wallet, SHARD, 92, 181, 5);
+    send(&mut world.ctx, &[paint], &[&painter]).await.unwrap();
+
+    let pixel: PixelRead = send_for_return(&mut world.ctx, &[get_pixel(91, 181)], &[]).await;
+    assert_eq!(pixel, PixelRead { color: 3, creator: owner });
+    let region: RegionRead = send_for_return(&mut world.ctx, &[get_region(0, 1, 4, 1)], &[]).await;
+    assert_eq!(region, RegionRead { creator: owner, colors: vec![7, 3, 5, 0] });
+    let account = world.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), BASELINE_SHARD_ACCOUNT_LEN);
+
+    // The paint survives the migration; the baseline had nowhere to count the foreign pixel
+    send(&mut world.ctx, &[migrate_shard_ix(session_key.pubkey(), SHARD)], &[&session_key]).await.unwrap();
+    refresh_blockhash(&mut world.ctx).await;
+    let region: RegionRead = send_for_return(&mut world.ctx, &[get_region(0, 1, 4, 1)], &[]).await;
+    assert_eq!(region, RegionRead { creator: owner, colors: vec![7, 3, 5, 0] });
+    let shard = fetch_shard(&mut world.ctx, address).await;
+    assert_eq!((shard.foreign_pixels, shard.last_foreign_paint), (0, 0));
+}
@@ -0,0 +1,201 @@
+//! Session keys: Ed25519-authorized registration, expiry, revocation and closing
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{instruction::Instruction, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use magicplace::{PixelError, SessionAccount, WalletProfile};
+use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Clock time the session tests run at
+const NOW: i64 = 1_000_000;
+
+fn initialize_user_ix(authority: Pubkey, main_wallet: Pubkey, signature: [u8; 64], nonce: u64, expires_at: i64) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::InitializeUser {
+            user: session_pda(&authority),
+            profile: profile_pda(&main_wallet),
+            authority,
+            system_program: system_program::ID,
+            instructions_sysvar: sysvar::instructions::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::InitializeUser { main_wallet, signature, nonce, expires_at }.data(),
+    }
+}
+
+/// An Ed25519 verify instruction over `message` signed by `signer`, and the signature
+fn ed25519_ix(signer: &Keypair, message: &str) -> (Instruction, [u8; 64]) {
+    let signature: [u8; 64] = signer.sign_message(message.as_bytes()).into();
+    let ix = new_ed25519_instruction_with_signature(message.as_bytes(), &signature, &signer.pubkey().to_bytes());
+    (ix, signature)
+}
+
+/// The verify and initialize_user instructions registering `authority` for `main_wallet`
+fn authorize(main_wallet: &Keypair, authority: Pubkey, nonce: u64, expires_at: i64) -> Vec<Instruction> {
+    let message = magicplace::session_auth_message(&authority, nonce, expires_at);
+    let (verify, signature) = ed25519_ix(main_wallet, &message);
+    vec![verify, initialize_user_ix(authority, main_wallet.pubkey(), signature, nonce, expires_at)]
+}
+
+/// A funded key to register as a session
+fn new_session_key(world: &mut World) -> Keypair {
+    let key = Keypair::new();
+    world.ctx.set_account(&key.pubkey(), &funded_account().into());
+    key
+}
+
+fn revoke_session_ix(main_wallet: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::RevokeSession {
+            session: session_pda(&authority),
+            profile: profile_pda(&main_wallet),
+            main_wallet,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::RevokeSession { authority }.data(),
+    }
+}
+
+fn set_session_expiry_ix(main_wallet: Pubkey, authority: Pubkey, expires_at: i64) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::SetSessionExpiry { session: session_pda(&authority), main_wallet }
+            .to_account_metas(None),
+        data: magicplace::instruction::SetSessionExpiry { _authority: authority, expires_at }.data(),
+    }
+}
+
+fn close_session_ix(main_wallet: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::CloseSession { session: session_pda(&authority), main_wallet }
+            .to_account_metas(None),
+        data: magicplace::instruction::CloseSession { authority }.data(),
+    }
+}
+
+#[tokio::test]
+async fn a_signed_authorization_registers_a_session_that_can_paint() {
+    let mut world = World::new().await;
+    world.warp_to(NOW).await;
+    let wallet = Keypair::new();
+    let session_key = new_session_key(&mut world);
+
+    send(&mut world.ctx, &authorize(&wallet, session_key.pubkey(), 7, NOW + 600), &[&session_key]).await.unwrap();
+
+    let session: SessionAccount = fetch(&mut world.ctx, session_pda(&session_key.pubkey())).await;
+    assert_eq!((session.main_address, session.authority), (wallet.pubkey(), session_key.pubkey()));
+    assert_eq!((session.auth_nonce, session.revoked), (7, false));
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&wallet.pubkey())).await;
+    assert_eq!(profile.main_address, wallet.pubkey());
+    assert_eq!((profile.sessions.clone(), profile.last_auth_nonce), (vec![session_key.pubkey()], 7));
+
+    let place = place_pixel_ix(session_key.pubkey(), wallet.pubkey(), SHARD, 90, 180, 3);
+    send(&mut world.ctx, &[place], &[&session_key]).await.unwrap();
+    let shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(shard.pixels[0], 3);
+
+    // A replayed or older nonce can't register another key; a newer one can
+    let second_key = new_session_key(&mut world);
+    let replay = authorize(&wallet, second_key.pubkey(), 7, NOW + 600);
+    assert_pixel_error_at(send(&mut world.ctx, &replay, &[&second_key]).await, 1, PixelError::AuthNonceReused);
+    send(&mut world.ctx, &authorize(&wallet, second_key.pubkey(), 8, NOW + 600), &[&second_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&wallet.pubkey())).await;
+    assert_eq!(profile.sessions, vec![session_key.pubkey(), second_key.pubkey()]);
+    assert_eq!(profile.last_auth_nonce, 8);
+}
+
+#[tokio::test]
+async fn initialize_user_rejects_bad_authorizations() {
+    let mut world = World::new().await;
+    world.warp_to(NOW).await;
+    let (wallet, impostor) = (Keypair::new(), Keypair::new());
+    let session_key = new_session_key(&mut world);
+    let authority = session_key.pubkey();
+    let message = |nonce| magicplace::session_auth_message(&authority, nonce, NOW + 600);
+
+    let expired = authorize(&wallet, authority, 1, NOW - 1);
+    assert_pixel_error_at(send(&mut world.ctx, &expired, &[&session_key]).await, 1, PixelError::AuthExpired);
+
+    // No verify instruction in front, or one that verifies nothing
+    let alone = || initialize_user_ix(authority, wallet.pubkey(), [0; 64], 1, NOW + 600);
+    assert_pixel_error(send(&mut world.ctx, &[alone()], &[&session_key]).await, PixelError::InvalidEd25519Instruction);
+    let empty = Instruction { program_id: solana_sdk::ed25519_program::ID, accounts: vec![], data: vec![0, 0] };
+    let result = send(&mut world.ctx, &[empty, alone()], &[&session_key]).await;
+    assert_pixel_error_at(result, 1, PixelError::InvalidEd25519Instruction);
+
+    let (verify, signature) = ed25519_ix(&impostor, &message(1));
+    let signed_by_impostor = initialize_user_ix(authority, wallet.pubkey(), signature, 1, NOW + 600);
+    let result = send(&mut world.ctx, &[verify, signed_by_impostor], &[&session_key]).await;
+    assert_pixel_error_at(result, 1, PixelError::AuthPubkeyMismatch);
+
+    let (verify, _) = ed25519_ix(&wallet, &message(1));
+    let (_, other_signature) = ed25519_ix(&wallet, &message(2));
+    let wrong_signature = initialize_user_ix(authority, wallet.pubkey(), other_signature, 1, NOW + 600);
+    let result = send(&mut world.ctx, &[verify, wrong_signature], &[&session_key]).await;
+    assert_pixel_error_at(result, 1, PixelError::AuthSignatureMismatch);
+
+    // A valid signature over the authorization for another nonce
+    let (verify, signature) = ed25519_ix(&wallet, &message(2));
+    let wrong_message = initialize_user_ix(authority, wallet.pubkey(), signature, 3, NOW + 600);
+    let result = send(&mut world.ctx, &[verify, wrong_message], &[&session_key]).await;
+    assert_pixel_error_at(result, 1, PixelError::AuthMessageMismatch);
+
+    assert!(world.ctx.banks_client.get_account(session_pda(&authority)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn a_wallet_holds_a_limited_number_of_sessions() {
+    let mut world = World::new().await;
+    world.warp_to(NOW).await;
+    let owner = world.owner.insecure_clone();
+    let mut profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner.pubkey())).await;
+    profile.sessions.extend([Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()]);
+    world.set(profile_pda(&owner.pubkey()), &profile);
+
+    let session_key = new_session_key(&mut world);
+    let fifth = authorize(&owner, session_key.pubkey(), 2, NOW + 600);
+    assert_pixel_error_at(send(&mut world.ctx, &fifth, &[&session_key]).await, 1, PixelError::TooManySessions);
+
+    // Revoking a key frees its slot
+    let revoke = revoke_session_ix(owner.pubkey(), world.session_key.pubkey());
+    send(&mut world.ctx, &[revoke], &[&owner]).await.unwrap();
+    send(&mut world.ctx, &authorize(&owner, session_key.pubkey(), 3, NOW + 600), &[&session_key]).await.unwrap();
+    let profile: WalletProfile = fetch(&mut world.ctx, profile_pda(&owner.pubkey())).await;
+    assert_eq!(profile.sessions.len(), 4);
+    assert_eq!(profile.sessions.last(), Some(&session_key.pubkey()));
+}
+
+#[tokio::test]
+async fn expired_and_revoked_sessions_stop_painting() {
+    let mut world = World::new().await;
+    let (owner, session_key) = (world.owner.insecure_clone(), world.session_key.insecure_clone());
+    let paint = |px| place_pixel_ix(session_key.pubkey(), owner.pubkey(), SHARD, px, 180, 3);
+
+    let expiry = set_session_expiry_ix(owner.pubkey(), session_key.pubkey(), NOW);
+    send(&mut world.ctx, &[expiry], &[&owner]).await.unwrap();
+    world.warp_to(NOW - 1).await;
+    send(&mut world.ctx, &[paint(90)], &[&session_key]).await.unwrap();
+    world.warp_to(NOW).await;
+    assert_pixel_error(send(&mut world.ctx, &[paint(91)], &[&session_key]).await, PixelError::SessionExpired);
+
+    let never = set_session_expiry_ix(owner.pubkey(), session_key.pubkey(), 0);
+    send(&mut world.ctx, &[never], &[&owner]).await.unwrap();
+    send(&mut world.ctx, &[paint(92)], &[&session_key]).await.unwrap();
+
+    // Only a revoked session may be closed, and a revoked one can't paint
+    let close = || close_session_ix(owner.pubkey(), session_key.pubkey());
+    assert_pixel_error(send(&mut world.ctx, &[close()], &[&owner]).await, PixelError::SessionNotRevoked);
+    send(&mut world.ctx, &[revoke_session_ix(owner.pubkey(), session_key.pubkey())], &[&owner]).await.unwrap();
+    assert_pixel_error(send(&mut world.ctx, &[paint(93)], &[&session_key]).await, PixelError::SessionRevoked);
+
+    refresh_blockhash(&mut world.ctx).await;
+    send(&mut world.ctx, &[close()], &[&owner]).await.unwrap();
+    assert!(world.ctx.banks_client.get_account(session_pda(&session_key.pubkey())).await.unwrap().is_none());
+}
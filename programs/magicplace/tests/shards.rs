@@ -0,0 +1,411 @@
+//! Shard creation and lifecycle
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::prelude::Rent;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use common::*;
+use magicplace::{
+    BulkPixel, ConfigParams, GameConfig, PixelError, PixelShard, ShardCoord, ShardDeed, ShardEarnings,
+    BASELINE_SHARD_ACCOUNT_LEN,
+};
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+
+/// A shard nobody has claimed in `World`
+const FRESH: (u16, u16) = (7, 7);
+
+fn claim_and_paint_ix(authority: Pubkey, (shard_x, shard_y): (u16, u16), pixels: Vec<BulkPixel>) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::ClaimAndPaint {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            session: session_pda(&authority),
+            config: config_pda(),
+            treasury: treasury_pda(),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::ClaimAndPaint { shard_x, shard_y, pixels }.data(),
+    }
+}
+
+fn initialize_shards_ix(authority: Pubkey, shards: &[(u16, u16)]) -> Instruction {
+    let mut accounts = magicplace::accounts::InitializeShards {
+        session: session_pda(&authority),
+        config: config_pda(),
+        treasury: treasury_pda(),
+        authority,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    for &(shard_x, shard_y) in shards {
+        accounts.push(AccountMeta::new(shard_pda(shard_x, shard_y), false));
+        accounts.push(AccountMeta::new(deed_pda(shard_x, shard_y), false));
+    }
+    let shards = shards.iter().map(|&(shard_x, shard_y)| ShardCoord { shard_x, shard_y }).collect();
+    Instruction {
+        program_id: magicplace::ID,
+        accounts,
+        data: magicplace::instruction::InitializeShards { shards }.data(),
+    }
+}
+
+fn close_shard_ix(owner: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::CloseShard {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            listing: listing_pda(shard_x, shard_y),
+            earnings: earnings_pda(shard_x, shard_y),
+            config: config_pda(),
+            owner,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::CloseShard { shard_x, shard_y }.data(),
+    }
+}
+
+fn initialize_deed_ix(payer: Pubkey, (shard_x, shard_y): (u16, u16)) -> Instruction {
+    Instruction {
+        program_id: magicplace::ID,
+        accounts: magicplace::accounts::InitializeDeed {
+            shard: shard_pda(shard_x, shard_y),
+            deed: deed_pda(shard_x, shard_y),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: magicplace::instruction::InitializeDeed { shard_x, shard_y }.data(),
+    }
+}
+
+/// Every shard of the `width` x `height` block with its top-left corner at `(x, y)`
+fn block((x, y): (u16, u16), width: u16, height: u16) -> Vec<(u16, u16)> {
+    (y..y + height).flat_map(|sy| (x..x + width).map(move |sx| (sx, sy))).collect()
+}
+
+fn bulk(local_x: u8, local_y: u8, color: u8) -> BulkPixel {
+    BulkPixel { local_x, local_y, color }
+}
+
+#[tokio::test]
+async fn claim_and_paint_on_an_owned_shard_only_paints() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { shard_fee_lamports: 1_000_000, ..config.params };
+    world.set(config_pda(), &config);
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+
+    let ix = claim_and_paint_ix(session_key.pubkey(), SHARD, vec![bulk(0, 0, 1), bulk(89, 89, 2)]);
+    send(&mut world.ctx, &[ix], &[&session_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!((shard.pixels[0], shard.pixels[90 * 90 - 1]), (1, 2));
+    assert_eq!(shard.creator, world.owner.pubkey());
+    assert_eq!({ shard.foreign_pixels }, 0);
+    // No fee once the shard exists
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before);
+}
+
+#[tokio::test]
+async fn claim_and_paint_rejects_shards_claimed_by_others() {
+    let mut world = World::new().await;
+    let (rival_key, _) = add_painter(&mut world);
+
+    let ix = claim_and_paint_ix(rival_key.pubkey(), SHARD, vec![bulk(0, 0, 1)]);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&rival_key]).await, PixelError::ShardAlreadyClaimed);
+
+    let session_key = world.session_key.insecure_clone();
+    let ix = claim_and_paint_ix(session_key.pubkey(), SHARD, vec![]);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::EmptyBulkPixels);
+
+    let mut deed: ShardDeed = fetch(&mut world.ctx, deed_pda(SHARD.0, SHARD.1)).await;
+    deed.frozen = true;
+    world.set(deed_pda(SHARD.0, SHARD.1), &deed);
+    let ix = claim_and_paint_ix(session_key.pubkey(), SHARD, vec![bulk(0, 0, 1)]);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::ShardFrozen);
+}
+
+#[tokio::test]
+async fn first_painter_claims_a_fresh_shard() {
+    let mut world = World::new().await;
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { shard_fee_lamports: 1_000_000, ..config.params };
+    world.set(config_pda(), &config);
+    let (first_key, first_wallet) = add_painter(&mut world);
+    let (rival_key, _) = add_painter(&mut world);
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+
+    let ix = claim_and_paint_ix(first_key.pubkey(), FRESH, vec![bulk(3, 4, 9)]);
+    send(&mut world.ctx, &[ix], &[&first_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(FRESH.0, FRESH.1)).await;
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(FRESH.0, FRESH.1)).await;
+    assert_eq!((shard.shard_x, shard.shard_y), FRESH);
+    assert_eq!(shard.pixels[4 * 90 + 3], 9);
+    assert_eq!(shard.creator, first_wallet);
+    assert_eq!(deed.owner, first_wallet);
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before + 1_000_000);
+
+    // Whoever lands second has lost the race
+    let ix = claim_and_paint_ix(rival_key.pubkey(), FRESH, vec![bulk(3, 4, 1)]);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&rival_key]).await, PixelError::ShardAlreadyClaimed);
+}
+
+#[tokio::test]
+async fn initialize_shard_creates_a_blank_shard_and_its_deed() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { shard_fee_lamports: 1_000_000, ..config.params };
+    world.set(config_pda(), &config);
+    world.warp_to(1_000_000).await;
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+
+    send(&mut world.ctx, &[initialize_shard_ix(session_key.pubkey(), FRESH)], &[&session_key]).await.unwrap();
+
+    let shard = fetch_shard(&mut world.ctx, shard_pda(FRESH.0, FRESH.1)).await;
+    assert_eq!((shard.shard_x, shard.shard_y, shard.version), (FRESH.0, FRESH.1, PixelShard::VERSION));
+    assert_eq!((shard.creator, shard.last_activity), (world.owner.pubkey(), 1_000_000));
+    assert!(shard.pixels.iter().all(|&pixel| pixel == 0));
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(FRESH.0, FRESH.1)).await;
+    assert_eq!((deed.owner, deed.frozen), (world.owner.pubkey(), false));
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before + 1_000_000);
+
+    let off_grid = initialize_shard_ix(session_key.pubkey(), (5826, 0));
+    assert_pixel_error(send(&mut world.ctx, &[off_grid], &[&session_key]).await, PixelError::InvalidShardCoord);
+    // The shard and deed accounts already exist
+    assert!(send(&mut world.ctx, &[initialize_shard_ix(session_key.pubkey(), SHARD)], &[&session_key]).await.is_err());
+}
+
+#[test]
+fn batch_fee_is_discounted_by_volume() {
+    let params = ConfigParams { shard_fee_lamports: 1_000_000, batch_discount_bps: 100, ..ConfigParams::default() };
+    assert_eq!(params.batch_shard_fee(1), 1_000_000);
+    assert_eq!(params.batch_shard_fee(4), 3_880_000);
+    // Capped at 25% from the 26th shard on
+    assert_eq!(params.batch_shard_fee(26), 19_500_000);
+    assert_eq!(params.batch_shard_fee(100), 75_000_000);
+
+    let free = ConfigParams { shard_fee_lamports: 0, ..params };
+    assert_eq!(free.batch_shard_fee(16), 0);
+}
+
+#[tokio::test]
+async fn initialize_shards_validates_the_block() {
+    let mut world = World::new().await;
+    let session_key = world.session_key.insecure_clone();
+    let authority = session_key.pubkey();
+    let mut expect = async |ix: Instruction, error: PixelError| {
+        assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, error);
+    };
+
+    expect(initialize_shards_ix(authority, &[]), PixelError::EmptyShardList).await;
+    // Too many accounts for one legacy transaction, but the count is checked first
+    let mut too_many = initialize_shards_ix(authority, &block(FRESH, 17, 1));
+    too_many.accounts.truncate(5);
+    expect(too_many, PixelError::TooManyShards).await;
+    expect(initialize_shards_ix(authority, &[FRESH, (8, 7), FRESH]), PixelError::DuplicateShard).await;
+    // Touching only at a corner is not contiguous
+    expect(initialize_shards_ix(authority, &[FRESH, (8, 8)]), PixelError::ShardsNotContiguous).await;
+    expect(initialize_shards_ix(authority, &[(7, 7), (7, 9), (8, 9)]), PixelError::ShardsNotContiguous).await;
+    expect(initialize_shards_ix(authority, &[(5825, 0), (5826, 0)]), PixelError::InvalidShardCoord).await;
+
+    let mut missing_deed = initialize_shards_ix(authority, &[FRESH, (8, 7)]);
+    missing_deed.accounts.pop();
+    expect(missing_deed, PixelError::InvalidShardAccount).await;
+}
+
+#[tokio::test]
+async fn initialize_shards_creates_a_block_at_a_discount() {
+    let mut world = World::new().await;
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { shard_fee_lamports: 1_000_000, batch_discount_bps: 100, ..config.params };
+    world.set(config_pda(), &config);
+    let session_key = world.session_key.insecure_clone();
+    let treasury_before = world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap();
+
+    let shards = block(FRESH, 2, 2);
+    let ix = initialize_shards_ix(session_key.pubkey(), &shards);
+    send(&mut world.ctx, &[ix], &[&session_key]).await.unwrap();
+
+    for (shard_x, shard_y) in shards {
+        let shard = fetch_shard(&mut world.ctx, shard_pda(shard_x, shard_y)).await;
+        let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(shard_x, shard_y)).await;
+        assert_eq!((shard.shard_x, shard.shard_y), (shard_x, shard_y));
+        assert_eq!(shard.pixels.len(), 90 * 90);
+        assert_eq!(shard.creator, world.owner.pubkey());
+        assert_eq!(deed.owner, world.owner.pubkey());
+    }
+    assert_eq!(world.ctx.banks_client.get_balance(treasury_pda()).await.unwrap(), treasury_before + 3_880_000);
+
+    // An existing shard anywhere in the block fails the whole batch
+    let ix = initialize_shards_ix(session_key.pubkey(), &[(FRESH.0 + 2, FRESH.1), (FRESH.0 + 1, FRESH.1)]);
+    assert_pixel_error(send(&mut world.ctx, &[ix], &[&session_key]).await, PixelError::ShardAlreadyClaimed);
+}
+
+#[tokio::test]
+async fn close_shard_refunds_shard_deed_and_earnings() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let (shard_x, shard_y) = SHARD;
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"earnings", &shard_x.to_le_bytes(), &shard_y.to_le_bytes()], &magicplace::ID);
+    world.set(earnings_pda(shard_x, shard_y), &ShardEarnings { shard_x, shard_y, claimed_pixels: 10, bump });
+
+    let closed = [shard_pda(shard_x, shard_y), deed_pda(shard_x, shard_y), earnings_pda(shard_x, shard_y)];
+    let mut rent = 0;
+    for address in closed {
+        rent += world.ctx.banks_client.get_balance(address).await.unwrap();
+    }
+    let before = world.ctx.banks_client.get_balance(owner.pubkey()).await.unwrap();
+
+    let stranger = world.session_key.insecure_clone();
+    let result = send(&mut world.ctx, &[close_shard_ix(stranger.pubkey(), SHARD)], &[&stranger]).await;
+    assert_pixel_error(result, PixelError::NotShardOwner);
+
+    send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await.unwrap();
+    let signature_fee = 5000;
+    assert_eq!(world.ctx.banks_client.get_balance(owner.pubkey()).await.unwrap(), before + rent - signature_fee);
+    for address in closed {
+        assert!(world.ctx.banks_client.get_account(address).await.unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+async fn close_shard_requires_an_undelegated_unlisted_shard() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+
+    let listing = Account { lamports: 1_000_000, data: vec![1], owner: magicplace::ID, ..Account::default() };
+    world.ctx.set_account(&listing_pda(SHARD.0, SHARD.1), &listing.into());
+    let result = send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), SHARD)], &[&owner]).await;
+    assert_pixel_error(result, PixelError::ShardListed);
+
+    world.add_shard(FRESH.0, FRESH.1, owner.pubkey());
+    commit_to_base(&mut world.ctx, shard_pda(FRESH.0, FRESH.1)).await;
+    let result = send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), FRESH)], &[&owner]).await;
+    assert_anchor_error(result, anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+}
+
+#[tokio::test]
+async fn close_shard_waits_out_foreign_paint() {
+    let mut world = World::new().await;
+    let owner = world.owner.insecure_clone();
+    let mut config: GameConfig = fetch(&mut world.ctx, config_pda()).await;
+    config.params = ConfigParams { close_quiet_secs: 3600, ..config.params };
+    world.set(config_pda(), &config);
+    world.warp_to(10_000).await;
+
+    let blank = (FRESH.0 + 1, FRESH.1);
+    for (shard_x, shard_y) in [FRESH, blank] {
+        world.add_shard(shard_x, shard_y, owner.pubkey());
+        let mut shard = fetch_shard(&mut world.ctx, shard_pda(shard_x, shard_y)).await;
+        shard.pixels[0] = if (shard_x, shard_y) == blank { 0 } else { 5 };
+        shard.last_foreign_paint = 9_000;
+        world.set_shard(&shard);
+    }
+
+    let result = send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), FRESH)], &[&owner]).await;
+    assert_pixel_error(result, PixelError::ShardNotQuiet);
+    // A blank shard can always be closed
+    send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), blank)], &[&owner]).await.unwrap();
+
+    world.warp_to(12_600).await;
+    send(&mut world.ctx, &[close_shard_ix(owner.pubkey(), FRESH), close_shard_ix(owner.pubkey(), SHARD)], &[&owner])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn migrate_shard_rewrites_the_baseline_layout() {
+    let mut world = World::new().await;
+    let (owner, session_key) = (world.owner.pubkey(), world.session_key.insecure_clone());
+    let mut pixels = vec![0u8; 90 * 90];
+    pixels[90] = 7;
+    let lamports = Rent::default().minimum_balance(BASELINE_SHARD_ACCOUNT_LEN);
+    let baseline = baseline_shard_account(SHARD, owner, &pixels, lamports);
+    // The size deployed shards actually have
+    assert_eq!(BASELINE_SHARD_ACCOUNT_LEN, 8149);
+    assert_eq!(baseline.data.len(), BASELINE_SHARD_ACCOUNT_LEN);
+    world.ctx.set_account(&shard_pda(SHARD.0, SHARD.1), &baseline.into());
+
+    send(&mut world.ctx, &[migrate_shard_ix(session_key.pubkey(), SHARD)], &[&session_key]).await.unwrap();
+    let shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!((shard.shard_x, shard.shard_y, shard.creator), (SHARD.0, SHARD.1, owner));
+    assert_eq!(shard.pixels.as_slice(), pixels.as_slice());
+    // Nothing was tracked before earnings existed
+    assert_eq!((shard.foreign_pixels, shard.last_activity, shard.last_foreign_paint), (0, 0, 0));
+    assert_eq!(shard.version, PixelShard::VERSION);
+
+    // Migrating again is a no-op, and the shard is usable again
+    send(&mut world.ctx, &[migrate_shard_ix(owner, SHARD)], &[&world.owner.insecure_clone()]).await.unwrap();
+    let again = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!(bytemuck::bytes_of(&again), bytemuck::bytes_of(&shard));
+    let place = place_pixel_ix(session_key.pubkey(), owner, SHARD, 91, 180, 2);
+    send(&mut world.ctx, &[place], &[&session_key]).await.unwrap();
+    let shard = fetch_shard(&mut world.ctx, shard_pda(SHARD.0, SHARD.1)).await;
+    assert_eq!((shard.pixels[1], shard.pixels[90]), (2, 7));
+}
+
+#[tokio::test]
+async fn migrate_shard_rejects_delegated_and_malformed_shards() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+    let lamports = Rent::default().minimum_balance(8 + std::mem::size_of::<PixelShard>());
+
+    let mut delegated = baseline_shard_account(SHARD, world.owner.pubkey(), &[0; 90 * 90], lamports);
+    delegated.owner = DELEGATION_PROGRAM_ID;
+    world.ctx.set_account(&shard_pda(SHARD.0, SHARD.1), &delegated.into());
+    let result = send(&mut world.ctx, &[migrate_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await;
+    assert_pixel_error(result, PixelError::InvalidShardAccount);
+
+    let truncated = baseline_shard_account(FRESH, world.owner.pubkey(), &[0; 100], lamports);
+    world.ctx.set_account(&shard_pda(FRESH.0, FRESH.1), &truncated.into());
+    let result = send(&mut world.ctx, &[migrate_shard_ix(payer.pubkey(), FRESH)], &[&payer]).await;
+    assert_pixel_error(result, PixelError::InvalidShardAccount);
+}
+
+#[tokio::test]
+async fn migrate_shard_tops_up_rent() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+    let lamports = Rent::default().minimum_balance(BASELINE_SHARD_ACCOUNT_LEN);
+    let baseline = baseline_shard_account(SHARD, world.owner.pubkey(), &[0; 90 * 90], lamports);
+    world.ctx.set_account(&shard_pda(SHARD.0, SHARD.1), &baseline.into());
+
+    send(&mut world.ctx, &[migrate_shard_ix(payer.pubkey(), SHARD)], &[&payer]).await.unwrap();
+    let account = world.ctx.banks_client.get_account(shard_pda(SHARD.0, SHARD.1)).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), 8 + std::mem::size_of::<PixelShard>());
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+}
+
+#[tokio::test]
+async fn shards_from_before_deeds_get_one_for_their_creator() {
+    let mut world = World::new().await;
+    let payer = world.session_key.insecure_clone();
+    let creator = Pubkey::new_unique();
+    let lamports = Rent::default().minimum_balance(8 + std::mem::size_of::<PixelShard>());
+    let baseline = baseline_shard_account(FRESH, creator, &[0; 90 * 90], lamports);
+    world.ctx.set_account(&shard_pda(FRESH.0, FRESH.1), &baseline.into());
+    world.warp_to(1_000_000).await;
+
+    // The shard doesn't have to be migrated first
+    send(&mut world.ctx, &[initialize_deed_ix(payer.pubkey(), FRESH)], &[&payer]).await.unwrap();
+    let deed: ShardDeed = fetch(&mut world.ctx, deed_pda(FRESH.0, FRESH.1)).await;
+    assert_eq!((deed.shard_x, deed.shard_y, deed.owner), (FRESH.0, FRESH.1, creator));
+    assert_eq!((deed.last_activity, deed.frozen, deed.pass_price_lamports), (1_000_000, false, 0));
+
+    // One deed per shard, and none for a shard that doesn't exist
+    refresh_blockhash(&mut world.ctx).await;
+    assert!(send(&mut world.ctx, &[initialize_deed_ix(payer.pubkey(), FRESH)], &[&payer]).await.is_err());
+    let result = send(&mut world.ctx, &[initialize_deed_ix(payer.pubkey(), (8, 8))], &[&payer]).await;
+    assert_pixel_error(result, PixelError::InvalidShardAccount);
+}
@@ -6,13 +6,23 @@ set -e
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
 
+# cluster to build for: localnet, devnet or mainnet (default devnet)
+# the program binds this tag into session authorizations, so it must match the cluster it is deployed to
+CLUSTER="${1:-devnet}"
+case "$CLUSTER" in
+    localnet) FEATURES=() ;;
+    devnet|mainnet) FEATURES=(-- --features "$CLUSTER") ;;
+    *) echo "Unknown cluster: $CLUSTER (expected localnet, devnet or mainnet)" >&2; exit 1 ;;
+esac
+
 # sync program address
 anchor keys sync
 
-# compile the program
-anchor build
+# compile the program (the IDL is generated with the same features)
+anchor build "${FEATURES[@]}"
 
 # Copy program type and IDL to app/src/idl/magicplace.ts
+# The app reads the cluster tag from the IDL, so it always matches this build
 
 # type is at target/types/magicplace.ts
 # IDL is at target/idl/magicplace.json
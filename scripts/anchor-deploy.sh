@@ -1,4 +1,14 @@
 #!/bin/bash
 
-# run tests
-anchor deploy --provider.cluster devnet
\ No newline at end of file
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+# cluster to deploy to: devnet or mainnet (default devnet)
+CLUSTER="${1:-devnet}"
+
+# build with the matching cluster feature so session authorizations carry the right tag
+"$SCRIPT_DIR/anchor-build.sh" "$CLUSTER"
+
+# deploy
+anchor deploy --provider.cluster "$CLUSTER"
@@ -30,6 +30,10 @@ describe("magicplace", () => {
   // Test constants
   const SHARD_SEED = Buffer.from("shard");
   const SESSION_SEED = Buffer.from("session");
+  const PROFILE_SEED = Buffer.from("wallet");
+  const DEED_SEED = Buffer.from("deed");
+  const CONFIG_SEED = Buffer.from("config");
+  const TREASURY_SEED = Buffer.from("treasury");
   const SHARD_DIMENSION = 128;
 
   // Helper to derive shard PDA
@@ -45,28 +49,68 @@ describe("magicplace", () => {
     return pda;
   }
 
-  // Helper to derive session PDA
-  function deriveSessionPDA(mainWallet: PublicKey): PublicKey {
+  // Helper to derive the deed PDA recording who owns a shard
+  function deriveDeedPDA(shardX: number, shardY: number): PublicKey {
+    const shardXBytes = Buffer.alloc(2);
+    shardXBytes.writeUInt16LE(shardX);
+    const shardYBytes = Buffer.alloc(2);
+    shardYBytes.writeUInt16LE(shardY);
+    const [pda] = PublicKey.findProgramAddressSync(
+      [DEED_SEED, shardXBytes, shardYBytes],
+      program.programId
+    );
+    return pda;
+  }
+
+  // Helper to derive session PDA (one per session key)
+  function deriveSessionPDA(sessionKey: PublicKey): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [SESSION_SEED, sessionKey.toBuffer()],
+      program.programId
+    );
+    return pda;
+  }
+
+  // Helper to derive the wallet profile PDA holding a main wallet's cooldown
+  function deriveProfilePDA(mainWallet: PublicKey): PublicKey {
     const [pda] = PublicKey.findProgramAddressSync(
-      [SESSION_SEED, mainWallet.toBuffer()],
+      [PROFILE_SEED, mainWallet.toBuffer()],
       program.programId
     );
     return pda;
   }
 
+  // Global config and the treasury collecting shard fees
+  const [configPDA] = PublicKey.findProgramAddressSync([CONFIG_SEED], program.programId);
+  const [treasuryPDA] = PublicKey.findProgramAddressSync([TREASURY_SEED], program.programId);
+  // Holds the upgrade authority, which is the only key allowed to initialize the config
+  const [programDataPDA] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   // Generate authorization message (must match program)
-  function generateAuthMessage(sessionKey: PublicKey, mainWallet: PublicKey): string {
-    return `Authorize session key: ${sessionKey.toBase58()} for wallet: ${mainWallet.toBase58()} on Magicplace`;
+  function generateAuthMessage(sessionKey: PublicKey, nonce: number, expiresAt: number): string {
+    return [
+      "Magicplace session authorization",
+      `program: ${program.programId.toBase58()}`,
+      `session: ${sessionKey.toBase58()}`,
+      "cluster: localnet",
+      `nonce: ${nonce}`,
+      `expires: ${expiresAt}`,
+    ].join("\n");
   }
 
   // Test shard coordinates
   const testShardX = 0;
   const testShardY = 0;
   const shardPDA = deriveShardPDA(testShardX, testShardY);
+  const deedPDA = deriveDeedPDA(testShardX, testShardY);
 
   // Session key for tests
   let sessionKeypair: Keypair;
   let sessionPDA: PublicKey;
+  const profilePDA = deriveProfilePDA(authority.publicKey);
 
   console.log("Program ID:", program.programId.toString());
   console.log("Test Shard PDA:", shardPDA.toString());
@@ -75,22 +119,37 @@ describe("magicplace", () => {
     const balance = await provider.connection.getBalance(authority.publicKey);
     console.log("Current balance:", balance / LAMPORTS_PER_SOL, "SOL\n");
 
+    // Create the config and treasury once per deployment; the provider wallet deployed the
+    // program, so as its upgrade authority it becomes admin
+    if (!(await provider.connection.getAccountInfo(configPDA))) {
+      const configTx = await program.methods
+        .initializeConfig()
+        .accountsPartial({
+          config: configPDA,
+          treasury: treasuryPDA,
+          programData: programDataPDA,
+          admin: authority.publicKey,
+        })
+        .rpc();
+      console.log(`initializeConfig txHash: ${configTx}`);
+    }
+
     // Generate a session keypair for testing
     sessionKeypair = Keypair.generate();
-    sessionPDA = deriveSessionPDA(authority.publicKey);
+    sessionPDA = deriveSessionPDA(sessionKeypair.publicKey);
     console.log("Session Key:", sessionKeypair.publicKey.toString());
     console.log("Session PDA:", sessionPDA.toString());
 
-    // Fund the session keypair
+    // Fund the session keypair; it pays rent and the platform fee for the test shards
     const fundTx = new anchor.web3.Transaction().add(
       anchor.web3.SystemProgram.transfer({
         fromPubkey: authority.publicKey,
         toPubkey: sessionKeypair.publicKey,
-        lamports: 0.1 * LAMPORTS_PER_SOL,
+        lamports: 0.5 * LAMPORTS_PER_SOL,
       })
     );
     await provider.sendAndConfirm(fundTx);
-    console.log("Funded session key with 0.1 SOL\n");
+    console.log("Funded session key with 0.5 SOL\n");
   });
 
   // ========================================
@@ -103,7 +162,9 @@ describe("magicplace", () => {
 
       // Step 1: Initialize user with Ed25519 signature verification
       // Generate the authorization message
-      const authMessage = generateAuthMessage(sessionKeypair.publicKey, authority.publicKey);
+      const nonce = Date.now();
+      const expiresAt = Math.floor(Date.now() / 1000) + 300;
+      const authMessage = generateAuthMessage(sessionKeypair.publicKey, nonce, expiresAt);
       const messageBytes = new TextEncoder().encode(authMessage);
 
       // Sign the message with the main wallet
@@ -124,10 +185,17 @@ describe("magicplace", () => {
 
       // Build the program instruction (no delegation in this step)
       const programIx = await program.methods
-        .initializeUser(authority.publicKey, Array.from(signature) as number[])
-        .accounts({
+        .initializeUser(
+          authority.publicKey,
+          Array.from(signature) as number[],
+          new anchor.BN(nonce),
+          new anchor.BN(expiresAt)
+        )
+        .accountsPartial({
+          user: sessionPDA,
+          profile: profilePDA,
           authority: sessionKeypair.publicKey,
-          // instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .instruction();
 
@@ -162,7 +230,11 @@ describe("magicplace", () => {
       const sessionAccount = await program.account.sessionAccount.fetch(sessionPDA);
       expect(sessionAccount.mainAddress.toBase58()).to.equal(authority.publicKey.toBase58());
       expect(sessionAccount.authority.toBase58()).to.equal(sessionKeypair.publicKey.toBase58());
-      expect(sessionAccount.ownedShards.toNumber()).to.equal(0);
+
+      // Cooldown is tracked per main wallet, which now lists this session key
+      const profile = await program.account.walletProfile.fetch(profilePDA);
+      expect(profile.mainAddress.toBase58()).to.equal(authority.publicKey.toBase58());
+      expect(profile.sessions.map((key) => key.toBase58())).to.include(sessionKeypair.publicKey.toBase58());
 
       // Step 2: Immediately delegate user to ER
       const remainingAccounts = providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
@@ -197,6 +269,26 @@ describe("magicplace", () => {
 
       console.log(`delegateUser txHash: ${delegateTxHash}`);
 
+      // Step 3: Delegate the wallet profile too, since placements charge cooldown to it
+      const delegateProfileTx = await program.methods
+        .delegateProfile(authority.publicKey)
+        .accounts({
+          authority: sessionKeypair.publicKey,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+
+      delegateProfileTx.feePayer = sessionKeypair.publicKey;
+      delegateProfileTx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      delegateProfileTx.sign(sessionKeypair);
+
+      const delegateProfileTxHash = await provider.connection.sendRawTransaction(delegateProfileTx.serialize(), {
+        skipPreflight: true,
+      });
+      await provider.connection.confirmTransaction(delegateProfileTxHash, "confirmed");
+
+      console.log(`delegateProfile txHash: ${delegateProfileTxHash}`);
+
       // Wait for delegation to propagate
       await new Promise((resolve) => setTimeout(resolve, 2000));
     });
@@ -210,12 +302,20 @@ describe("magicplace", () => {
     it("initializes a shard and delegates to ER", async () => {
       const start = Date.now();
 
-      // Step 1: Initialize the shard (no delegation)
+      // Step 1: Initialize the shard (no delegation), paying the configured platform fee
+      const config = await program.account.gameConfig.fetch(configPDA);
+      const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
       const initTx = await program.methods
         .initializeShard(testShardX, testShardY)
-        .accounts({
-          authority: authority.publicKey,
+        .accountsPartial({
+          shard: shardPDA,
+          deed: deedPDA,
+          session: sessionPDA,
+          config: configPDA,
+          treasury: treasuryPDA,
+          authority: sessionKeypair.publicKey,
         })
+        .signers([sessionKeypair])
         .rpc();
 
       console.log(`initializeShard txHash: ${initTx}`);
@@ -227,6 +327,13 @@ describe("magicplace", () => {
       expect(shardAccount.creator.toBase58()).to.equal(authority.publicKey.toBase58());
       expect(shardAccount.pixels.length).to.equal(8192); // 128*128/2 bytes (4-bit packed)
 
+      // Ownership is recorded on a deed that stays on the base layer
+      const deed = await program.account.shardDeed.fetch(deedPDA);
+      expect(deed.owner.toBase58()).to.equal(authority.publicKey.toBase58());
+
+      const treasuryAfter = await provider.connection.getBalance(treasuryPDA);
+      expect(treasuryAfter - treasuryBefore).to.equal(config.params.shardFeeLamports.toNumber());
+
       // Step 2: Delegate the shard to ER
       const remainingAccounts = providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
         providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1") ||
@@ -263,9 +370,15 @@ describe("magicplace", () => {
       // Step 1: Initialize the shard
       const initTx = await program.methods
         .initializeShard(shardX, shardY)
-        .accounts({
-          authority: authority.publicKey,
+        .accountsPartial({
+          shard: shard2PDA,
+          deed: deriveDeedPDA(shardX, shardY),
+          session: sessionPDA,
+          config: configPDA,
+          treasury: treasuryPDA,
+          authority: sessionKeypair.publicKey,
         })
+        .signers([sessionKeypair])
         .rpc();
 
       console.log(`initializeShard (1,0) txHash: ${initTx}`);
@@ -317,8 +430,13 @@ describe("magicplace", () => {
       // Build transaction
       let tx = await program.methods
         .placePixel(testShardX, testShardY, px, py, color)
-        .accounts({
-          signer: authority.publicKey,
+        .accountsPartial({
+          shard: shardPDA,
+          deed: deedPDA,
+          session: sessionPDA,
+          profile: profilePDA,
+          config: configPDA,
+          signer: sessionKeypair.publicKey,
         })
         .transaction();
 
@@ -326,6 +444,7 @@ describe("magicplace", () => {
       tx.feePayer = providerEphemeralRollup.wallet.publicKey;
       tx.recentBlockhash = (await providerEphemeralRollup.connection.getLatestBlockhash()).blockhash;
       tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+      tx.partialSign(sessionKeypair);
 
       const txHash = await providerEphemeralRollup.connection.sendRawTransaction(tx.serialize(), {
         skipPreflight: true,
@@ -347,14 +466,20 @@ describe("magicplace", () => {
       for (const { px, py, color } of pixels) {
         let tx = await program.methods
           .placePixel(testShardX, testShardY, px, py, color)
-          .accounts({
-            signer: authority.publicKey,
+          .accountsPartial({
+            shard: shardPDA,
+            deed: deedPDA,
+            session: sessionPDA,
+            profile: profilePDA,
+            config: configPDA,
+            signer: sessionKeypair.publicKey,
           })
           .transaction();
 
         tx.feePayer = providerEphemeralRollup.wallet.publicKey;
         tx.recentBlockhash = (await providerEphemeralRollup.connection.getLatestBlockhash()).blockhash;
         tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+        tx.partialSign(sessionKeypair);
 
         const txHash = await providerEphemeralRollup.connection.sendRawTransaction(tx.serialize(), {
           skipPreflight: true,
@@ -375,14 +500,20 @@ describe("magicplace", () => {
 
       let tx = await program.methods
         .erasePixel(testShardX, testShardY, px, py)
-        .accounts({
-          signer: authority.publicKey,
+        .accountsPartial({
+          shard: shardPDA,
+          deed: deedPDA,
+          session: sessionPDA,
+          profile: profilePDA,
+          config: configPDA,
+          signer: sessionKeypair.publicKey,
         })
         .transaction();
 
       tx.feePayer = providerEphemeralRollup.wallet.publicKey;
       tx.recentBlockhash = (await providerEphemeralRollup.connection.getLatestBlockhash()).blockhash;
       tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+      tx.partialSign(sessionKeypair);
 
       const txHash = await providerEphemeralRollup.connection.sendRawTransaction(tx.serialize(), {
         skipPreflight: true,
@@ -432,14 +563,20 @@ describe("magicplace", () => {
       try {
         let tx = await program.methods
           .placePixel(testShardX, testShardY, 50, 50, 0) // color 0 is invalid
-          .accounts({
-            signer: authority.publicKey,
+          .accountsPartial({
+            shard: shardPDA,
+            deed: deedPDA,
+            session: sessionPDA,
+            profile: profilePDA,
+            config: configPDA,
+            signer: sessionKeypair.publicKey,
           })
           .transaction();
 
         tx.feePayer = providerEphemeralRollup.wallet.publicKey;
         tx.recentBlockhash = (await providerEphemeralRollup.connection.getLatestBlockhash()).blockhash;
         tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+        tx.partialSign(sessionKeypair);
 
         await providerEphemeralRollup.connection.sendRawTransaction(tx.serialize(), {
           skipPreflight: true,
@@ -456,14 +593,20 @@ describe("magicplace", () => {
       try {
         let tx = await program.methods
           .placePixel(testShardX, testShardY, 50, 50, 16) // color 16 is invalid
-          .accounts({
-            signer: authority.publicKey,
+          .accountsPartial({
+            shard: shardPDA,
+            deed: deedPDA,
+            session: sessionPDA,
+            profile: profilePDA,
+            config: configPDA,
+            signer: sessionKeypair.publicKey,
           })
           .transaction();
 
         tx.feePayer = providerEphemeralRollup.wallet.publicKey;
         tx.recentBlockhash = (await providerEphemeralRollup.connection.getLatestBlockhash()).blockhash;
         tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+        tx.partialSign(sessionKeypair);
 
         await providerEphemeralRollup.connection.sendRawTransaction(tx.serialize(), {
           skipPreflight: true,
@@ -479,9 +622,13 @@ describe("magicplace", () => {
       try {
         await program.methods
           .initializeShard(5000, 0) // 5000 > 4095
-          .accounts({
-            authority: authority.publicKey,
+          .accountsPartial({
+            session: sessionPDA,
+            config: configPDA,
+            treasury: treasuryPDA,
+            authority: sessionKeypair.publicKey,
           })
+          .signers([sessionKeypair])
           .rpc({ skipPreflight: true });
 
         expect.fail("Should have thrown an error");